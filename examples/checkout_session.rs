@@ -1,21 +1,17 @@
 //! Checkout session example
 
-use noah_sdk::apis::configuration::{ApiKey, Configuration};
+use noah_sdk::apis::configuration::Configuration;
 use noah_sdk::apis::{payin_api, payout_api};
 use noah_sdk::models::{CheckoutPayinCryptoPostRequest, CheckoutPayoutFiatPostRequest, LineItem};
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let mut config = Configuration::default();
-    config.base_path = "https://api.sandbox.noah.com/v1".to_string();
-    config.api_key = Some(ApiKey {
-        prefix: None,
-        key: "your-api-key-here".to_string(),
-    });
+    // Reads NOAH_API_KEY and the optional NOAH_ENV, NOAH_BASE_URL, ... settings
+    let config = Configuration::from_env()?;
 
     // Example: Create a crypto payin session
     let crypto_payin = CheckoutPayinCryptoPostRequest {
-        crypto_currency: "USDC".to_string(),
+        crypto_currency: "USDC_TEST".to_string(),
         crypto_amount: "100.0".to_string(),
         return_url: "https://example.com/return".to_string(),
         customer_id: "customer-123".to_string(),
@@ -40,7 +36,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // Example: Create a fiat payout session
     let fiat_payout = CheckoutPayoutFiatPostRequest {
-        crypto_currency: "USDC".to_string(),
+        crypto_currency: "USDC_TEST".to_string(),
         fiat_currency: "USD".to_string(),
         fiat_amount: "100.0".to_string(),
         crypto_authorized_amount: "100.0".to_string(),
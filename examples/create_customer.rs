@@ -1,17 +1,13 @@
 //! Create customer example
 
-use noah_sdk::apis::configuration::{ApiKey, Configuration};
+use noah_sdk::apis::configuration::Configuration;
 use noah_sdk::apis::{onboarding_api, utilities_api};
 use noah_sdk::models::{CustomerInput, FullName, IndividualCustomerInput, StreetAddress};
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let mut config = Configuration::default();
-    config.base_path = "https://api.sandbox.noah.com/v1".to_string();
-    config.api_key = Some(ApiKey {
-        prefix: None,
-        key: "your-api-key-here".to_string(),
-    });
+    // Reads NOAH_API_KEY and the optional NOAH_ENV, NOAH_BASE_URL, ... settings
+    let config = Configuration::from_env()?;
 
     // Create an individual customer
     let customer_input = CustomerInput::Individual(Box::new(IndividualCustomerInput {
@@ -38,11 +34,11 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let customer_id = "customer-123".to_string();
     onboarding_api::customers_customer_id_put(&config, &customer_id, customer_input, None).await?;
 
-    println!("Customer created successfully: {}", customer_id);
+    println!("Customer created successfully: {customer_id}");
 
     // Retrieve the customer
     let customer = utilities_api::customers_customer_id_get(&config, &customer_id).await?;
-    println!("Retrieved customer: {:?}", customer);
+    println!("Retrieved customer: {customer:?}");
 
     Ok(())
 }
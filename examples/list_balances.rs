@@ -1,17 +1,12 @@
 //! List balances example
 
-use noah_sdk::apis::configuration::{ApiKey, Configuration};
+use noah_sdk::apis::configuration::Configuration;
 use noah_sdk::apis::utilities_api;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    // Create configuration
-    let mut config = Configuration::default();
-    config.base_path = "https://api.sandbox.noah.com/v1".to_string();
-    config.api_key = Some(ApiKey {
-        prefix: None,
-        key: "your-api-key-here".to_string(),
-    });
+    // Reads NOAH_API_KEY and the optional NOAH_ENV, NOAH_BASE_URL, ... settings
+    let config = Configuration::from_env()?;
 
     println!("Base URL: {}", config.base_path);
     println!("Requesting balances...");
@@ -1,57 +1,46 @@
 //! Sell transaction example
 
-use noah_sdk::apis::configuration::{ApiKey, Configuration};
-use noah_sdk::apis::payout_api;
-use noah_sdk::models::{PrepareSellRequest, SellRequest};
+use std::time::Duration;
+
+use noah_sdk::apis::configuration::Configuration;
+use noah_sdk::polling::PollPolicy;
+use noah_sdk::sell::SellFlow;
+use noah_sdk::Decimal;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let mut config = Configuration::default();
-    config.base_path = "https://api.sandbox.noah.com/v1".to_string();
-    config.api_key = Some(ApiKey {
-        prefix: None,
-        key: "your-api-key-here".to_string(),
-    });
+    // Reads NOAH_API_KEY and the optional NOAH_ENV, NOAH_BASE_URL, ... settings
+    let config = Configuration::from_env()?;
 
-    // Step 1: Prepare the sell transaction
-    let prepare_request = PrepareSellRequest {
-        channel_id: uuid::Uuid::parse_str("00000000-0000-0000-0000-000000000000")?,
-        payment_method_id: None,
-        crypto_currency: "USDC".to_string(),
-        customer_id: Some("customer-123".to_string()),
-        fiat_amount: "100.0".to_string(),
-        form: None,
-        delayed_sell: None,
-    };
+    let flow = SellFlow::new(&config, "USDC_TEST", "100.0")
+        .fiat_currency("EUR")
+        .customer_id("customer-123")
+        .form_field("IBAN", "DE89370400440532013000")
+        .max_fee(Decimal::new(2, 0))
+        .max_slippage_bps(100)
+        .max_quote_age(Duration::from_secs(30))
+        .external_id("external-789")
+        .wait_for_settlement(PollPolicy::new(Duration::from_secs(600)));
 
-    let prepare_response =
-        payout_api::transactions_sell_prepare_post(&config, prepare_request, None).await?;
-    println!("Prepared sell transaction:");
-    println!("  Total Fee: {}", prepare_response.total_fee);
+    // Step 1: Pick the cheapest EUR channel, fill its form and prepare the sell
+    let quote = flow.quote().await?;
+    println!("Quoted through channel {}:", quote.channel().id);
+    println!("  Total Fee: {}", quote.total_fee());
     println!(
-        "  Crypto Amount Estimate: {}",
-        prepare_response.crypto_amount_estimate
+        "  Rate: {} ({} bps slippage)",
+        quote.rate(),
+        quote.slippage_bps(quote.quoted_rate())
     );
     println!(
         "  Crypto Authorized Amount: {}",
-        prepare_response.crypto_authorized_amount
+        quote.prepared().crypto_authorized_amount
     );
-    println!("  Form Session ID: {}", prepare_response.form_session_id);
-
-    // Step 2: Execute the sell transaction
-    let sell_request = SellRequest {
-        crypto_currency: "USDC".to_string(),
-        fiat_amount: "100.0".to_string(),
-        crypto_authorized_amount: prepare_response.crypto_authorized_amount,
-        form_session_id: prepare_response.form_session_id,
-        nonce: uuid::Uuid::new_v4().to_string(),
-        external_id: Some("external-789".to_string()),
-    };
 
-    let sell_response = payout_api::transactions_sell_post(&config, sell_request, None).await?;
+    // Step 2: Execute the quote unless it went stale, and wait for it to settle
+    let receipt = flow.execute(quote).await?;
     println!("Sell transaction created!");
-    println!("  Transaction ID: {}", sell_response.transaction.id);
-    println!("  Status: {:?}", sell_response.transaction.status);
+    println!("  Transaction ID: {}", receipt.transaction.id);
+    println!("  Status: {}", receipt.transaction.status);
 
     Ok(())
 }
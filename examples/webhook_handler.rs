@@ -12,7 +12,7 @@ use serde_json::Value;
 fn handle_webhook(body: &str, signature: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
     // In production, verify the signature here
     if let Some(sig) = signature {
-        println!("Received signature: {}", sig);
+        println!("Received signature: {sig}");
         // TODO: Implement signature verification
     }
 
@@ -25,9 +25,9 @@ fn handle_webhook(body: &str, signature: Option<&str>) -> Result<(), Box<dyn std
     let occurred = payload["Occurred"].as_str().unwrap_or("Unknown");
 
     println!("Webhook received:");
-    println!("  Event Type: {}", event_type);
-    println!("  Event Version: {}", event_version);
-    println!("  Occurred: {}", occurred);
+    println!("  Event Type: {event_type}");
+    println!("  Event Version: {event_version}");
+    println!("  Occurred: {occurred}");
 
     // Handle different event types
     match event_type {
@@ -48,7 +48,7 @@ fn handle_webhook(body: &str, signature: Option<&str>) -> Result<(), Box<dyn std
             // Handle customer event
         }
         _ => {
-            println!("  Unknown event type: {}", event_type);
+            println!("  Unknown event type: {event_type}");
         }
     }
 
@@ -1,38 +1,675 @@
+use std::collections::HashMap;
+use std::error;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+
+use serde::Deserialize;
+
+use crate::redact::Secret;
+
 #[derive(Debug, Clone)]
 pub struct Configuration {
     pub base_path: String,
     pub user_agent: Option<String>,
     pub client: reqwest::Client,
     pub basic_auth: Option<BasicAuth>,
-    pub oauth_access_token: Option<String>,
-    pub bearer_access_token: Option<String>,
+    pub oauth_access_token: Option<Secret<String>>,
+    pub bearer_access_token: Option<Secret<String>>,
     pub api_key: Option<ApiKey>,
+    pub retry: RetryPolicy,
+    pub request_options: RequestOptions,
+    /// Serves repeated calls to slow-changing endpoints from memory. Off by default.
+    pub cache: Option<std::sync::Arc<crate::cache::ResponseCache>>,
+    /// Holds requests back to stay under rate limits. Off by default.
+    pub rate_limiter: Option<std::sync::Arc<crate::rate_limit::RateLimiter>>,
+    /// Fails calls fast while an endpoint keeps failing. Off by default.
+    pub circuit_breaker: Option<std::sync::Arc<crate::circuit_breaker::CircuitBreaker>>,
+    /// Records or replays HTTP interactions instead of calling the API directly.
+    #[cfg(feature = "test-util")]
+    pub cassette: Option<std::sync::Arc<crate::testing::Cassette>>,
 }
 
-pub type BasicAuth = (String, Option<String>);
+pub type BasicAuth = (String, Option<Secret<String>>);
 
 #[derive(Debug, Clone)]
 pub struct ApiKey {
     pub prefix: Option<String>,
-    pub key: String,
+    pub key: Secret<String>,
 }
 
 impl Configuration {
     pub fn new() -> Configuration {
         Configuration::default()
     }
+
+    /// Returns a default configuration pointed at `environment`.
+    pub fn for_environment(environment: Environment) -> Configuration {
+        Configuration {
+            base_path: environment.base_path(),
+            ..Configuration::default()
+        }
+    }
+
+    pub fn set_environment(&mut self, environment: Environment) {
+        self.base_path = environment.base_path();
+    }
+
+    /// The environment targeted by `base_path`.
+    pub fn environment(&self) -> Environment {
+        Environment::from_base_path(&self.base_path)
+    }
+
+    /// Returns a copy of this configuration that applies `options` to every call made with it.
+    pub fn with_request_options(&self, options: RequestOptions) -> Configuration {
+        Configuration {
+            request_options: options,
+            ..self.clone()
+        }
+    }
+
+    /// Loads a configuration from `NOAH_*` environment variables. See [`Profile::from_env`].
+    pub fn from_env() -> Result<Configuration, ConfigError> {
+        Profile::from_env()?.into_configuration()
+    }
+
+    /// Loads the configuration stored as `profile` in a TOML or JSON file. See
+    /// [`Profile::from_file`].
+    pub fn from_file(path: impl AsRef<Path>, profile: &str) -> Result<Configuration, ConfigError> {
+        Profile::from_file(path, profile)?.into_configuration()
+    }
 }
 
 impl Default for Configuration {
     fn default() -> Self {
         Configuration {
-            base_path: "https://api.sandbox.noah.com/v1".to_owned(),
+            base_path: SANDBOX_BASE_PATH.to_owned(),
             user_agent: Some("OpenAPI-Generator/0.1.3/rust".to_owned()),
             client: reqwest::Client::new(),
             basic_auth: None,
             oauth_access_token: None,
             bearer_access_token: None,
             api_key: None,
+            retry: RetryPolicy::default(),
+            request_options: RequestOptions::default(),
+            cache: None,
+            rate_limiter: None,
+            circuit_breaker: None,
+            #[cfg(feature = "test-util")]
+            cassette: None,
+        }
+    }
+}
+
+/// Per-call overrides for timeouts, the total deadline and extra headers.
+///
+/// Every endpoint takes a `&Configuration`, so options are applied to a single call by passing a
+/// copy made with [`Configuration::with_request_options`]. The copy shares the client, cache and
+/// other handles of the original, and can be reused: the deadline is a budget counted from the
+/// start of each call.
+///
+///
+/// ```no_run
+/// # async fn example(
+/// #     config: &noah_sdk::apis::configuration::Configuration,
+/// # ) -> Result<(), Box<dyn std::error::Error>> {
+/// use std::time::Duration;
+/// use noah_sdk::apis::{configuration::RequestOptions, utilities_api};
+/// use reqwest::header::{HeaderName, HeaderValue};
+///
+/// let options = RequestOptions::new()
+///     .timeout(Duration::from_secs(2))
+///     .deadline(Duration::from_secs(5))
+///     .header(
+///         HeaderName::from_static("x-request-id"),
+///         HeaderValue::from_static("checkout-42"),
+///     );
+/// let channels = utilities_api::channels_sell_get(
+///     &config.with_request_options(options),
+///     "USDC_TEST", Some("US"), None, None, None, None, None, None, None,
+/// )
+/// .await?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// Dropping the future returned by an endpoint cancels the call, including pending retries.
+#[derive(Debug, Clone, Default)]
+pub struct RequestOptions {
+    /// Timeout of each HTTP attempt, overriding the timeout of the `reqwest::Client`.
+    pub timeout: Option<Duration>,
+    /// Time the call, including all retries, may take from when it starts. Once it has passed,
+    /// the call fails with [`Error::DeadlineExceeded`](super::Error::DeadlineExceeded).
+    pub deadline: Option<Duration>,
+    /// Headers added to the request, replacing headers of the same name.
+    pub headers: HeaderMap,
+}
+
+impl RequestOptions {
+    pub fn new() -> RequestOptions {
+        RequestOptions::default()
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> RequestOptions {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn deadline(mut self, budget: Duration) -> RequestOptions {
+        self.deadline = Some(budget);
+        self
+    }
+
+    pub fn header(mut self, name: HeaderName, value: HeaderValue) -> RequestOptions {
+        self.headers.insert(name, value);
+        self
+    }
+}
+
+/// How failed requests are retried.
+///
+/// Connection errors, timeouts and `429`, `500`, `502`, `503` and `504` responses are retried with
+/// exponential backoff. Only requests that are safe to send twice are retried: `GET`s and other
+/// idempotent methods, and requests whose body carries a `Nonce`, which Noah deduplicates. Other
+/// `POST`s are sent once, since a lost response doesn't tell whether Noah acted on them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Retries after the first attempt. `0` disables retrying.
+    pub max_retries: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl RetryPolicy {
+    pub fn new(max_retries: u32) -> RetryPolicy {
+        RetryPolicy {
+            max_retries,
+            ..RetryPolicy::default()
+        }
+    }
+
+    /// Delay before retry number `retry` (starting at 1).
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let factor = 2u32.saturating_pow(retry.saturating_sub(1));
+        self.initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff)
+    }
+
+    /// Whether `req` may be sent again at all.
+    pub(crate) fn may_retry(&self, req: &reqwest::Request) -> bool {
+        if req.method().is_idempotent() {
+            return true;
+        }
+        let body = req.body().and_then(reqwest::Body::as_bytes);
+        body.and_then(|body| serde_json::from_slice::<serde_json::Value>(body).ok())
+            .is_some_and(|body| {
+                body["Nonce"]
+                    .as_str()
+                    .is_some_and(|nonce| !nonce.is_empty())
+            })
+    }
+
+    pub(crate) fn should_retry_status(&self, status: reqwest::StatusCode) -> bool {
+        matches!(status.as_u16(), 429 | 500 | 502 | 503 | 504)
+    }
+
+    pub(crate) fn should_retry_error(&self, error: &reqwest::Error) -> bool {
+        error.is_connect() || error.is_timeout()
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_retries: 0,
+            initial_backoff: Duration::from_millis(200),
+            max_backoff: Duration::from_secs(5),
+        }
+    }
+}
+
+pub const SANDBOX_BASE_PATH: &str = "https://api.sandbox.noah.com/v1";
+pub const PRODUCTION_BASE_PATH: &str = "https://api.noah.com/v1";
+
+/// Noah deployment targeted by a [`Configuration`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Environment {
+    #[default]
+    Sandbox,
+    Production,
+    /// Any other base URL, e.g. a proxy or a local mock server. No environment guards apply.
+    Custom(url::Url),
+}
+
+impl Environment {
+    pub fn base_path(&self) -> String {
+        match self {
+            Environment::Sandbox => SANDBOX_BASE_PATH.to_owned(),
+            Environment::Production => PRODUCTION_BASE_PATH.to_owned(),
+            Environment::Custom(url) => url.as_str().trim_end_matches('/').to_owned(),
+        }
+    }
+
+    /// Infers the environment from a configured base path.
+    pub fn from_base_path(base_path: &str) -> Environment {
+        match base_path.trim_end_matches('/') {
+            SANDBOX_BASE_PATH => Environment::Sandbox,
+            PRODUCTION_BASE_PATH => Environment::Production,
+            // An unparsable base path fails every request anyway; report it as a custom target.
+            other => Environment::Custom(
+                url::Url::parse(other)
+                    .unwrap_or_else(|_| url::Url::parse("about:blank").expect("valid URL")),
+            ),
+        }
+    }
+
+    pub fn is_sandbox(&self) -> bool {
+        matches!(self, Environment::Sandbox)
+    }
+
+    pub fn is_production(&self) -> bool {
+        matches!(self, Environment::Production)
+    }
+
+    /// Rejects operations that only exist in the sandbox when running against production.
+    pub fn check_operation(&self, operation: &str) -> Result<(), EnvironmentError> {
+        if self.is_production() && SANDBOX_ONLY_OPERATIONS.contains(&operation) {
+            return Err(EnvironmentError::SandboxOnlyOperation {
+                operation: operation.to_owned(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Rejects sandbox-only `CryptoCurrency`/`Network` codes in production and production-only
+    /// codes in the sandbox. Values that are not crypto or network codes are ignored.
+    pub fn check_code(&self, field: &str, value: &str) -> Result<(), EnvironmentError> {
+        for (production, sandbox) in CODES.iter().filter(|(p, s)| p != s) {
+            if self.is_production() && value == *sandbox {
+                return Err(EnvironmentError::SandboxCode {
+                    field: field.to_owned(),
+                    value: value.to_owned(),
+                });
+            }
+            if self.is_sandbox() && value == *production {
+                return Err(EnvironmentError::ProductionCode {
+                    field: field.to_owned(),
+                    value: value.to_owned(),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Environment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Environment::Sandbox => write!(f, "sandbox"),
+            Environment::Production => write!(f, "production"),
+            Environment::Custom(url) => write!(f, "{url}"),
+        }
+    }
+}
+
+/// Operations that only exist in the sandbox.
+const SANDBOX_ONLY_OPERATIONS: &[&str] = &["sandbox_fiat_deposit_simulate_post"];
+
+/// `(production, sandbox)` pairs of `CryptoCurrency` and `Network` codes.
+const CODES: &[(&str, &str)] = &[
+    ("BTC", "BTC_TEST"),
+    ("USDC", "USDC_TEST"),
+    ("Bitcoin", "BitcoinTest"),
+    ("Celo", "CeloTestSepolia"),
+    ("FlowEvm", "FlowEvmTest"),
+    ("Gnosis", "GnosisTestChiado"),
+    ("Lightning", "LightningTest"),
+    ("Ethereum", "EthereumTestSepolia"),
+    ("PolygonPos", "PolygonTestAmoy"),
+    ("Solana", "SolanaDevnet"),
+    ("OffNetwork", "OffNetwork"),
+];
+
+/// Request fields and query parameters holding `CryptoCurrency` or `Network` codes.
+pub(crate) const CODE_FIELDS: &[&str] = &[
+    "CryptoCurrency",
+    "Network",
+    "SourceCurrency",
+    "DestinationCurrency",
+];
+
+/// A request was refused because it does not fit the configured [`Environment`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EnvironmentError {
+    SandboxOnlyOperation { operation: String },
+    SandboxCode { field: String, value: String },
+    ProductionCode { field: String, value: String },
+}
+
+impl fmt::Display for EnvironmentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EnvironmentError::SandboxOnlyOperation { operation } => {
+                write!(f, "`{operation}` is only available in the sandbox")
+            }
+            EnvironmentError::SandboxCode { field, value } => {
+                write!(f, "{field} `{value}` is a sandbox-only code")
+            }
+            EnvironmentError::ProductionCode { field, value } => {
+                write!(f, "{field} `{value}` is a production-only code")
+            }
+        }
+    }
+}
+
+impl error::Error for EnvironmentError {}
+
+/// Settings for building a [`Configuration`], loaded from environment variables or a profile file.
+///
+/// | Environment variable | Profile key | |
+/// |---|---|---|
+/// | `NOAH_API_KEY` | `api_key` | API key sent as `X-Api-Key` |
+/// | | `api_key_env` | Name of an environment variable holding the API key |
+/// | `NOAH_ENV` | `environment` | `sandbox` or `production` |
+/// | `NOAH_BASE_URL` | `base_url` | Base URL, for proxies and mock servers |
+/// | `NOAH_USER_AGENT` | `user_agent` | |
+/// | `NOAH_TIMEOUT_SECS` | `timeout_secs` | Total timeout of each HTTP request |
+/// | `NOAH_CONNECT_TIMEOUT_SECS` | `connect_timeout_secs` | |
+/// | `NOAH_MAX_RETRIES` | `max_retries` | See [`RetryPolicy`] |
+/// | `NOAH_RETRY_BACKOFF_MS` | `retry_backoff_ms` | Initial retry backoff |
+///
+/// An API key is required. `NOAH_ENV` and `NOAH_BASE_URL` may both be set only if they agree.
+///
+/// A profile file holds named profiles, e.g. in TOML:
+///
+/// ```toml
+/// [profiles.sandbox]
+/// environment = "sandbox"
+/// api_key = "sandbox-key"
+///
+/// [profiles.prod]
+/// environment = "production"
+/// api_key_env = "NOAH_PROD_API_KEY"
+/// max_retries = 3
+/// ```
+///
+/// JSON files use the same layout: `{"profiles": {"sandbox": {...}, "prod": {...}}}`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Profile {
+    pub api_key: Option<Secret<String>>,
+    pub api_key_env: Option<String>,
+    pub environment: Option<String>,
+    pub base_url: Option<String>,
+    pub user_agent: Option<String>,
+    pub timeout_secs: Option<u64>,
+    pub connect_timeout_secs: Option<u64>,
+    pub max_retries: Option<u32>,
+    pub retry_backoff_ms: Option<u64>,
+    #[serde(skip)]
+    origin: Origin,
+}
+
+/// Where a [`Profile`] was loaded from, used to name settings in errors.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+enum Origin {
+    #[default]
+    Env,
+    File {
+        path: PathBuf,
+        profile: String,
+    },
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ProfileFile {
+    profiles: HashMap<String, Profile>,
+}
+
+impl Profile {
+    /// Reads the `NOAH_*` environment variables of the process.
+    pub fn from_env() -> Result<Profile, ConfigError> {
+        Profile::from_vars(std::env::vars())
+    }
+
+    /// Reads `NOAH_*` settings from `vars`. Other variables and empty values are ignored.
+    pub fn from_vars<K, V>(vars: impl IntoIterator<Item = (K, V)>) -> Result<Profile, ConfigError>
+    where
+        K: AsRef<str>,
+        V: AsRef<str>,
+    {
+        let vars: HashMap<String, String> = vars
+            .into_iter()
+            .filter(|(key, value)| key.as_ref().starts_with("NOAH_") && !value.as_ref().is_empty())
+            .map(|(key, value)| (key.as_ref().to_owned(), value.as_ref().to_owned()))
+            .collect();
+        Ok(Profile {
+            api_key: vars.get("NOAH_API_KEY").cloned().map(Secret::new),
+            api_key_env: None,
+            environment: vars.get("NOAH_ENV").cloned(),
+            base_url: vars.get("NOAH_BASE_URL").cloned(),
+            user_agent: vars.get("NOAH_USER_AGENT").cloned(),
+            timeout_secs: parse_var(&vars, "NOAH_TIMEOUT_SECS")?,
+            connect_timeout_secs: parse_var(&vars, "NOAH_CONNECT_TIMEOUT_SECS")?,
+            max_retries: parse_var(&vars, "NOAH_MAX_RETRIES")?,
+            retry_backoff_ms: parse_var(&vars, "NOAH_RETRY_BACKOFF_MS")?,
+            origin: Origin::Env,
+        })
+    }
+
+    /// Reads `profile` from a TOML (`.toml`) or JSON (any other extension) profile file.
+    pub fn from_file(path: impl AsRef<Path>, profile: &str) -> Result<Profile, ConfigError> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path).map_err(|source| ConfigError::Io {
+            path: path.to_owned(),
+            source,
+        })?;
+        let parse_error = |message: String| ConfigError::Parse {
+            path: path.to_owned(),
+            message,
+        };
+        let file: ProfileFile = match path.extension().and_then(|e| e.to_str()) {
+            Some("toml") => toml::from_str(&content).map_err(|e| parse_error(e.to_string()))?,
+            _ => serde_json::from_str(&content).map_err(|e| parse_error(e.to_string()))?,
+        };
+        let mut found =
+            file.profiles
+                .get(profile)
+                .cloned()
+                .ok_or_else(|| ConfigError::UnknownProfile {
+                    path: path.to_owned(),
+                    profile: profile.to_owned(),
+                })?;
+        found.origin = Origin::File {
+            path: path.to_owned(),
+            profile: profile.to_owned(),
+        };
+        Ok(found)
+    }
+
+    /// Validates the settings and builds a [`Configuration`].
+    pub fn into_configuration(self) -> Result<Configuration, ConfigError> {
+        let api_key = match (&self.api_key, &self.api_key_env) {
+            (Some(_), Some(_)) => {
+                return Err(ConfigError::Conflict {
+                    first: self.setting("api_key"),
+                    second: self.setting("api_key_env"),
+                })
+            }
+            (Some(key), None) => key.expose().clone(),
+            (None, Some(var)) => std::env::var(var)
+                .ok()
+                .filter(|key| !key.is_empty())
+                .ok_or_else(|| ConfigError::Missing {
+                    setting: format!("{var} (named by {})", self.setting("api_key_env")),
+                })?,
+            (None, None) => {
+                return Err(ConfigError::Missing {
+                    setting: self.setting("api_key"),
+                })
+            }
+        };
+
+        let environment = self.resolve_environment()?;
+
+        let mut client = reqwest::Client::builder();
+        if let Some(secs) = self.timeout_secs {
+            client = client.timeout(Duration::from_secs(secs));
+        }
+        if let Some(secs) = self.connect_timeout_secs {
+            client = client.connect_timeout(Duration::from_secs(secs));
+        }
+        let client = client.build().map_err(|e| ConfigError::Invalid {
+            setting: self.setting("timeout_secs"),
+            reason: e.to_string(),
+        })?;
+
+        let mut retry = RetryPolicy::default();
+        if let Some(max_retries) = self.max_retries {
+            retry.max_retries = max_retries;
+        }
+        if let Some(ms) = self.retry_backoff_ms {
+            retry.initial_backoff = Duration::from_millis(ms);
+        }
+
+        let mut configuration = Configuration::for_environment(environment);
+        configuration.client = client;
+        configuration.api_key = Some(ApiKey {
+            prefix: None,
+            key: api_key.into(),
+        });
+        configuration.retry = retry;
+        if let Some(user_agent) = self.user_agent {
+            configuration.user_agent = Some(user_agent);
+        }
+        Ok(configuration)
+    }
+
+    fn resolve_environment(&self) -> Result<Environment, ConfigError> {
+        let named = match self.environment.as_deref().map(str::to_ascii_lowercase) {
+            None => None,
+            Some(name) if name == "sandbox" => Some(Environment::Sandbox),
+            Some(name) if name == "production" || name == "prod" => Some(Environment::Production),
+            Some(name) => {
+                return Err(ConfigError::Invalid {
+                    setting: self.setting("environment"),
+                    reason: format!("expected `sandbox` or `production`, got `{name}`"),
+                })
+            }
+        };
+        let from_url = match &self.base_url {
+            None => None,
+            Some(base_url) => match url::Url::parse(base_url) {
+                Ok(_) => Some(Environment::from_base_path(base_url)),
+                Err(e) => {
+                    return Err(ConfigError::Invalid {
+                        setting: self.setting("base_url"),
+                        reason: e.to_string(),
+                    })
+                }
+            },
+        };
+        match (named, from_url) {
+            (Some(named), Some(from_url)) if named != from_url => Err(ConfigError::Conflict {
+                first: self.setting("environment"),
+                second: self.setting("base_url"),
+            }),
+            (_, Some(environment)) | (Some(environment), None) => Ok(environment),
+            (None, None) => Ok(Environment::default()),
+        }
+    }
+
+    /// Names a setting the way the user wrote it: as an environment variable or a profile key.
+    fn setting(&self, key: &str) -> String {
+        match &self.origin {
+            Origin::Env if key == "environment" => "NOAH_ENV".to_owned(),
+            Origin::Env => format!("NOAH_{}", key.to_ascii_uppercase()),
+            Origin::File { path, profile } => {
+                format!("`{key}` in profile `{profile}` of {}", path.display())
+            }
+        }
+    }
+}
+
+fn parse_var<T>(vars: &HashMap<String, String>, name: &str) -> Result<Option<T>, ConfigError>
+where
+    T: std::str::FromStr,
+    T::Err: fmt::Display,
+{
+    vars.get(name)
+        .map(|value| {
+            value.parse().map_err(|e: T::Err| ConfigError::Invalid {
+                setting: name.to_owned(),
+                reason: format!("`{value}`: {e}"),
+            })
+        })
+        .transpose()
+}
+
+/// A [`Configuration`] could not be loaded.
+#[derive(Debug)]
+pub enum ConfigError {
+    /// A required setting is not set.
+    Missing {
+        setting: String,
+    },
+    /// A setting has a value that cannot be used.
+    Invalid {
+        setting: String,
+        reason: String,
+    },
+    /// Two settings are set that contradict each other.
+    Conflict {
+        first: String,
+        second: String,
+    },
+    UnknownProfile {
+        path: PathBuf,
+        profile: String,
+    },
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    Parse {
+        path: PathBuf,
+        message: String,
+    },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Missing { setting } => write!(f, "missing setting {setting}"),
+            ConfigError::Invalid { setting, reason } => {
+                write!(f, "invalid setting {setting}: {reason}")
+            }
+            ConfigError::Conflict { first, second } => {
+                write!(f, "conflicting settings {first} and {second}")
+            }
+            ConfigError::UnknownProfile { path, profile } => {
+                write!(f, "no profile `{profile}` in {}", path.display())
+            }
+            ConfigError::Io { path, source } => {
+                write!(f, "cannot read {}: {source}", path.display())
+            }
+            ConfigError::Parse { path, message } => {
+                write!(f, "cannot parse {}: {message}", path.display())
+            }
+        }
+    }
+}
+
+impl error::Error for ConfigError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            ConfigError::Io { source, .. } => Some(source),
+            _ => None,
         }
     }
 }
@@ -1,5 +1,6 @@
 use std::error;
 use std::fmt;
+use std::time::Instant;
 
 #[derive(Debug, Clone)]
 pub struct ResponseContent<T> {
@@ -14,6 +15,17 @@ pub enum Error<T> {
     Serde(serde_json::Error),
     Io(std::io::Error),
     ResponseError(ResponseContent<T>),
+    Environment(configuration::EnvironmentError),
+    /// The [`RequestOptions::deadline`](configuration::RequestOptions::deadline) passed before a
+    /// response was received.
+    DeadlineExceeded,
+    /// The [`CircuitBreaker`](crate::circuit_breaker::CircuitBreaker) of the configuration has
+    /// stopped calls to `operation` after repeated failures. It will let a probe through in
+    /// `retry_in`.
+    CircuitOpen {
+        operation: &'static str,
+        retry_in: std::time::Duration,
+    },
 }
 
 impl<T> fmt::Display for Error<T> {
@@ -23,6 +35,18 @@ impl<T> fmt::Display for Error<T> {
             Error::Serde(e) => ("serde", e.to_string()),
             Error::Io(e) => ("IO", e.to_string()),
             Error::ResponseError(e) => ("response", format!("status code {}", e.status)),
+            Error::Environment(e) => ("environment", e.to_string()),
+            Error::DeadlineExceeded => ("deadline", "request deadline exceeded".to_string()),
+            Error::CircuitOpen {
+                operation,
+                retry_in,
+            } => (
+                "circuit breaker",
+                format!(
+                    "circuit for {operation} is open, retry in {}s",
+                    retry_in.as_secs_f64()
+                ),
+            ),
         };
         write!(f, "error in {module}: {e}")
     }
@@ -35,6 +59,8 @@ impl<T: fmt::Debug> error::Error for Error<T> {
             Error::Serde(e) => e,
             Error::Io(e) => e,
             Error::ResponseError(_) => return None,
+            Error::Environment(e) => e,
+            Error::DeadlineExceeded | Error::CircuitOpen { .. } => return None,
         })
     }
 }
@@ -57,6 +83,12 @@ impl<T> From<std::io::Error> for Error<T> {
     }
 }
 
+impl<T> From<configuration::EnvironmentError> for Error<T> {
+    fn from(e: configuration::EnvironmentError) -> Self {
+        Error::Environment(e)
+    }
+}
+
 pub fn urlencode<T: AsRef<str>>(s: T) -> String {
     ::url::form_urlencoded::byte_serialize(s.as_ref().as_bytes()).collect()
 }
@@ -112,6 +144,200 @@ impl From<&str> for ContentType {
     }
 }
 
+/// Sends a built request for `operation` through the steps shared by every endpoint.
+pub(crate) async fn execute<T>(
+    configuration: &configuration::Configuration,
+    operation: &'static str,
+    mut req: reqwest::Request,
+) -> Result<reqwest::Response, Error<T>> {
+    check_environment(&configuration.environment(), operation, &req)?;
+
+    let options = &configuration.request_options;
+    for (name, value) in &options.headers {
+        req.headers_mut().insert(name.clone(), value.clone());
+    }
+    if options.timeout.is_some() {
+        *req.timeout_mut() = options.timeout;
+    }
+    let deadline = options.deadline.map(|budget| Instant::now() + budget);
+    match &configuration.cache {
+        Some(cache) => {
+            cache
+                .fetch(operation, req, deadline, |req| {
+                    send_with_retries(configuration, operation, req, deadline)
+                })
+                .await
+        }
+        None => send_with_retries(configuration, operation, req, deadline).await,
+    }
+}
+
+async fn send_with_retries<T>(
+    configuration: &configuration::Configuration,
+    operation: &'static str,
+    mut req: reqwest::Request,
+    deadline: Option<Instant>,
+) -> Result<reqwest::Response, Error<T>> {
+    let attempt_timeout = req.timeout().copied();
+    let deadline_passed = || deadline.is_some_and(|d| Instant::now() >= d);
+
+    let retry = &configuration.retry;
+    let max_retries = if retry.may_retry(&req) {
+        retry.max_retries
+    } else {
+        0
+    };
+    let mut attempt = 0;
+    loop {
+        // An open circuit fails the call without spending a rate-limit token on it.
+        let permit = match &configuration.circuit_breaker {
+            Some(breaker) => {
+                Some(
+                    breaker
+                        .permit(operation)
+                        .map_err(|retry_in| Error::CircuitOpen {
+                            operation,
+                            retry_in,
+                        })?,
+                )
+            }
+            None => None,
+        };
+        if let Some(limiter) = &configuration.rate_limiter {
+            match deadline {
+                Some(deadline) => {
+                    tokio::time::timeout_at(deadline.into(), limiter.acquire(operation))
+                        .await
+                        .map_err(|_| Error::DeadlineExceeded)?;
+                }
+                None => limiter.acquire(operation).await,
+            }
+        }
+        // Capping each attempt at the remaining budget makes the deadline cover the body too.
+        if let Some(deadline) = deadline {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return Err(Error::DeadlineExceeded);
+            }
+            *req.timeout_mut() = Some(attempt_timeout.map_or(remaining, |t| t.min(remaining)));
+        }
+        // Bodies built by the endpoints are always buffered, so cloning only fails for streams.
+        let next = if attempt < max_retries {
+            req.try_clone()
+        } else {
+            None
+        };
+
+        let result = send_via(configuration, operation, req).await;
+        if let Some(permit) = permit {
+            permit.record(&result);
+        }
+        if let (Some(limiter), Ok(resp)) = (&configuration.rate_limiter, &result) {
+            limiter.observe(operation, resp.status(), resp.headers());
+        }
+        let retryable = match &result {
+            Ok(resp) => retry.should_retry_status(resp.status()),
+            Err(e) => retry.should_retry_error(e),
+        };
+        attempt += 1;
+        let backoff = retry.backoff(attempt);
+        let fits_deadline = deadline.is_none_or(|deadline| Instant::now() + backoff < deadline);
+        match next {
+            Some(next) if retryable && fits_deadline => {
+                tokio::time::sleep(backoff).await;
+                req = next;
+            }
+            _ => {
+                return match result {
+                    Ok(resp) => Ok(resp),
+                    Err(e) if e.is_timeout() && deadline_passed() => Err(Error::DeadlineExceeded),
+                    Err(e) => Err(e.into()),
+                }
+            }
+        }
+    }
+}
+
+#[cfg_attr(not(feature = "test-util"), allow(unused_variables))]
+async fn send_via(
+    configuration: &configuration::Configuration,
+    operation: &'static str,
+    req: reqwest::Request,
+) -> Result<reqwest::Response, reqwest::Error> {
+    #[cfg(feature = "test-util")]
+    if let Some(cassette) = &configuration.cassette {
+        return cassette.send(configuration, operation, req).await;
+    }
+    send(&configuration.client, req)
+        .await
+        .map(reqwest::Response::from)
+}
+
+/// Sends `req` and reads the whole body, so that timeouts and deadlines also cover the body.
+pub(crate) async fn send(
+    client: &reqwest::Client,
+    req: reqwest::Request,
+) -> Result<http::Response<bytes::Bytes>, reqwest::Error> {
+    let resp = client.execute(req).await?;
+    let status = resp.status();
+    let version = resp.version();
+    let headers = resp.headers().clone();
+    let mut buffered = http::Response::new(resp.bytes().await?);
+    *buffered.status_mut() = status;
+    *buffered.version_mut() = version;
+    *buffered.headers_mut() = headers;
+    Ok(buffered)
+}
+
+fn check_environment(
+    environment: &configuration::Environment,
+    operation: &str,
+    req: &reqwest::Request,
+) -> Result<(), configuration::EnvironmentError> {
+    if let configuration::Environment::Custom(_) = environment {
+        return Ok(());
+    }
+    environment.check_operation(operation)?;
+    for (key, value) in req.url().query_pairs() {
+        if configuration::CODE_FIELDS.contains(&key.as_ref()) {
+            environment.check_code(&key, &value)?;
+        }
+    }
+    if let Some(body) = req.body().and_then(|body| body.as_bytes()) {
+        if let Ok(value) = serde_json::from_slice::<serde_json::Value>(body) {
+            check_environment_json(environment, &value)?;
+        }
+    }
+    Ok(())
+}
+
+fn check_environment_json(
+    environment: &configuration::Environment,
+    value: &serde_json::Value,
+) -> Result<(), configuration::EnvironmentError> {
+    match value {
+        serde_json::Value::Object(object) => {
+            for (key, value) in object {
+                match value {
+                    serde_json::Value::String(code)
+                        if configuration::CODE_FIELDS.contains(&key.as_str()) =>
+                    {
+                        environment.check_code(key, code)?
+                    }
+                    _ => check_environment_json(environment, value)?,
+                }
+            }
+        }
+        serde_json::Value::Array(array) => {
+            for value in array {
+                check_environment_json(environment, value)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
 pub mod onboarding_api;
 pub mod payin_api;
 pub mod payout_api;
@@ -69,7 +69,7 @@ pub async fn customers_customer_id_put(
         req_builder = req_builder.header("Api-Signature", param_value.to_string());
     }
     if let Some(ref apikey) = configuration.api_key {
-        let key = apikey.key.clone();
+        let key = apikey.key.expose().clone();
         let value = match apikey.prefix {
             Some(ref prefix) => format!("{prefix} {key}"),
             None => key,
@@ -79,7 +79,7 @@ pub async fn customers_customer_id_put(
     req_builder = req_builder.json(&p_body_customer_input);
 
     let req = req_builder.build()?;
-    let resp = configuration.client.execute(req).await?;
+    let resp = super::execute(configuration, "customers_customer_id_put", req).await?;
 
     let status = resp.status();
 
@@ -124,7 +124,7 @@ pub async fn onboarding_customer_id_post(
         req_builder = req_builder.header("Api-Signature", param_value.to_string());
     }
     if let Some(ref apikey) = configuration.api_key {
-        let key = apikey.key.clone();
+        let key = apikey.key.expose().clone();
         let value = match apikey.prefix {
             Some(ref prefix) => format!("{prefix} {key}"),
             None => key,
@@ -134,7 +134,7 @@ pub async fn onboarding_customer_id_post(
     req_builder = req_builder.json(&p_body_hosted_onboarding_request);
 
     let req = req_builder.build()?;
-    let resp = configuration.client.execute(req).await?;
+    let resp = super::execute(configuration, "onboarding_customer_id_post", req).await?;
 
     let status = resp.status();
     let content_type = resp
@@ -205,7 +205,7 @@ pub async fn onboarding_customer_id_prefill_documents_upload_url_get(
         req_builder = req_builder.header("Api-Signature", param_value.to_string());
     }
     if let Some(ref apikey) = configuration.api_key {
-        let key = apikey.key.clone();
+        let key = apikey.key.expose().clone();
         let value = match apikey.prefix {
             Some(ref prefix) => format!("{prefix} {key}"),
             None => key,
@@ -214,7 +214,12 @@ pub async fn onboarding_customer_id_prefill_documents_upload_url_get(
     };
 
     let req = req_builder.build()?;
-    let resp = configuration.client.execute(req).await?;
+    let resp = super::execute(
+        configuration,
+        "onboarding_customer_id_prefill_documents_upload_url_get",
+        req,
+    )
+    .await?;
 
     let status = resp.status();
     let content_type = resp
@@ -271,7 +276,7 @@ pub async fn onboarding_customer_id_prefill_post(
         req_builder = req_builder.header("Api-Signature", param_value.to_string());
     }
     if let Some(ref apikey) = configuration.api_key {
-        let key = apikey.key.clone();
+        let key = apikey.key.expose().clone();
         let value = match apikey.prefix {
             Some(ref prefix) => format!("{prefix} {key}"),
             None => key,
@@ -281,7 +286,7 @@ pub async fn onboarding_customer_id_prefill_post(
     req_builder = req_builder.json(&p_body_prefill_onboarding_request);
 
     let req = req_builder.build()?;
-    let resp = configuration.client.execute(req).await?;
+    let resp = super::execute(configuration, "onboarding_customer_id_prefill_post", req).await?;
 
     let status = resp.status();
 
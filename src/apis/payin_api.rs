@@ -75,7 +75,7 @@ pub async fn checkout_payin_crypto_post(
         req_builder = req_builder.header("Api-Signature", param_value.to_string());
     }
     if let Some(ref apikey) = configuration.api_key {
-        let key = apikey.key.clone();
+        let key = apikey.key.expose().clone();
         let value = match apikey.prefix {
             Some(ref prefix) => format!("{prefix} {key}"),
             None => key,
@@ -85,7 +85,7 @@ pub async fn checkout_payin_crypto_post(
     req_builder = req_builder.json(&p_body_checkout_payin_crypto_post_request);
 
     let req = req_builder.build()?;
-    let resp = configuration.client.execute(req).await?;
+    let resp = super::execute(configuration, "checkout_payin_crypto_post", req).await?;
 
     let status = resp.status();
     let content_type = resp
@@ -135,7 +135,7 @@ pub async fn checkout_payin_fiat_post(
         req_builder = req_builder.header("Api-Signature", param_value.to_string());
     }
     if let Some(ref apikey) = configuration.api_key {
-        let key = apikey.key.clone();
+        let key = apikey.key.expose().clone();
         let value = match apikey.prefix {
             Some(ref prefix) => format!("{prefix} {key}"),
             None => key,
@@ -145,7 +145,7 @@ pub async fn checkout_payin_fiat_post(
     req_builder = req_builder.json(&p_body_checkout_payin_fiat_post_request);
 
     let req = req_builder.build()?;
-    let resp = configuration.client.execute(req).await?;
+    let resp = super::execute(configuration, "checkout_payin_fiat_post", req).await?;
 
     let status = resp.status();
     let content_type = resp
@@ -200,7 +200,7 @@ pub async fn hosted_workflows_bank_deposit_to_onchain_address_post(
         req_builder = req_builder.header("Api-Signature", param_value.to_string());
     }
     if let Some(ref apikey) = configuration.api_key {
-        let key = apikey.key.clone();
+        let key = apikey.key.expose().clone();
         let value = match apikey.prefix {
             Some(ref prefix) => format!("{prefix} {key}"),
             None => key,
@@ -210,7 +210,12 @@ pub async fn hosted_workflows_bank_deposit_to_onchain_address_post(
     req_builder = req_builder.json(&p_body_bank_deposit_to_onchain_address_hosted_request);
 
     let req = req_builder.build()?;
-    let resp = configuration.client.execute(req).await?;
+    let resp = super::execute(
+        configuration,
+        "hosted_workflows_bank_deposit_to_onchain_address_post",
+        req,
+    )
+    .await?;
 
     let status = resp.status();
     let content_type = resp
@@ -261,7 +266,7 @@ pub async fn sandbox_fiat_deposit_simulate_post(
         req_builder = req_builder.header("Api-Signature", param_value.to_string());
     }
     if let Some(ref apikey) = configuration.api_key {
-        let key = apikey.key.clone();
+        let key = apikey.key.expose().clone();
         let value = match apikey.prefix {
             Some(ref prefix) => format!("{prefix} {key}"),
             None => key,
@@ -271,7 +276,7 @@ pub async fn sandbox_fiat_deposit_simulate_post(
     req_builder = req_builder.json(&p_body_fiat_deposit_simulate_request);
 
     let req = req_builder.build()?;
-    let resp = configuration.client.execute(req).await?;
+    let resp = super::execute(configuration, "sandbox_fiat_deposit_simulate_post", req).await?;
 
     let status = resp.status();
     let content_type = resp
@@ -328,7 +333,7 @@ pub async fn workflows_bank_deposit_to_onchain_address_post(
         req_builder = req_builder.header("Api-Signature", param_value.to_string());
     }
     if let Some(ref apikey) = configuration.api_key {
-        let key = apikey.key.clone();
+        let key = apikey.key.expose().clone();
         let value = match apikey.prefix {
             Some(ref prefix) => format!("{prefix} {key}"),
             None => key,
@@ -338,7 +343,12 @@ pub async fn workflows_bank_deposit_to_onchain_address_post(
     req_builder = req_builder.json(&p_body_bank_deposit_to_onchain_address_request);
 
     let req = req_builder.build()?;
-    let resp = configuration.client.execute(req).await?;
+    let resp = super::execute(
+        configuration,
+        "workflows_bank_deposit_to_onchain_address_post",
+        req,
+    )
+    .await?;
 
     let status = resp.status();
     let content_type = resp
@@ -65,7 +65,7 @@ pub async fn checkout_payout_fiat_post(
         req_builder = req_builder.header("Api-Signature", param_value.to_string());
     }
     if let Some(ref apikey) = configuration.api_key {
-        let key = apikey.key.clone();
+        let key = apikey.key.expose().clone();
         let value = match apikey.prefix {
             Some(ref prefix) => format!("{prefix} {key}"),
             None => key,
@@ -75,7 +75,7 @@ pub async fn checkout_payout_fiat_post(
     req_builder = req_builder.json(&p_body_checkout_payout_fiat_post_request);
 
     let req = req_builder.build()?;
-    let resp = configuration.client.execute(req).await?;
+    let resp = super::execute(configuration, "checkout_payout_fiat_post", req).await?;
 
     let status = resp.status();
     let content_type = resp
@@ -125,7 +125,7 @@ pub async fn transactions_sell_post(
         req_builder = req_builder.header("Api-Signature", param_value.to_string());
     }
     if let Some(ref apikey) = configuration.api_key {
-        let key = apikey.key.clone();
+        let key = apikey.key.expose().clone();
         let value = match apikey.prefix {
             Some(ref prefix) => format!("{prefix} {key}"),
             None => key,
@@ -135,7 +135,7 @@ pub async fn transactions_sell_post(
     req_builder = req_builder.json(&p_body_sell_request);
 
     let req = req_builder.build()?;
-    let resp = configuration.client.execute(req).await?;
+    let resp = super::execute(configuration, "transactions_sell_post", req).await?;
 
     let status = resp.status();
     let content_type = resp
@@ -185,7 +185,7 @@ pub async fn transactions_sell_prepare_post(
         req_builder = req_builder.header("Api-Signature", param_value.to_string());
     }
     if let Some(ref apikey) = configuration.api_key {
-        let key = apikey.key.clone();
+        let key = apikey.key.expose().clone();
         let value = match apikey.prefix {
             Some(ref prefix) => format!("{prefix} {key}"),
             None => key,
@@ -195,7 +195,7 @@ pub async fn transactions_sell_prepare_post(
     req_builder = req_builder.json(&p_body_prepare_sell_request);
 
     let req = req_builder.build()?;
-    let resp = configuration.client.execute(req).await?;
+    let resp = super::execute(configuration, "transactions_sell_prepare_post", req).await?;
 
     let status = resp.status();
     let content_type = resp
@@ -252,7 +252,7 @@ pub async fn workflows_onchain_deposit_to_payment_method_post(
         req_builder = req_builder.header("Api-Signature", param_value.to_string());
     }
     if let Some(ref apikey) = configuration.api_key {
-        let key = apikey.key.clone();
+        let key = apikey.key.expose().clone();
         let value = match apikey.prefix {
             Some(ref prefix) => format!("{prefix} {key}"),
             None => key,
@@ -262,7 +262,12 @@ pub async fn workflows_onchain_deposit_to_payment_method_post(
     req_builder = req_builder.json(&p_body_onchain_deposit_to_payment_method_request);
 
     let req = req_builder.build()?;
-    let resp = configuration.client.execute(req).await?;
+    let resp = super::execute(
+        configuration,
+        "workflows_onchain_deposit_to_payment_method_post",
+        req,
+    )
+    .await?;
 
     let status = resp.status();
     let content_type = resp
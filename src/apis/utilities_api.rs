@@ -139,7 +139,7 @@ pub async fn balances_get(
         req_builder = req_builder.header("Api-Signature", param_value.to_string());
     }
     if let Some(ref apikey) = configuration.api_key {
-        let key = apikey.key.clone();
+        let key = apikey.key.expose().clone();
         let value = match apikey.prefix {
             Some(ref prefix) => format!("{prefix} {key}"),
             None => key,
@@ -148,7 +148,7 @@ pub async fn balances_get(
     };
 
     let req = req_builder.build()?;
-    let resp = configuration.client.execute(req).await?;
+    let resp = super::execute(configuration, "balances_get", req).await?;
 
     let status = resp.status();
     let content_type = resp
@@ -210,7 +210,7 @@ pub async fn channels_channel_id_form_get(
         req_builder = req_builder.header("Api-Signature", param_value.to_string());
     }
     if let Some(ref apikey) = configuration.api_key {
-        let key = apikey.key.clone();
+        let key = apikey.key.expose().clone();
         let value = match apikey.prefix {
             Some(ref prefix) => format!("{prefix} {key}"),
             None => key,
@@ -219,7 +219,7 @@ pub async fn channels_channel_id_form_get(
     };
 
     let req = req_builder.build()?;
-    let resp = configuration.client.execute(req).await?;
+    let resp = super::execute(configuration, "channels_channel_id_form_get", req).await?;
 
     let status = resp.status();
     let content_type = resp
@@ -284,7 +284,7 @@ pub async fn channels_channel_id_get(
         req_builder = req_builder.header("Api-Signature", param_value.to_string());
     }
     if let Some(ref apikey) = configuration.api_key {
-        let key = apikey.key.clone();
+        let key = apikey.key.expose().clone();
         let value = match apikey.prefix {
             Some(ref prefix) => format!("{prefix} {key}"),
             None => key,
@@ -293,7 +293,7 @@ pub async fn channels_channel_id_get(
     };
 
     let req = req_builder.build()?;
-    let resp = configuration.client.execute(req).await?;
+    let resp = super::execute(configuration, "channels_channel_id_get", req).await?;
 
     let status = resp.status();
     let content_type = resp
@@ -344,7 +344,7 @@ pub async fn channels_sell_countries_get(
         req_builder = req_builder.header("Api-Signature", param_value.to_string());
     }
     if let Some(ref apikey) = configuration.api_key {
-        let key = apikey.key.clone();
+        let key = apikey.key.expose().clone();
         let value = match apikey.prefix {
             Some(ref prefix) => format!("{prefix} {key}"),
             None => key,
@@ -353,7 +353,7 @@ pub async fn channels_sell_countries_get(
     };
 
     let req = req_builder.build()?;
-    let resp = configuration.client.execute(req).await?;
+    let resp = super::execute(configuration, "channels_sell_countries_get", req).await?;
 
     let status = resp.status();
     let content_type = resp
@@ -437,7 +437,7 @@ pub async fn channels_sell_get(
         req_builder = req_builder.header("Api-Signature", param_value.to_string());
     }
     if let Some(ref apikey) = configuration.api_key {
-        let key = apikey.key.clone();
+        let key = apikey.key.expose().clone();
         let value = match apikey.prefix {
             Some(ref prefix) => format!("{prefix} {key}"),
             None => key,
@@ -446,7 +446,7 @@ pub async fn channels_sell_get(
     };
 
     let req = req_builder.build()?;
-    let resp = configuration.client.execute(req).await?;
+    let resp = super::execute(configuration, "channels_sell_get", req).await?;
 
     let status = resp.status();
     let content_type = resp
@@ -493,7 +493,7 @@ pub async fn customers_customer_id_get(
         req_builder = req_builder.header(reqwest::header::USER_AGENT, user_agent.clone());
     }
     if let Some(ref apikey) = configuration.api_key {
-        let key = apikey.key.clone();
+        let key = apikey.key.expose().clone();
         let value = match apikey.prefix {
             Some(ref prefix) => format!("{prefix} {key}"),
             None => key,
@@ -502,7 +502,7 @@ pub async fn customers_customer_id_get(
     };
 
     let req = req_builder.build()?;
-    let resp = configuration.client.execute(req).await?;
+    let resp = super::execute(configuration, "customers_customer_id_get", req).await?;
 
     let status = resp.status();
     let content_type = resp
@@ -563,7 +563,7 @@ pub async fn customers_get(
         req_builder = req_builder.header("Api-Signature", param_value.to_string());
     }
     if let Some(ref apikey) = configuration.api_key {
-        let key = apikey.key.clone();
+        let key = apikey.key.expose().clone();
         let value = match apikey.prefix {
             Some(ref prefix) => format!("{prefix} {key}"),
             None => key,
@@ -572,7 +572,7 @@ pub async fn customers_get(
     };
 
     let req = req_builder.build()?;
-    let resp = configuration.client.execute(req).await?;
+    let resp = super::execute(configuration, "customers_get", req).await?;
 
     let status = resp.status();
     let content_type = resp
@@ -631,7 +631,7 @@ pub async fn payment_methods_get(
         req_builder = req_builder.header("Api-Signature", param_value.to_string());
     }
     if let Some(ref apikey) = configuration.api_key {
-        let key = apikey.key.clone();
+        let key = apikey.key.expose().clone();
         let value = match apikey.prefix {
             Some(ref prefix) => format!("{prefix} {key}"),
             None => key,
@@ -640,7 +640,7 @@ pub async fn payment_methods_get(
     };
 
     let req = req_builder.build()?;
-    let resp = configuration.client.execute(req).await?;
+    let resp = super::execute(configuration, "payment_methods_get", req).await?;
 
     let status = resp.status();
     let content_type = resp
@@ -710,7 +710,7 @@ pub async fn prices_get(
         req_builder = req_builder.header(reqwest::header::USER_AGENT, user_agent.clone());
     }
     if let Some(ref apikey) = configuration.api_key {
-        let key = apikey.key.clone();
+        let key = apikey.key.expose().clone();
         let value = match apikey.prefix {
             Some(ref prefix) => format!("{prefix} {key}"),
             None => key,
@@ -719,7 +719,7 @@ pub async fn prices_get(
     };
 
     let req = req_builder.build()?;
-    let resp = configuration.client.execute(req).await?;
+    let resp = super::execute(configuration, "prices_get", req).await?;
 
     let status = resp.status();
     let content_type = resp
@@ -780,7 +780,7 @@ pub async fn transactions_get(
         req_builder = req_builder.header("Api-Signature", param_value.to_string());
     }
     if let Some(ref apikey) = configuration.api_key {
-        let key = apikey.key.clone();
+        let key = apikey.key.expose().clone();
         let value = match apikey.prefix {
             Some(ref prefix) => format!("{prefix} {key}"),
             None => key,
@@ -789,7 +789,7 @@ pub async fn transactions_get(
     };
 
     let req = req_builder.build()?;
-    let resp = configuration.client.execute(req).await?;
+    let resp = super::execute(configuration, "transactions_get", req).await?;
 
     let status = resp.status();
     let content_type = resp
@@ -841,7 +841,7 @@ pub async fn transactions_transaction_id_get(
         req_builder = req_builder.header("Api-Signature", param_value.to_string());
     }
     if let Some(ref apikey) = configuration.api_key {
-        let key = apikey.key.clone();
+        let key = apikey.key.expose().clone();
         let value = match apikey.prefix {
             Some(ref prefix) => format!("{prefix} {key}"),
             None => key,
@@ -850,7 +850,7 @@ pub async fn transactions_transaction_id_get(
     };
 
     let req = req_builder.build()?;
-    let resp = configuration.client.execute(req).await?;
+    let resp = super::execute(configuration, "transactions_transaction_id_get", req).await?;
 
     let status = resp.status();
     let content_type = resp
@@ -0,0 +1,228 @@
+//! Caching responses of endpoints whose data changes slowly.
+//!
+//! Channels, countries and prices are read far more often than they change. Setting
+//! [`Configuration::cache`](crate::apis::configuration::Configuration::cache) to a
+//! [`ResponseCache`] serves repeated calls to those endpoints from memory until their time to
+//! live passes.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+
+use bytes::Bytes;
+use reqwest::header::HeaderMap;
+
+use crate::apis::Error;
+
+/// An in-memory cache of successful `GET` responses, keyed by endpoint, URL and credentials.
+///
+/// Only endpoints with a time to live are cached. [`ResponseCache::new`] caches
+/// `channels_sell_countries_get` for an hour, `channels_sell_get` for five minutes and
+/// `prices_get` for fifteen seconds, and [`ttl`](ResponseCache::ttl) changes or adds others.
+///
+/// Identical calls made while the first is in flight wait for its response instead of sending
+/// their own. Error responses are not cached, so the next call tries again.
+///
+/// ```
+/// use std::sync::Arc;
+/// use std::time::Duration;
+///
+/// use noah_sdk::apis::configuration::Configuration;
+/// use noah_sdk::cache::ResponseCache;
+///
+/// let cache = Arc::new(ResponseCache::new().ttl("prices_get", Duration::from_secs(5)));
+/// let config = Configuration {
+///     cache: Some(cache.clone()),
+///     ..Configuration::default()
+/// };
+/// // After changing the channels of a customer:
+/// cache.invalidate("channels_sell_get");
+/// ```
+pub struct ResponseCache {
+    ttls: HashMap<String, Duration>,
+    entries: Mutex<HashMap<Key, Arc<tokio::sync::Mutex<Option<Cached>>>>>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct Key {
+    operation: &'static str,
+    url: String,
+    /// A hash of the authentication headers, so that callers with different credentials don't
+    /// share responses.
+    credentials: u64,
+}
+
+struct Cached {
+    status: reqwest::StatusCode,
+    version: reqwest::Version,
+    headers: HeaderMap,
+    body: Bytes,
+    expires: Instant,
+}
+
+impl Cached {
+    fn is_fresh(&self) -> bool {
+        Instant::now() < self.expires
+    }
+
+    fn response(&self) -> reqwest::Response {
+        let mut response = http::Response::new(self.body.clone());
+        *response.status_mut() = self.status;
+        *response.version_mut() = self.version;
+        *response.headers_mut() = self.headers.clone();
+        reqwest::Response::from(response)
+    }
+}
+
+impl ResponseCache {
+    pub fn new() -> ResponseCache {
+        ResponseCache {
+            ttls: HashMap::new(),
+            entries: Mutex::default(),
+        }
+        .ttl("channels_sell_countries_get", Duration::from_secs(60 * 60))
+        .ttl("channels_sell_get", Duration::from_secs(5 * 60))
+        .ttl("prices_get", Duration::from_secs(15))
+    }
+
+    /// Caches the responses of `operation`, the name of the endpoint function, for `ttl`. A zero
+    /// `ttl` stops caching it.
+    pub fn ttl(mut self, operation: &str, ttl: Duration) -> ResponseCache {
+        if ttl.is_zero() {
+            self.ttls.remove(operation);
+        } else {
+            self.ttls.insert(operation.to_owned(), ttl);
+        }
+        self
+    }
+
+    /// Drops the cached responses of `operation`.
+    pub fn invalidate(&self, operation: &str) {
+        self.entries().retain(|key, _| key.operation != operation);
+    }
+
+    /// Drops every cached response.
+    pub fn clear(&self) {
+        self.entries().clear();
+    }
+
+    /// The number of responses that would be served from the cache.
+    pub fn len(&self) -> usize {
+        self.entries()
+            .values()
+            .filter(|slot| {
+                slot.try_lock()
+                    .is_ok_and(|cached| cached.as_ref().is_some_and(Cached::is_fresh))
+            })
+            .count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Serves `req` from the cache, or sends it with `send` and caches a successful response.
+    /// Requests that aren't cached go straight to `send`. Waiting for an identical call in flight
+    /// ends with [`Error::DeadlineExceeded`] once `deadline` passes.
+    pub(crate) async fn fetch<F, Fut, T>(
+        &self,
+        operation: &'static str,
+        req: reqwest::Request,
+        deadline: Option<Instant>,
+        send: F,
+    ) -> Result<reqwest::Response, Error<T>>
+    where
+        F: FnOnce(reqwest::Request) -> Fut,
+        Fut: std::future::Future<Output = Result<reqwest::Response, Error<T>>>,
+    {
+        let Some(ttl) = self.ttl_of(operation, &req) else {
+            return send(req).await;
+        };
+        let slot = self.slot(Key {
+            operation,
+            url: req.url().to_string(),
+            credentials: credentials(req.headers()),
+        });
+        // Holding the slot while sending makes identical calls wait for this one.
+        let mut cached = match deadline {
+            Some(deadline) => tokio::time::timeout_at(deadline.into(), slot.lock())
+                .await
+                .map_err(|_| Error::DeadlineExceeded)?,
+            None => slot.lock().await,
+        };
+        if let Some(fresh) = cached.as_ref().filter(|cached| cached.is_fresh()) {
+            return Ok(fresh.response());
+        }
+        let response = send(req).await?;
+        if !response.status().is_success() {
+            return Ok(response);
+        }
+        let status = response.status();
+        let version = response.version();
+        let headers = response.headers().clone();
+        let body = response.bytes().await?;
+        let fresh = cached.insert(Cached {
+            status,
+            version,
+            headers,
+            body,
+            expires: Instant::now() + ttl,
+        });
+        Ok(fresh.response())
+    }
+
+    fn ttl_of(&self, operation: &str, req: &reqwest::Request) -> Option<Duration> {
+        if req.method() != reqwest::Method::GET {
+            return None;
+        }
+        self.ttls.get(operation).copied()
+    }
+
+    /// Locks the entries. Nothing that can panic runs under the lock, so a poisoned lock still
+    /// holds a consistent map.
+    fn entries(&self) -> MutexGuard<'_, HashMap<Key, Arc<tokio::sync::Mutex<Option<Cached>>>>> {
+        self.entries.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn slot(&self, key: Key) -> Arc<tokio::sync::Mutex<Option<Cached>>> {
+        let mut entries = self.entries();
+        if let Some(slot) = entries.get(&key) {
+            return slot.clone();
+        }
+        // Drop expired responses as new keys come in, so the map doesn't only grow.
+        entries.retain(|_, slot| {
+            slot.try_lock()
+                .map_or(true, |cached| cached.as_ref().is_some_and(Cached::is_fresh))
+        });
+        entries.entry(key).or_default().clone()
+    }
+}
+
+impl Default for ResponseCache {
+    fn default() -> Self {
+        ResponseCache::new()
+    }
+}
+
+impl fmt::Debug for ResponseCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ResponseCache")
+            .field("ttls", &self.ttls)
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+fn credentials(headers: &HeaderMap) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    for name in ["X-Api-Key", "Authorization"] {
+        headers
+            .get(name)
+            .map(|value| value.as_bytes())
+            .hash(&mut hasher);
+    }
+    hasher.finish()
+}
@@ -0,0 +1,365 @@
+//! Choosing the sell channel to pay out through.
+//!
+//! [`ChannelSelector`] filters the channels returned by
+//! [`channels_sell_get`](crate::apis::utilities_api::channels_sell_get) by country, currency,
+//! payment method category and amount, and ranks the rest by a [`CostFunction`]. Every channel it
+//! leaves out comes with the reason why.
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::sync::Arc;
+
+use rust_decimal::Decimal;
+
+use crate::apis::configuration::Configuration;
+use crate::apis::utilities_api::{self, ChannelsSellGetError};
+use crate::apis::Error;
+use crate::models;
+
+/// A channel that passed the filters, with its amounts parsed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Candidate {
+    pub channel: models::Channel,
+    /// `Calculated.TotalFee`, or zero when Noah didn't calculate it.
+    pub total_fee: Decimal,
+    /// `Rate`, in fiat per crypto.
+    pub rate: Decimal,
+    /// The crypto spent to pay out the selector's amount: the amount at `rate` plus `total_fee`.
+    /// Just `total_fee` when the selector has no amount.
+    pub total_cost: Decimal,
+    pub processing_seconds: i32,
+    pub processing_tier: Option<models::ProcessingTier>,
+}
+
+/// Scores candidates; the lowest cost ranks first.
+///
+/// Implemented for closures taking a [`Candidate`].
+pub trait CostFunction: Send + Sync {
+    fn cost(&self, candidate: &Candidate) -> Decimal;
+}
+
+impl<F> CostFunction for F
+where
+    F: Fn(&Candidate) -> Decimal + Send + Sync,
+{
+    fn cost(&self, candidate: &Candidate) -> Decimal {
+        self(candidate)
+    }
+}
+
+/// The default cost: the total cost, plus a price on waiting and on the wrong processing tier.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct WeightedCost {
+    /// Added per hour of `ProcessingSeconds`, in the same unit as the total cost.
+    pub per_hour: Decimal,
+    pub preferred_tier: Option<models::ProcessingTier>,
+    /// Added when the channel isn't on `preferred_tier`.
+    pub tier_penalty: Decimal,
+}
+
+impl CostFunction for WeightedCost {
+    fn cost(&self, candidate: &Candidate) -> Decimal {
+        let hours = Decimal::from(candidate.processing_seconds) / Decimal::from(3600);
+        let mut cost = candidate.total_cost + self.per_hour * hours;
+        if self.preferred_tier.is_some() && candidate.processing_tier != self.preferred_tier {
+            cost += self.tier_penalty;
+        }
+        cost
+    }
+}
+
+/// Why a channel was left out.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Exclusion {
+    Country {
+        wanted: String,
+    },
+    FiatCurrency {
+        wanted: String,
+    },
+    PaymentMethodCategory {
+        wanted: String,
+    },
+    BelowMinimum {
+        amount: Decimal,
+        min: Decimal,
+    },
+    AboveMaximum {
+        amount: Decimal,
+        max: Decimal,
+    },
+    /// An amount of the channel is not a decimal number.
+    InvalidValue {
+        field: &'static str,
+        value: String,
+    },
+}
+
+impl fmt::Display for Exclusion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Exclusion::Country { wanted } => write!(f, "not in {wanted}"),
+            Exclusion::FiatCurrency { wanted } => write!(f, "does not pay out {wanted}"),
+            Exclusion::PaymentMethodCategory { wanted } => write!(f, "not a {wanted} channel"),
+            Exclusion::BelowMinimum { amount, min } => {
+                write!(f, "{amount} is below the minimum of {min}")
+            }
+            Exclusion::AboveMaximum { amount, max } => {
+                write!(f, "{amount} is above the maximum of {max}")
+            }
+            Exclusion::InvalidValue { field, value } => write!(f, "invalid {field} `{value}`"),
+        }
+    }
+}
+
+/// A channel left out, and why.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Excluded {
+    pub channel: models::Channel,
+    pub reason: Exclusion,
+}
+
+impl fmt::Display for Excluded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} channel {}: {}",
+            self.channel.country, self.channel.payment_method_type, self.channel.id, self.reason
+        )
+    }
+}
+
+/// A candidate and its cost.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Ranked {
+    pub candidate: Candidate,
+    pub cost: Decimal,
+}
+
+/// The outcome of [`ChannelSelector::select`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Selection {
+    /// The candidates, cheapest first.
+    pub ranked: Vec<Ranked>,
+    pub excluded: Vec<Excluded>,
+}
+
+impl Selection {
+    pub fn best(&self) -> Option<&models::Channel> {
+        self.ranked.first().map(|ranked| &ranked.candidate.channel)
+    }
+}
+
+/// Filters and ranks sell channels.
+///
+/// Cost ties are broken by the better rate, then the faster channel, then the channel ID.
+///
+/// ```
+/// use noah_sdk::channels::{ChannelSelector, WeightedCost};
+/// use noah_sdk::models::ProcessingTier;
+/// use noah_sdk::Decimal;
+///
+/// # fn example(channels: Vec<noah_sdk::models::Channel>) {
+/// let selection = ChannelSelector::new()
+///     .fiat_currency("USD")
+///     .payment_method_category("Bank")
+///     .fiat_amount(Decimal::new(250, 0))
+///     .cost(WeightedCost {
+///         per_hour: Decimal::new(1, 2),
+///         preferred_tier: Some(ProcessingTier::Priority),
+///         tier_penalty: Decimal::new(50, 2),
+///     })
+///     .select(channels);
+/// for excluded in &selection.excluded {
+///     println!("skipped {excluded}");
+/// }
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct ChannelSelector {
+    country: Option<String>,
+    fiat_currency: Option<String>,
+    payment_method_category: Option<String>,
+    fiat_amount: Option<Decimal>,
+    cost: Arc<dyn CostFunction>,
+}
+
+impl fmt::Debug for ChannelSelector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ChannelSelector")
+            .field("country", &self.country)
+            .field("fiat_currency", &self.fiat_currency)
+            .field("payment_method_category", &self.payment_method_category)
+            .field("fiat_amount", &self.fiat_amount)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for ChannelSelector {
+    fn default() -> Self {
+        ChannelSelector {
+            country: None,
+            fiat_currency: None,
+            payment_method_category: None,
+            fiat_amount: None,
+            cost: Arc::new(WeightedCost::default()),
+        }
+    }
+}
+
+impl ChannelSelector {
+    pub fn new() -> ChannelSelector {
+        ChannelSelector::default()
+    }
+
+    /// Keeps the channels in `country`, an ISO 3166-1 alpha-2 code.
+    pub fn country(mut self, country: impl Into<String>) -> ChannelSelector {
+        self.country = Some(country.into());
+        self
+    }
+
+    pub fn fiat_currency(mut self, fiat_currency: impl Into<String>) -> ChannelSelector {
+        self.fiat_currency = Some(fiat_currency.into());
+        self
+    }
+
+    /// Keeps the channels whose `PaymentMethodCategory` is `category`, such as `Bank`.
+    pub fn payment_method_category(mut self, category: impl Into<String>) -> ChannelSelector {
+        self.payment_method_category = Some(category.into());
+        self
+    }
+
+    /// Keeps the channels whose limits admit `fiat_amount`, and prices the candidates for it.
+    pub fn fiat_amount(mut self, fiat_amount: Decimal) -> ChannelSelector {
+        self.fiat_amount = Some(fiat_amount);
+        self
+    }
+
+    /// Ranks by `cost` instead of [`WeightedCost::default`], which is the total cost alone.
+    pub fn cost(mut self, cost: impl CostFunction + 'static) -> ChannelSelector {
+        self.cost = Arc::new(cost);
+        self
+    }
+
+    pub fn select(&self, channels: impl IntoIterator<Item = models::Channel>) -> Selection {
+        let mut selection = Selection::default();
+        for channel in channels {
+            match self.candidate(&channel) {
+                Ok(candidate) => {
+                    let cost = self.cost.cost(&candidate);
+                    selection.ranked.push(Ranked { candidate, cost });
+                }
+                Err(reason) => selection.excluded.push(Excluded { channel, reason }),
+            }
+        }
+        selection.ranked.sort_by(rank);
+        selection
+    }
+
+    /// Lists every page of sell channels for `crypto_currency` and selects among them.
+    pub async fn fetch(
+        &self,
+        configuration: &Configuration,
+        crypto_currency: &str,
+        customer_id: Option<&str>,
+        payment_method_id: Option<&str>,
+    ) -> Result<Selection, Error<ChannelsSellGetError>> {
+        let fiat_amount = self.fiat_amount.map(|amount| amount.to_string());
+        let mut channels = Vec::new();
+        let mut page_token = None;
+        loop {
+            let page = utilities_api::channels_sell_get(
+                configuration,
+                crypto_currency,
+                self.country.as_deref(),
+                self.fiat_currency.as_deref(),
+                fiat_amount.as_deref(),
+                customer_id,
+                payment_method_id,
+                None,
+                page_token.as_deref(),
+                None,
+            )
+            .await?;
+            channels.extend(page.items);
+            match page.page_token {
+                Some(token) if !token.is_empty() => page_token = Some(token),
+                _ => break,
+            }
+        }
+        Ok(self.select(channels))
+    }
+
+    fn candidate(&self, channel: &models::Channel) -> Result<Candidate, Exclusion> {
+        if let Some(wanted) = &self.country {
+            if !channel.country.eq_ignore_ascii_case(wanted) {
+                return Err(Exclusion::Country {
+                    wanted: wanted.clone(),
+                });
+            }
+        }
+        if let Some(wanted) = &self.fiat_currency {
+            if !channel.fiat_currency.eq_ignore_ascii_case(wanted) {
+                return Err(Exclusion::FiatCurrency {
+                    wanted: wanted.clone(),
+                });
+            }
+        }
+        if let Some(wanted) = &self.payment_method_category {
+            if channel.payment_method_category != *wanted {
+                return Err(Exclusion::PaymentMethodCategory {
+                    wanted: wanted.clone(),
+                });
+            }
+        }
+        let rate = decimal("Rate", &channel.rate)?;
+        let total_fee = match &channel.calculated {
+            Some(calculated) => decimal("TotalFee", &calculated.total_fee)?,
+            None => Decimal::ZERO,
+        };
+        let mut total_cost = total_fee;
+        if let Some(amount) = self.fiat_amount {
+            let min = decimal("MinLimit", &channel.limits.min_limit)?;
+            if amount < min {
+                return Err(Exclusion::BelowMinimum { amount, min });
+            }
+            if let Some(max) = &channel.limits.max_limit {
+                let max = decimal("MaxLimit", max)?;
+                if amount > max {
+                    return Err(Exclusion::AboveMaximum { amount, max });
+                }
+            }
+            total_cost += amount.checked_div(rate).ok_or(Exclusion::InvalidValue {
+                field: "Rate",
+                value: channel.rate.clone(),
+            })?;
+        }
+        Ok(Candidate {
+            channel: channel.clone(),
+            total_fee,
+            rate,
+            total_cost,
+            processing_seconds: channel.processing_seconds,
+            processing_tier: channel.processing_tier,
+        })
+    }
+}
+
+fn rank(a: &Ranked, b: &Ranked) -> Ordering {
+    a.cost
+        .cmp(&b.cost)
+        .then(b.candidate.rate.cmp(&a.candidate.rate))
+        .then(
+            a.candidate
+                .processing_seconds
+                .cmp(&b.candidate.processing_seconds),
+        )
+        .then_with(|| a.candidate.channel.id.cmp(&b.candidate.channel.id))
+}
+
+fn decimal(field: &'static str, value: &str) -> Result<Decimal, Exclusion> {
+    value.parse().map_err(|_| Exclusion::InvalidValue {
+        field,
+        value: value.to_owned(),
+    })
+}
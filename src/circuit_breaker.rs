@@ -0,0 +1,329 @@
+//! Failing fast while Noah is failing.
+//!
+//! Setting
+//! [`Configuration::circuit_breaker`](crate::apis::configuration::Configuration::circuit_breaker)
+//! to a [`CircuitBreaker`] stops calls to an endpoint after it has failed several times in a row.
+//! They fail at once with [`Error::CircuitOpen`](crate::apis::Error::CircuitOpen) instead of
+//! waiting on another timeout, until a probe shows the endpoint is back.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+
+/// Whether calls to an endpoint go through.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CircuitState {
+    /// Calls go through.
+    Closed,
+    /// Calls fail fast.
+    Open,
+    /// A few probe calls go through to test the endpoint; the rest fail fast.
+    HalfOpen,
+}
+
+impl fmt::Display for CircuitState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            CircuitState::Closed => "closed",
+            CircuitState::Open => "open",
+            CircuitState::HalfOpen => "half-open",
+        })
+    }
+}
+
+/// A circuit changing state, as passed to [`CircuitBreaker::on_state_change`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StateChange {
+    pub operation: &'static str,
+    pub from: CircuitState,
+    pub to: CircuitState,
+    /// Consecutive failures when the circuit opened, zero otherwise.
+    pub failures: u32,
+}
+
+impl fmt::Display for StateChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "circuit for {} went {}", self.operation, self.to)?;
+        if self.failures > 0 {
+            write!(f, " after {} failures", self.failures)?;
+        }
+        Ok(())
+    }
+}
+
+type Listener = Arc<dyn Fn(&StateChange) + Send + Sync>;
+
+/// One circuit per endpoint, opened by consecutive `5xx` responses, timeouts or connection
+/// errors.
+///
+/// After `failure_threshold` failures in a row, the circuit opens and calls fail fast for
+/// `open_for`. Then it turns half-open and lets `half_open_probes` calls through. If they all
+/// succeed it closes, and if one fails it opens again. Any other response, including `4xx`, shows
+/// the endpoint is up and counts as a success.
+///
+/// Each attempt of a retried call goes through the breaker on its own.
+///
+/// ```
+/// use std::sync::Arc;
+/// use std::time::Duration;
+///
+/// use noah_sdk::apis::configuration::Configuration;
+/// use noah_sdk::circuit_breaker::CircuitBreaker;
+///
+/// let breaker = CircuitBreaker::new()
+///     .failure_threshold(3)
+///     .open_for(Duration::from_secs(10))
+///     .on_state_change(|change| eprintln!("{change}"));
+/// let config = Configuration {
+///     circuit_breaker: Some(Arc::new(breaker)),
+///     ..Configuration::default()
+/// };
+/// ```
+pub struct CircuitBreaker {
+    failure_threshold: u32,
+    open_for: Duration,
+    half_open_probes: u32,
+    listeners: Vec<Listener>,
+    circuits: Mutex<HashMap<&'static str, Circuit>>,
+}
+
+#[derive(Debug)]
+struct Circuit {
+    state: CircuitState,
+    failures: u32,
+    opened_at: Instant,
+    probes: u32,
+    successes: u32,
+}
+
+impl Default for Circuit {
+    fn default() -> Self {
+        Circuit {
+            state: CircuitState::Closed,
+            failures: 0,
+            opened_at: Instant::now(),
+            probes: 0,
+            successes: 0,
+        }
+    }
+}
+
+impl Circuit {
+    fn change(&mut self, operation: &'static str, to: CircuitState) -> StateChange {
+        let change = StateChange {
+            operation,
+            from: self.state,
+            to,
+            failures: if to == CircuitState::Open {
+                self.failures
+            } else {
+                0
+            },
+        };
+        self.state = to;
+        self.probes = 0;
+        self.successes = 0;
+        match to {
+            CircuitState::Open => self.opened_at = Instant::now(),
+            CircuitState::Closed => self.failures = 0,
+            CircuitState::HalfOpen => {}
+        }
+        change
+    }
+}
+
+impl CircuitBreaker {
+    pub fn new() -> CircuitBreaker {
+        CircuitBreaker {
+            failure_threshold: 5,
+            open_for: Duration::from_secs(30),
+            half_open_probes: 1,
+            listeners: Vec::new(),
+            circuits: Mutex::default(),
+        }
+    }
+
+    /// Opens a circuit after this many failures in a row. Defaults to 5.
+    pub fn failure_threshold(mut self, failures: u32) -> CircuitBreaker {
+        self.failure_threshold = failures.max(1);
+        self
+    }
+
+    /// How long an open circuit fails calls before probing. Defaults to 30 seconds.
+    pub fn open_for(mut self, open_for: Duration) -> CircuitBreaker {
+        self.open_for = open_for;
+        self
+    }
+
+    /// How many probes must succeed to close a half-open circuit. Defaults to 1.
+    pub fn half_open_probes(mut self, probes: u32) -> CircuitBreaker {
+        self.half_open_probes = probes.max(1);
+        self
+    }
+
+    /// Calls `listener` whenever a circuit changes state, for logging or alerting.
+    pub fn on_state_change(
+        mut self,
+        listener: impl Fn(&StateChange) + Send + Sync + 'static,
+    ) -> CircuitBreaker {
+        self.listeners.push(Arc::new(listener));
+        self
+    }
+
+    pub fn state(&self, operation: &str) -> CircuitState {
+        let circuits = self.circuits();
+        circuits
+            .get(operation)
+            .map_or(CircuitState::Closed, |circuit| circuit.state)
+    }
+
+    /// Closes the circuit of `operation`, such as after a manual check that Noah is back.
+    pub fn reset(&self, operation: &'static str) {
+        let change = {
+            let mut circuits = self.circuits();
+            let circuit = circuits.entry(operation).or_default();
+            circuit.failures = 0;
+            (circuit.state != CircuitState::Closed)
+                .then(|| circuit.change(operation, CircuitState::Closed))
+        };
+        self.notify(change);
+    }
+
+    /// Lets a call to `operation` through, or returns how long until the circuit will probe.
+    pub(crate) fn permit(&self, operation: &'static str) -> Result<Permit<'_>, Duration> {
+        let mut change = None;
+        let result = {
+            let mut circuits = self.circuits();
+            let circuit = circuits.entry(operation).or_default();
+            if circuit.state == CircuitState::Open {
+                let elapsed = circuit.opened_at.elapsed();
+                if elapsed < self.open_for {
+                    return Err(self.open_for - elapsed);
+                }
+                change = Some(circuit.change(operation, CircuitState::HalfOpen));
+            }
+            match circuit.state {
+                CircuitState::HalfOpen
+                    if circuit.probes + circuit.successes >= self.half_open_probes =>
+                {
+                    Err(Duration::ZERO)
+                }
+                CircuitState::HalfOpen => {
+                    circuit.probes += 1;
+                    Ok(Permit {
+                        breaker: self,
+                        operation,
+                        probe: true,
+                    })
+                }
+                _ => Ok(Permit {
+                    breaker: self,
+                    operation,
+                    probe: false,
+                }),
+            }
+        };
+        self.notify(change);
+        result
+    }
+
+    fn record(&self, operation: &'static str, probe: bool, failed: bool) {
+        let change = {
+            let mut circuits = self.circuits();
+            let circuit = circuits.entry(operation).or_default();
+            let probing = probe && circuit.state == CircuitState::HalfOpen;
+            if probing {
+                circuit.probes = circuit.probes.saturating_sub(1);
+            }
+            match (circuit.state, failed) {
+                (CircuitState::Closed, true) => {
+                    circuit.failures += 1;
+                    (circuit.failures >= self.failure_threshold)
+                        .then(|| circuit.change(operation, CircuitState::Open))
+                }
+                (CircuitState::Closed, false) => {
+                    circuit.failures = 0;
+                    None
+                }
+                (CircuitState::HalfOpen, true) if probing => {
+                    circuit.failures += 1;
+                    Some(circuit.change(operation, CircuitState::Open))
+                }
+                (CircuitState::HalfOpen, false) if probing => {
+                    circuit.successes += 1;
+                    (circuit.successes >= self.half_open_probes)
+                        .then(|| circuit.change(operation, CircuitState::Closed))
+                }
+                // Calls let through before the circuit opened don't change it any more.
+                _ => None,
+            }
+        };
+        self.notify(change);
+    }
+
+    /// Locks the circuits. They are only changed under the lock by code that can't panic midway,
+    /// and listeners run after it is released, so a poisoned lock still holds consistent state.
+    fn circuits(&self) -> MutexGuard<'_, HashMap<&'static str, Circuit>> {
+        self.circuits.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn notify(&self, change: Option<StateChange>) {
+        if let Some(change) = change {
+            for listener in &self.listeners {
+                listener(&change);
+            }
+        }
+    }
+}
+
+impl Default for CircuitBreaker {
+    fn default() -> Self {
+        CircuitBreaker::new()
+    }
+}
+
+impl fmt::Debug for CircuitBreaker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CircuitBreaker")
+            .field("failure_threshold", &self.failure_threshold)
+            .field("open_for", &self.open_for)
+            .field("half_open_probes", &self.half_open_probes)
+            .field("circuits", &self.circuits)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A call let through by the breaker. Dropping it unrecorded, as when the call is cancelled,
+/// frees its probe slot without counting it either way.
+pub(crate) struct Permit<'a> {
+    breaker: &'a CircuitBreaker,
+    operation: &'static str,
+    probe: bool,
+}
+
+impl Permit<'_> {
+    /// Records the outcome of an attempt: a response with `status`, or a transport error.
+    pub(crate) fn record(mut self, result: &Result<reqwest::Response, reqwest::Error>) {
+        let failed = match result {
+            Ok(response) => response.status().is_server_error(),
+            Err(error) => error.is_timeout() || error.is_connect(),
+        };
+        self.breaker.record(self.operation, self.probe, failed);
+        self.probe = false;
+    }
+}
+
+impl Drop for Permit<'_> {
+    fn drop(&mut self) {
+        if !self.probe {
+            return;
+        }
+        let mut circuits = self.breaker.circuits();
+        if let Some(circuit) = circuits.get_mut(self.operation) {
+            if circuit.state == CircuitState::HalfOpen {
+                circuit.probes = circuit.probes.saturating_sub(1);
+            }
+        }
+    }
+}
@@ -0,0 +1,470 @@
+//! Exporting transaction history, available with the `export` feature.
+//!
+//! [`TransactionExport`] lists every transaction created in a date range, flattens each into an
+//! [`ExportRow`] and writes the rows as CSV or JSON Lines, for accounting tools and spreadsheets.
+//! The columns are the same for every export, whether or not any transaction fills them, and
+//! amounts are exact decimal strings, never passed through a float.
+
+use std::collections::VecDeque;
+use std::error;
+use std::fmt;
+use std::io;
+
+use chrono::{DateTime, Utc};
+use futures_util::stream::{self, Stream, StreamExt};
+use rust_decimal::Decimal;
+use serde::Serialize;
+
+use crate::apis::configuration::Configuration;
+use crate::apis::utilities_api::{self, TransactionsGetError};
+use crate::apis::Error;
+use crate::models;
+
+/// Why an export stopped.
+#[derive(Debug)]
+pub enum ExportError {
+    /// A page of transactions could not be listed.
+    Api(Box<Error<TransactionsGetError>>),
+    /// A transaction has a `Created` time or an amount that doesn't parse.
+    InvalidValue {
+        transaction_id: uuid::Uuid,
+        field: &'static str,
+        value: String,
+    },
+    Io(io::Error),
+}
+
+impl fmt::Display for ExportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExportError::Api(e) => write!(f, "could not list transactions: {e}"),
+            ExportError::InvalidValue {
+                transaction_id,
+                field,
+                value,
+            } => write!(
+                f,
+                "transaction {transaction_id} has an invalid {field} `{value}`"
+            ),
+            ExportError::Io(e) => write!(f, "could not write the export: {e}"),
+        }
+    }
+}
+
+impl error::Error for ExportError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            ExportError::Api(e) => Some(e.as_ref()),
+            ExportError::InvalidValue { .. } => None,
+            ExportError::Io(e) => Some(e),
+        }
+    }
+}
+
+impl From<io::Error> for ExportError {
+    fn from(e: io::Error) -> Self {
+        ExportError::Io(e)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// A header line with [`ExportRow::COLUMNS`], then one line per transaction. Lines end with
+    /// CRLF and fields are quoted as RFC 4180 requires; missing values are empty.
+    Csv,
+    /// One JSON object per line, with every column as a key and `null` for missing values.
+    JsonLines,
+}
+
+/// Declares [`ExportRow`] from one list of fields, so that the struct, its serialized names,
+/// [`ExportRow::COLUMNS`] and [`ExportRow::values`] can't get out of step.
+macro_rules! export_row {
+    ($($field:ident: $type:ty = $column:literal,)*) => {
+        /// A [`models::Transaction`] flattened into columns.
+        ///
+        /// The `FiatPayment`, `Breakdown` and `AdjustmentFor` fields each get columns of their own.
+        /// Breakdown amounts are summed per type; types other than `ChannelFee`, `BusinessFee` and
+        /// `Remaining` are listed in `BreakdownOther` as `Type:Amount`, separated by `; `.
+        #[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+        pub struct ExportRow {
+            $(
+                #[serde(rename = $column)]
+                pub $field: $type,
+            )*
+        }
+
+        impl ExportRow {
+            /// The column names, in the order rows are written.
+            pub const COLUMNS: [&'static str; 24] = [$($column),*];
+
+            /// The values of the row, in the order of [`COLUMNS`](ExportRow::COLUMNS).
+            pub fn values(&self) -> [Option<&str>; 24] {
+                [$(Column::value(&self.$field)),*]
+            }
+        }
+    };
+}
+
+export_row! {
+    id: String = "ID",
+    public_id: Option<String> = "PublicID",
+    created: String = "Created",
+    status: String = "Status",
+    direction: String = "Direction",
+    network: String = "Network",
+    customer_id: Option<String> = "CustomerID",
+    external_id: Option<String> = "ExternalID",
+    crypto_currency: String = "CryptoCurrency",
+    amount: Option<String> = "Amount",
+    network_fee: Option<String> = "NetworkFee",
+    fiat_amount: Option<String> = "FiatAmount",
+    fiat_fee_amount: Option<String> = "FiatFeeAmount",
+    fiat_rate: Option<String> = "FiatRate",
+    fiat_currency: Option<String> = "FiatCurrency",
+    fiat_deposit_id: Option<String> = "FiatDepositID",
+    payment_system_id: Option<String> = "PaymentSystemID",
+    breakdown_channel_fee: Option<String> = "BreakdownChannelFee",
+    breakdown_business_fee: Option<String> = "BreakdownBusinessFee",
+    breakdown_remaining: Option<String> = "BreakdownRemaining",
+    breakdown_other: Option<String> = "BreakdownOther",
+    adjusted_transaction_id: Option<String> = "AdjustedTransactionID",
+    adjustment_id: Option<String> = "AdjustmentID",
+    adjustment_reason: Option<String> = "AdjustmentReason",
+}
+
+/// A field of [`ExportRow`], written as an empty value when missing.
+trait Column {
+    fn value(&self) -> Option<&str>;
+}
+
+impl Column for String {
+    fn value(&self) -> Option<&str> {
+        Some(self)
+    }
+}
+
+impl Column for Option<String> {
+    fn value(&self) -> Option<&str> {
+        self.as_deref()
+    }
+}
+
+impl ExportRow {
+    /// Flattens `transaction`. Amounts must be plain decimals, such as `-12.50`, and are written as
+    /// Noah sent them. Breakdown items of the same type are summed, unless the sum can't be exact.
+    pub fn new(transaction: &models::Transaction) -> Result<ExportRow, ExportError> {
+        let amount =
+            |field: &'static str, value: &str| amount(transaction, field, value).map(str::to_owned);
+        let mut row = ExportRow {
+            id: transaction.id.to_string(),
+            public_id: transaction.public_id.clone(),
+            created: transaction.created.clone(),
+            status: transaction.status.to_string(),
+            direction: transaction.direction.to_string(),
+            network: transaction.network.clone(),
+            customer_id: transaction.customer_id.clone(),
+            external_id: transaction.external_id.clone(),
+            crypto_currency: transaction.crypto_currency.clone(),
+            amount: transaction
+                .amount
+                .as_deref()
+                .map(|value| amount("Amount", value))
+                .transpose()?,
+            network_fee: transaction
+                .network_fee
+                .as_deref()
+                .map(|value| amount("NetworkFee", value))
+                .transpose()?,
+            ..ExportRow::default()
+        };
+        if let Some(payment) = &transaction.fiat_payment {
+            row.fiat_amount = Some(amount("FiatPayment.Amount", &payment.amount)?);
+            row.fiat_fee_amount = Some(amount("FiatPayment.FeeAmount", &payment.fee_amount)?);
+            row.fiat_rate = payment
+                .rate
+                .as_deref()
+                .map(|rate| amount("FiatPayment.Rate", rate))
+                .transpose()?;
+            row.fiat_currency = Some(payment.fiat_currency.clone());
+            row.fiat_deposit_id = payment.fiat_deposit_id.clone();
+            row.payment_system_id = payment.payment_system_id.clone();
+        }
+
+        let mut other = Vec::new();
+        for item in transaction.breakdown.iter().flatten() {
+            let value = amount("Breakdown.Amount", &item.amount)?;
+            let total = match item.r#type.as_str() {
+                "ChannelFee" => &mut row.breakdown_channel_fee,
+                "BusinessFee" => &mut row.breakdown_business_fee,
+                "Remaining" => &mut row.breakdown_remaining,
+                _ => {
+                    other.push(format!("{}:{value}", item.r#type));
+                    continue;
+                }
+            };
+            *total = Some(match total.take() {
+                Some(total) => exact_sum(transaction, &total, &value)?,
+                None => value,
+            });
+        }
+        row.breakdown_other = (!other.is_empty()).then(|| other.join("; "));
+
+        if let Some(adjustment) = &transaction.adjustment_for {
+            row.adjusted_transaction_id = Some(adjustment.adjusted_transaction_id.to_string());
+            row.adjustment_id = Some(adjustment.adjustment_id.to_string());
+            row.adjustment_reason = Some(adjustment.reason.clone());
+        }
+        Ok(row)
+    }
+}
+
+/// The transactions created from `from` up to, but not including, `until`.
+///
+/// Pages are listed in `sort_direction` order, oldest first by default, and listing stops at the
+/// first transaction past the far end of the range, so exporting a recent month doesn't list the
+/// whole history when sorted newest first.
+///
+/// ```no_run
+/// # async fn example(
+/// #     config: &noah_sdk::apis::configuration::Configuration,
+/// # ) -> Result<(), Box<dyn std::error::Error>> {
+/// use std::fs::File;
+/// use std::io::BufWriter;
+///
+/// use chrono::{TimeZone, Utc};
+/// use noah_sdk::export::{ExportFormat, TransactionExport};
+/// use noah_sdk::models::SortDirection;
+///
+/// let september = TransactionExport::new(
+///     config,
+///     Utc.with_ymd_and_hms(2026, 9, 1, 0, 0, 0).unwrap(),
+///     Utc.with_ymd_and_hms(2026, 10, 1, 0, 0, 0).unwrap(),
+/// )
+/// .sort_direction(SortDirection::Desc);
+/// let file = BufWriter::new(File::create("transactions-2026-09.csv")?);
+/// let rows = september.write(ExportFormat::Csv, file).await?;
+/// println!("exported {rows} transactions");
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct TransactionExport {
+    configuration: Configuration,
+    from: DateTime<Utc>,
+    until: DateTime<Utc>,
+    sort_direction: models::SortDirection,
+    page_size: Option<i32>,
+}
+
+impl TransactionExport {
+    pub fn new(
+        configuration: &Configuration,
+        from: DateTime<Utc>,
+        until: DateTime<Utc>,
+    ) -> TransactionExport {
+        TransactionExport {
+            configuration: configuration.clone(),
+            from,
+            until,
+            sort_direction: models::SortDirection::Asc,
+            page_size: None,
+        }
+    }
+
+    pub fn sort_direction(mut self, sort_direction: models::SortDirection) -> TransactionExport {
+        self.sort_direction = sort_direction;
+        self
+    }
+
+    /// How many transactions to list per request. Defaults to the API's page size.
+    pub fn page_size(mut self, page_size: i32) -> TransactionExport {
+        self.page_size = Some(page_size);
+        self
+    }
+
+    /// Yields each transaction in the range, listing pages as they are needed. The stream ends
+    /// after the first error.
+    pub fn transactions(
+        &self,
+    ) -> impl Stream<Item = Result<models::Transaction, ExportError>> + Send + Unpin + 'static {
+        let pager = Pager {
+            export: self.clone(),
+            page: VecDeque::new(),
+            page_token: None,
+            listed: false,
+            done: false,
+        };
+        Box::pin(stream::unfold(pager, |mut pager| async move {
+            let item = pager.next().await?;
+            Some((item, pager))
+        }))
+    }
+
+    /// Writes every transaction in the range to `writer` in `format`, returning how many were
+    /// written. Rows already written stay written if the export fails part way.
+    pub async fn write<W: io::Write>(
+        &self,
+        format: ExportFormat,
+        mut writer: W,
+    ) -> Result<usize, ExportError> {
+        if format == ExportFormat::Csv {
+            write_csv_record(&mut writer, ExportRow::COLUMNS.map(Some))?;
+        }
+        let mut transactions = self.transactions();
+        let mut rows = 0;
+        while let Some(transaction) = transactions.next().await {
+            let row = ExportRow::new(&transaction?)?;
+            match format {
+                ExportFormat::Csv => write_csv_record(&mut writer, row.values())?,
+                ExportFormat::JsonLines => {
+                    serde_json::to_writer(&mut writer, &row).map_err(io::Error::from)?;
+                    writer.write_all(b"\n")?;
+                }
+            }
+            rows += 1;
+        }
+        writer.flush()?;
+        Ok(rows)
+    }
+}
+
+/// Where a transaction is relative to the range, in listing order.
+enum Position {
+    Before,
+    Within,
+    Past,
+}
+
+struct Pager {
+    export: TransactionExport,
+    page: VecDeque<models::Transaction>,
+    page_token: Option<String>,
+    listed: bool,
+    done: bool,
+}
+
+impl Pager {
+    async fn next(&mut self) -> Option<Result<models::Transaction, ExportError>> {
+        loop {
+            if self.done {
+                return None;
+            }
+            while let Some(transaction) = self.page.pop_front() {
+                match self.position(&transaction) {
+                    Ok(Position::Before) => {}
+                    Ok(Position::Within) => return Some(Ok(transaction)),
+                    Ok(Position::Past) => {
+                        self.done = true;
+                        return None;
+                    }
+                    Err(e) => {
+                        self.done = true;
+                        return Some(Err(e));
+                    }
+                }
+            }
+            if self.listed && self.page_token.is_none() {
+                self.done = true;
+                return None;
+            }
+            let page = utilities_api::transactions_get(
+                &self.export.configuration,
+                self.export.page_size,
+                self.page_token.as_deref(),
+                Some(self.export.sort_direction),
+                None,
+            )
+            .await;
+            match page {
+                Ok(page) => {
+                    self.listed = true;
+                    self.page = page.items.into();
+                    self.page_token = page.page_token.filter(|token| !token.is_empty());
+                }
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(ExportError::Api(Box::new(e))));
+                }
+            }
+        }
+    }
+
+    fn position(&self, transaction: &models::Transaction) -> Result<Position, ExportError> {
+        let created = DateTime::parse_from_rfc3339(&transaction.created)
+            .map_err(|_| ExportError::InvalidValue {
+                transaction_id: transaction.id,
+                field: "Created",
+                value: transaction.created.clone(),
+            })?
+            .with_timezone(&Utc);
+        let (from, until) = (self.export.from, self.export.until);
+        Ok(match self.export.sort_direction {
+            models::SortDirection::Asc if created < from => Position::Before,
+            models::SortDirection::Asc if created >= until => Position::Past,
+            models::SortDirection::Desc if created >= until => Position::Before,
+            models::SortDirection::Desc if created < from => Position::Past,
+            _ => Position::Within,
+        })
+    }
+}
+
+/// Checks that `value` is a plain decimal: an optional minus sign, digits, and optionally a point
+/// followed by more digits.
+fn amount<'a>(
+    transaction: &models::Transaction,
+    field: &'static str,
+    value: &'a str,
+) -> Result<&'a str, ExportError> {
+    let digits = |part: &str| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit());
+    let unsigned = value.strip_prefix('-').unwrap_or(value);
+    let plain = match unsigned.split_once('.') {
+        Some((whole, fraction)) => digits(whole) && digits(fraction),
+        None => digits(unsigned),
+    };
+    if plain {
+        Ok(value)
+    } else {
+        Err(invalid(transaction, field, value))
+    }
+}
+
+/// Adds two plain decimals, failing rather than rounding when the sum has more digits than a
+/// [`Decimal`] holds.
+fn exact_sum(transaction: &models::Transaction, a: &str, b: &str) -> Result<String, ExportError> {
+    let parse = |value: &str| {
+        Decimal::from_str_exact(value).map_err(|_| invalid(transaction, "Breakdown.Amount", value))
+    };
+    let (x, y) = (parse(a)?, parse(b)?);
+    match x.checked_add(y) {
+        Some(sum) if sum.checked_sub(y) == Some(x) && sum.checked_sub(x) == Some(y) => {
+            Ok(sum.to_string())
+        }
+        _ => Err(invalid(transaction, "Breakdown.Amount", b)),
+    }
+}
+
+fn invalid(transaction: &models::Transaction, field: &'static str, value: &str) -> ExportError {
+    ExportError::InvalidValue {
+        transaction_id: transaction.id,
+        field,
+        value: value.to_owned(),
+    }
+}
+
+fn write_csv_record<'a>(
+    writer: &mut impl io::Write,
+    fields: impl IntoIterator<Item = Option<&'a str>>,
+) -> io::Result<()> {
+    for (i, field) in fields.into_iter().enumerate() {
+        if i > 0 {
+            writer.write_all(b",")?;
+        }
+        let field = field.unwrap_or_default();
+        if field.contains([',', '"', '\n', '\r']) {
+            write!(writer, "\"{}\"", field.replace('"', "\"\""))?;
+        } else {
+            writer.write_all(field.as_bytes())?;
+        }
+    }
+    writer.write_all(b"\r\n")
+}
@@ -0,0 +1,439 @@
+//! Idempotency keys for Nonce-bearing requests.
+//!
+//! `SellRequest`, `RuleCreateRequest` and the checkout POST requests carry a `Nonce` which Noah uses
+//! to deduplicate retried requests. [`IdempotencyManager`] hands out one stable nonce per business
+//! operation ID and remembers the result once the operation succeeds, so replaying an operation
+//! after a crash reuses the original nonce instead of creating a second transaction.
+
+use std::collections::HashMap;
+use std::error;
+use std::fmt;
+use std::fs;
+use std::future::Future;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::models;
+
+/// What the store remembers about a business operation.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct IdempotencyRecord {
+    /// Caller-supplied business operation ID.
+    pub operation_id: String,
+    /// Nonce sent to Noah for every attempt of this operation.
+    pub nonce: String,
+    /// Serialized result of the operation, once it has succeeded.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<serde_json::Value>,
+}
+
+impl IdempotencyRecord {
+    pub fn new(operation_id: String, nonce: String) -> IdempotencyRecord {
+        IdempotencyRecord {
+            operation_id,
+            nonce,
+            result: None,
+        }
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.result.is_some()
+    }
+}
+
+/// Storage backend for [`IdempotencyRecord`]s.
+///
+/// Implementations must make [`IdempotencyStore::get_or_insert`] atomic, so that two concurrent
+/// callers for the same operation always observe the same nonce.
+pub trait IdempotencyStore: Send + Sync {
+    /// Returns the record for `operation_id`, inserting one with `nonce` if none exists yet.
+    fn get_or_insert(
+        &self,
+        operation_id: &str,
+        nonce: &str,
+    ) -> Result<IdempotencyRecord, StoreError>;
+
+    fn get(&self, operation_id: &str) -> Result<Option<IdempotencyRecord>, StoreError>;
+
+    /// Stores the result of a successful operation.
+    fn complete(&self, operation_id: &str, result: serde_json::Value) -> Result<(), StoreError>;
+
+    fn remove(&self, operation_id: &str) -> Result<(), StoreError>;
+}
+
+impl<S: IdempotencyStore + ?Sized> IdempotencyStore for Arc<S> {
+    fn get_or_insert(
+        &self,
+        operation_id: &str,
+        nonce: &str,
+    ) -> Result<IdempotencyRecord, StoreError> {
+        (**self).get_or_insert(operation_id, nonce)
+    }
+
+    fn get(&self, operation_id: &str) -> Result<Option<IdempotencyRecord>, StoreError> {
+        (**self).get(operation_id)
+    }
+
+    fn complete(&self, operation_id: &str, result: serde_json::Value) -> Result<(), StoreError> {
+        (**self).complete(operation_id, result)
+    }
+
+    fn remove(&self, operation_id: &str) -> Result<(), StoreError> {
+        (**self).remove(operation_id)
+    }
+}
+
+#[derive(Debug)]
+pub enum StoreError {
+    Io(io::Error),
+    Serde(serde_json::Error),
+    /// The operation ID is unknown to the store.
+    NotFound(String),
+    /// A thread panicked while holding the store lock.
+    Poisoned,
+}
+
+impl fmt::Display for StoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StoreError::Io(e) => write!(f, "idempotency store IO error: {e}"),
+            StoreError::Serde(e) => write!(f, "idempotency store serde error: {e}"),
+            StoreError::NotFound(id) => write!(f, "no idempotency record for operation `{id}`"),
+            StoreError::Poisoned => write!(f, "idempotency store lock poisoned"),
+        }
+    }
+}
+
+impl error::Error for StoreError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            StoreError::Io(e) => Some(e),
+            StoreError::Serde(e) => Some(e),
+            StoreError::NotFound(_) | StoreError::Poisoned => None,
+        }
+    }
+}
+
+impl From<io::Error> for StoreError {
+    fn from(e: io::Error) -> Self {
+        StoreError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for StoreError {
+    fn from(e: serde_json::Error) -> Self {
+        StoreError::Serde(e)
+    }
+}
+
+/// Error returned by [`IdempotencyManager::run`].
+#[derive(Debug)]
+pub enum Error<E> {
+    Store(StoreError),
+    Operation(E),
+}
+
+impl<E: fmt::Display> fmt::Display for Error<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Store(e) => write!(f, "{e}"),
+            Error::Operation(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl<E: error::Error + 'static> error::Error for Error<E> {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::Store(e) => Some(e),
+            Error::Operation(e) => Some(e),
+        }
+    }
+}
+
+impl<E> From<StoreError> for Error<E> {
+    fn from(e: StoreError) -> Self {
+        Error::Store(e)
+    }
+}
+
+/// Keeps records in process memory. Records are lost when the process exits.
+#[derive(Debug, Default)]
+pub struct InMemoryStore {
+    records: Mutex<HashMap<String, IdempotencyRecord>>,
+}
+
+impl InMemoryStore {
+    pub fn new() -> InMemoryStore {
+        InMemoryStore::default()
+    }
+}
+
+impl IdempotencyStore for InMemoryStore {
+    fn get_or_insert(
+        &self,
+        operation_id: &str,
+        nonce: &str,
+    ) -> Result<IdempotencyRecord, StoreError> {
+        let mut records = self.records.lock().map_err(|_| StoreError::Poisoned)?;
+        Ok(records
+            .entry(operation_id.to_owned())
+            .or_insert_with(|| IdempotencyRecord::new(operation_id.to_owned(), nonce.to_owned()))
+            .clone())
+    }
+
+    fn get(&self, operation_id: &str) -> Result<Option<IdempotencyRecord>, StoreError> {
+        let records = self.records.lock().map_err(|_| StoreError::Poisoned)?;
+        Ok(records.get(operation_id).cloned())
+    }
+
+    fn complete(&self, operation_id: &str, result: serde_json::Value) -> Result<(), StoreError> {
+        let mut records = self.records.lock().map_err(|_| StoreError::Poisoned)?;
+        match records.get_mut(operation_id) {
+            Some(record) => {
+                record.result = Some(result);
+                Ok(())
+            }
+            None => Err(StoreError::NotFound(operation_id.to_owned())),
+        }
+    }
+
+    fn remove(&self, operation_id: &str) -> Result<(), StoreError> {
+        let mut records = self.records.lock().map_err(|_| StoreError::Poisoned)?;
+        records.remove(operation_id);
+        Ok(())
+    }
+}
+
+/// Keeps records in a single JSON file, rewritten atomically on every change.
+///
+/// The file is re-read on every call, so several stores (or processes run one after the other)
+/// pointing at the same path share their records. Concurrent writers from different processes are
+/// not supported.
+#[derive(Debug)]
+pub struct FileStore {
+    path: PathBuf,
+    lock: Mutex<()>,
+}
+
+impl FileStore {
+    pub fn new(path: impl Into<PathBuf>) -> FileStore {
+        FileStore {
+            path: path.into(),
+            lock: Mutex::new(()),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn read(&self) -> Result<HashMap<String, IdempotencyRecord>, StoreError> {
+        match fs::read(&self.path) {
+            Ok(bytes) if bytes.is_empty() => Ok(HashMap::new()),
+            Ok(bytes) => Ok(serde_json::from_slice(&bytes)?),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(HashMap::new()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Replaces the file atomically. The temporary file is synced before the rename and the
+    /// directory after it, so a crash leaves either the old records or the new ones on disk.
+    fn write(&self, records: &HashMap<String, IdempotencyRecord>) -> Result<(), StoreError> {
+        let mut tmp = self.path.clone().into_os_string();
+        tmp.push(".tmp");
+        let mut file = fs::File::create(&tmp)?;
+        file.write_all(&serde_json::to_vec_pretty(records)?)?;
+        file.sync_all()?;
+        drop(file);
+        fs::rename(&tmp, &self.path)?;
+        sync_dir(&self.path)?;
+        Ok(())
+    }
+}
+
+/// Syncs the directory holding `path`, which makes a rename into it durable.
+#[cfg(unix)]
+pub(crate) fn sync_dir(path: &Path) -> io::Result<()> {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    fs::File::open(dir)?.sync_all()
+}
+
+/// Other platforms can't open a directory to sync it, so only the file is synced there.
+#[cfg(not(unix))]
+pub(crate) fn sync_dir(_path: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+impl IdempotencyStore for FileStore {
+    fn get_or_insert(
+        &self,
+        operation_id: &str,
+        nonce: &str,
+    ) -> Result<IdempotencyRecord, StoreError> {
+        let _guard = self.lock.lock().map_err(|_| StoreError::Poisoned)?;
+        let mut records = self.read()?;
+        if let Some(record) = records.get(operation_id) {
+            return Ok(record.clone());
+        }
+        let record = IdempotencyRecord::new(operation_id.to_owned(), nonce.to_owned());
+        records.insert(operation_id.to_owned(), record.clone());
+        self.write(&records)?;
+        Ok(record)
+    }
+
+    fn get(&self, operation_id: &str) -> Result<Option<IdempotencyRecord>, StoreError> {
+        let _guard = self.lock.lock().map_err(|_| StoreError::Poisoned)?;
+        Ok(self.read()?.remove(operation_id))
+    }
+
+    fn complete(&self, operation_id: &str, result: serde_json::Value) -> Result<(), StoreError> {
+        let _guard = self.lock.lock().map_err(|_| StoreError::Poisoned)?;
+        let mut records = self.read()?;
+        match records.get_mut(operation_id) {
+            Some(record) => record.result = Some(result),
+            None => return Err(StoreError::NotFound(operation_id.to_owned())),
+        }
+        self.write(&records)
+    }
+
+    fn remove(&self, operation_id: &str) -> Result<(), StoreError> {
+        let _guard = self.lock.lock().map_err(|_| StoreError::Poisoned)?;
+        let mut records = self.read()?;
+        if records.remove(operation_id).is_some() {
+            self.write(&records)?;
+        }
+        Ok(())
+    }
+}
+
+/// Request models that carry a Noah `Nonce`.
+pub trait Nonced {
+    fn nonce_mut(&mut self) -> &mut String;
+}
+
+impl Nonced for models::SellRequest {
+    fn nonce_mut(&mut self) -> &mut String {
+        &mut self.nonce
+    }
+}
+
+impl Nonced for models::RuleCreateRequest {
+    fn nonce_mut(&mut self) -> &mut String {
+        &mut self.nonce
+    }
+}
+
+impl Nonced for models::CheckoutPayinCryptoPostRequest {
+    fn nonce_mut(&mut self) -> &mut String {
+        &mut self.nonce
+    }
+}
+
+impl Nonced for models::CheckoutPayinFiatPostRequest {
+    fn nonce_mut(&mut self) -> &mut String {
+        &mut self.nonce
+    }
+}
+
+impl Nonced for models::CheckoutPayoutFiatPostRequest {
+    fn nonce_mut(&mut self) -> &mut String {
+        &mut self.nonce
+    }
+}
+
+/// Hands out stable nonces per business operation and remembers operation results.
+#[derive(Debug, Clone)]
+pub struct IdempotencyManager<S> {
+    store: S,
+}
+
+impl<S: IdempotencyStore> IdempotencyManager<S> {
+    pub fn new(store: S) -> IdempotencyManager<S> {
+        IdempotencyManager { store }
+    }
+
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    /// Returns the nonce for `operation_id`, generating and persisting a new UUID on first use.
+    pub fn nonce(&self, operation_id: &str) -> Result<String, StoreError> {
+        let candidate = uuid::Uuid::new_v4().to_string();
+        Ok(self.store.get_or_insert(operation_id, &candidate)?.nonce)
+    }
+
+    /// Sets the nonce of `request` to the stable nonce for `operation_id`.
+    pub fn apply<R: Nonced>(&self, operation_id: &str, mut request: R) -> Result<R, StoreError> {
+        *request.nonce_mut() = self.nonce(operation_id)?;
+        Ok(request)
+    }
+
+    /// Returns the stored result of `operation_id`, if it has completed.
+    pub fn result<T: DeserializeOwned>(&self, operation_id: &str) -> Result<Option<T>, StoreError> {
+        match self.store.get(operation_id)? {
+            Some(IdempotencyRecord {
+                result: Some(value),
+                ..
+            }) => Ok(Some(serde_json::from_value(value)?)),
+            _ => Ok(None),
+        }
+    }
+
+    /// Records `result` as the outcome of `operation_id`.
+    pub fn complete<T: Serialize>(&self, operation_id: &str, result: &T) -> Result<(), StoreError> {
+        self.store
+            .complete(operation_id, serde_json::to_value(result)?)
+    }
+
+    /// Forgets `operation_id`, so the next call starts over with a fresh nonce.
+    pub fn forget(&self, operation_id: &str) -> Result<(), StoreError> {
+        self.store.remove(operation_id)
+    }
+
+    /// Runs `operation` at most once to completion.
+    ///
+    /// If `operation_id` already completed, its stored result is returned without calling
+    /// `operation`. Otherwise `operation` is called with the stable nonce for `operation_id` and
+    /// its result is stored on success. Failed attempts keep the nonce, so the next call retries
+    /// idempotently.
+    ///
+    /// ```no_run
+    /// # async fn example(
+    /// #     configuration: &noah_sdk::apis::configuration::Configuration,
+    /// #     request: noah_sdk::models::SellRequest,
+    /// # ) -> Result<(), Box<dyn std::error::Error>> {
+    /// use noah_sdk::apis::payout_api;
+    /// use noah_sdk::idempotency::{FileStore, IdempotencyManager};
+    ///
+    /// let idempotency = IdempotencyManager::new(FileStore::new("sells.json"));
+    /// let response = idempotency
+    ///     .run("payout-2024-06-01-42", |nonce| {
+    ///         let request = noah_sdk::models::SellRequest { nonce, ..request };
+    ///         payout_api::transactions_sell_post(configuration, request, None)
+    ///     })
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn run<T, E, F, Fut>(&self, operation_id: &str, operation: F) -> Result<T, Error<E>>
+    where
+        T: Serialize + DeserializeOwned,
+        F: FnOnce(String) -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        if let Some(result) = self.result(operation_id)? {
+            return Ok(result);
+        }
+        let nonce = self.nonce(operation_id)?;
+        let result = operation(nonce).await.map_err(Error::Operation)?;
+        self.complete(operation_id, &result)?;
+        Ok(result)
+    }
+}
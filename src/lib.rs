@@ -8,4 +8,22 @@ extern crate serde_repr;
 extern crate url;
 
 pub mod apis;
+pub mod cache;
+pub mod channels;
+pub mod circuit_breaker;
+#[cfg(feature = "export")]
+pub mod export;
+pub mod idempotency;
 pub mod models;
+pub mod payouts;
+pub mod polling;
+pub mod prices;
+pub mod rate_limit;
+pub mod redact;
+pub mod sell;
+#[cfg(feature = "test-util")]
+pub mod testing;
+#[cfg(feature = "webhooks")]
+pub mod webhooks;
+
+pub use rust_decimal::Decimal;
@@ -1,7 +1,7 @@
 use crate::models;
 use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct AssociateInformationInput {
     /// Associate ID.
     #[serde(rename = "ID")]
@@ -51,6 +51,26 @@ impl AssociateInformationInput {
         }
     }
 }
+
+impl std::fmt::Debug for AssociateInformationInput {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AssociateInformationInput")
+            .field("id", &self.id)
+            .field("relationship_types", &self.relationship_types)
+            .field("full_name", &self.full_name)
+            .field(
+                "date_of_birth",
+                &crate::redact::Redacted(&self.date_of_birth),
+            )
+            .field("identities", &self.identities)
+            .field("tax_residence_country", &self.tax_residence_country)
+            .field("email", &crate::redact::Redacted(&self.email))
+            .field("phone_number", &crate::redact::Redacted(&self.phone_number))
+            .field("residential_address", &self.residential_address)
+            .field("ubo", &self.ubo)
+            .finish()
+    }
+}
 /// Relationship types.
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
 pub enum RelationshipTypes {
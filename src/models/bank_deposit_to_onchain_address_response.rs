@@ -1,7 +1,7 @@
 use crate::models;
 use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct BankDepositToOnchainAddressResponse {
     /// Existing payment method id to be used.
     #[serde(rename = "PaymentMethodID")]
@@ -41,3 +41,21 @@ impl BankDepositToOnchainAddressResponse {
         }
     }
 }
+
+impl std::fmt::Debug for BankDepositToOnchainAddressResponse {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BankDepositToOnchainAddressResponse")
+            .field("payment_method_id", &self.payment_method_id)
+            .field("payment_method_type", &self.payment_method_type)
+            .field("reference", &self.reference)
+            .field(
+                "account_number",
+                &crate::redact::Redacted(&self.account_number),
+            )
+            .field("account_holder_name", &self.account_holder_name)
+            .field("bank_code", &self.bank_code)
+            .field("bank_name", &self.bank_name)
+            .field("bank_address", &self.bank_address)
+            .finish()
+    }
+}
@@ -1,9 +1,9 @@
 use crate::models;
 use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct BusinessCustomer {
-    #[serde(rename = "Type")]
+    #[serde(rename = "Type", default, skip_serializing)]
     pub r#type: Type,
     /// A unique ID which identifies the customer in the Business' internal system and in NOAH.
     #[serde(rename = "CustomerID")]
@@ -62,6 +62,25 @@ impl BusinessCustomer {
         }
     }
 }
+
+impl std::fmt::Debug for BusinessCustomer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BusinessCustomer")
+            .field("type", &self.r#type)
+            .field("customer_id", &self.customer_id)
+            .field("created", &self.created)
+            .field("registered_name", &self.registered_name)
+            .field("email", &crate::redact::Redacted(&self.email))
+            .field("registration_number", &self.registration_number)
+            .field("registration_country", &self.registration_country)
+            .field("registered_address", &self.registered_address)
+            .field("incorporation_date", &self.incorporation_date)
+            .field("verification", &self.verification)
+            .field("verifications", &self.verifications)
+            .field("metadata", &self.metadata)
+            .finish()
+    }
+}
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
 pub enum Type {
     #[serde(rename = "Business")]
@@ -1,9 +1,9 @@
 use crate::models;
 use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct BusinessCustomerInput {
-    #[serde(rename = "Type")]
+    #[serde(rename = "Type", default, skip_serializing)]
     pub r#type: Type,
     /// Name of the business.
     #[serde(rename = "RegisteredName")]
@@ -43,6 +43,20 @@ impl BusinessCustomerInput {
         }
     }
 }
+
+impl std::fmt::Debug for BusinessCustomerInput {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BusinessCustomerInput")
+            .field("type", &self.r#type)
+            .field("registered_name", &self.registered_name)
+            .field("email", &crate::redact::Redacted(&self.email))
+            .field("registration_number", &self.registration_number)
+            .field("registration_country", &self.registration_country)
+            .field("registered_address", &self.registered_address)
+            .field("incorporation_date", &self.incorporation_date)
+            .finish()
+    }
+}
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
 pub enum Type {
     #[serde(rename = "Business")]
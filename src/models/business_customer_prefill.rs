@@ -1,9 +1,9 @@
 use crate::models;
 use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct BusinessCustomerPrefill {
-    #[serde(rename = "Type")]
+    #[serde(rename = "Type", default, skip_serializing)]
     pub r#type: Type,
     /// ISO 3166-1 alpha-2 country code.
     #[serde(
@@ -103,6 +103,40 @@ impl BusinessCustomerPrefill {
         }
     }
 }
+
+impl std::fmt::Debug for BusinessCustomerPrefill {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BusinessCustomerPrefill")
+            .field("type", &self.r#type)
+            .field("registration_country", &self.registration_country)
+            .field("company_name", &self.company_name)
+            .field("registration_number", &self.registration_number)
+            .field("legal_address", &self.legal_address)
+            .field("incorporation_date", &self.incorporation_date)
+            .field("entity_type", &self.entity_type)
+            .field("tax_id", &crate::redact::Redacted(&self.tax_id))
+            .field("primary_website", &self.primary_website)
+            .field("trade_name", &self.trade_name)
+            .field(
+                "registered_foreign_branches",
+                &self.registered_foreign_branches,
+            )
+            .field("primary_physical_address", &self.primary_physical_address)
+            .field("ownership_type", &self.ownership_type)
+            .field("legal_entity_identifier", &self.legal_entity_identifier)
+            .field("naics_code", &self.naics_code)
+            .field("source_of_funds", &self.source_of_funds)
+            .field("financials_usd", &self.financials_usd)
+            .field(
+                "monthly_transaction_frequency",
+                &self.monthly_transaction_frequency,
+            )
+            .field("associates", &self.associates)
+            .field("business_associates", &self.business_associates)
+            .field("amlctf_regulated", &self.amlctf_regulated)
+            .finish()
+    }
+}
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
 pub enum Type {
     #[serde(rename = "BusinessCustomerPrefill")]
@@ -1,7 +1,7 @@
 use crate::models;
 use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct CustomerIdentity {
     /// ISO 3166-1 alpha-2 country code.
     #[serde(rename = "IssuingCountry")]
@@ -28,3 +28,15 @@ impl CustomerIdentity {
         }
     }
 }
+
+impl std::fmt::Debug for CustomerIdentity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CustomerIdentity")
+            .field("issuing_country", &self.issuing_country)
+            .field("id_number", &crate::redact::Redacted(&self.id_number))
+            .field("issued_date", &self.issued_date)
+            .field("expiry_date", &self.expiry_date)
+            .field("id_type", &self.id_type)
+            .finish()
+    }
+}
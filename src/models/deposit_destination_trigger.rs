@@ -3,7 +3,7 @@ use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
 pub struct DepositDestinationTrigger {
-    #[serde(rename = "Type")]
+    #[serde(rename = "Type", default, skip_serializing)]
     pub r#type: Type,
     #[serde(rename = "Amount")]
     pub amount: String,
@@ -3,7 +3,7 @@ use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
 pub struct DepositSourceTrigger {
-    #[serde(rename = "Type")]
+    #[serde(rename = "Type", default, skip_serializing)]
     pub r#type: Type,
     /// Conditions that trigger the rule.
     #[serde(rename = "Conditions")]
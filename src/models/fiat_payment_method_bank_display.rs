@@ -1,9 +1,9 @@
 use crate::models;
 use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct FiatPaymentMethodBankDisplay {
-    #[serde(rename = "Type")]
+    #[serde(rename = "Type", default, skip_serializing)]
     pub r#type: Type,
     #[serde(rename = "AccountNumber", skip_serializing_if = "Option::is_none")]
     pub account_number: Option<String>,
@@ -20,6 +20,19 @@ impl FiatPaymentMethodBankDisplay {
         }
     }
 }
+
+impl std::fmt::Debug for FiatPaymentMethodBankDisplay {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FiatPaymentMethodBankDisplay")
+            .field("type", &self.r#type)
+            .field(
+                "account_number",
+                &crate::redact::Redacted(&self.account_number),
+            )
+            .field("bank_code", &crate::redact::Redacted(&self.bank_code))
+            .finish()
+    }
+}
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
 pub enum Type {
     #[serde(rename = "FiatPaymentMethodBankDisplay")]
@@ -3,7 +3,7 @@ use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
 pub struct FiatPaymentMethodIdentifierDisplay {
-    #[serde(rename = "Type")]
+    #[serde(rename = "Type", default, skip_serializing)]
     pub r#type: Type,
     /// Identifier type:  * PhoneNumber  * Email  * TaxID
     #[serde(rename = "IdentifierType")]
@@ -1,7 +1,7 @@
 use crate::models;
 use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct FullName {
     /// user's first name
     #[serde(rename = "FirstName")]
@@ -23,3 +23,13 @@ impl FullName {
         }
     }
 }
+
+impl std::fmt::Debug for FullName {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FullName")
+            .field("first_name", &crate::redact::Redacted(&self.first_name))
+            .field("last_name", &crate::redact::Redacted(&self.last_name))
+            .field("middle_name", &crate::redact::Redacted(&self.middle_name))
+            .finish()
+    }
+}
@@ -1,9 +1,9 @@
 use crate::models;
 use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct IndividualCustomer {
-    #[serde(rename = "Type")]
+    #[serde(rename = "Type", default, skip_serializing)]
     pub r#type: Type,
     /// A unique ID which identifies the customer in the Business' internal system and in NOAH.
     #[serde(rename = "CustomerID")]
@@ -52,6 +52,26 @@ impl IndividualCustomer {
         }
     }
 }
+
+impl std::fmt::Debug for IndividualCustomer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("IndividualCustomer")
+            .field("type", &self.r#type)
+            .field("customer_id", &self.customer_id)
+            .field("created", &self.created)
+            .field(
+                "date_of_birth",
+                &crate::redact::Redacted(&self.date_of_birth),
+            )
+            .field("full_name", &self.full_name)
+            .field("identities", &self.identities)
+            .field("primary_residence", &self.primary_residence)
+            .field("verification", &self.verification)
+            .field("verifications", &self.verifications)
+            .field("metadata", &self.metadata)
+            .finish()
+    }
+}
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
 pub enum Type {
     #[serde(rename = "Individual")]
@@ -1,9 +1,9 @@
 use crate::models;
 use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct IndividualCustomerInput {
-    #[serde(rename = "Type")]
+    #[serde(rename = "Type", default, skip_serializing)]
     pub r#type: Type,
     #[serde(rename = "FullName")]
     pub full_name: Box<models::FullName>,
@@ -39,6 +39,23 @@ impl IndividualCustomerInput {
         }
     }
 }
+
+impl std::fmt::Debug for IndividualCustomerInput {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("IndividualCustomerInput")
+            .field("type", &self.r#type)
+            .field("full_name", &self.full_name)
+            .field(
+                "date_of_birth",
+                &crate::redact::Redacted(&self.date_of_birth),
+            )
+            .field("email", &crate::redact::Redacted(&self.email))
+            .field("phone_number", &crate::redact::Redacted(&self.phone_number))
+            .field("identities", &self.identities)
+            .field("primary_residence", &self.primary_residence)
+            .finish()
+    }
+}
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
 pub enum Type {
     #[serde(rename = "Individual")]
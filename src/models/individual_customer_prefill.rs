@@ -1,9 +1,9 @@
 use crate::models;
 use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct IndividualCustomerPrefill {
-    #[serde(rename = "Type")]
+    #[serde(rename = "Type", default, skip_serializing)]
     pub r#type: Type,
     #[serde(rename = "FullName", skip_serializing_if = "Option::is_none")]
     pub full_name: Option<Box<models::FullName>>,
@@ -66,6 +66,30 @@ impl IndividualCustomerPrefill {
         }
     }
 }
+
+impl std::fmt::Debug for IndividualCustomerPrefill {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("IndividualCustomerPrefill")
+            .field("type", &self.r#type)
+            .field("full_name", &self.full_name)
+            .field(
+                "date_of_birth",
+                &crate::redact::Redacted(&self.date_of_birth),
+            )
+            .field("identities", &self.identities)
+            .field("primary_residence", &self.primary_residence)
+            .field("citizenship", &self.citizenship)
+            .field("tax_residence_country", &self.tax_residence_country)
+            .field("email", &crate::redact::Redacted(&self.email))
+            .field("phone_number", &crate::redact::Redacted(&self.phone_number))
+            .field("source_of_income", &self.source_of_income)
+            .field("employment_status", &self.employment_status)
+            .field("work_industry", &self.work_industry)
+            .field("financials_usd", &self.financials_usd)
+            .field("transaction_frequency", &self.transaction_frequency)
+            .finish()
+    }
+}
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
 pub enum Type {
     #[serde(rename = "IndividualCustomerPrefill")]
@@ -1,7 +1,7 @@
 use crate::models;
 use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct SenderPaymentMethod {
     #[serde(rename = "FullName", skip_serializing_if = "Option::is_none")]
     pub full_name: Option<String>,
@@ -17,3 +17,12 @@ impl SenderPaymentMethod {
         }
     }
 }
+
+impl std::fmt::Debug for SenderPaymentMethod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SenderPaymentMethod")
+            .field("full_name", &crate::redact::Redacted(&self.full_name))
+            .field("details", &self.details)
+            .finish()
+    }
+}
@@ -1,7 +1,7 @@
 use crate::models;
 use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct StreetAddress {
     /// Street: the primary name of an address's street.
     #[serde(rename = "Street")]
@@ -41,3 +41,16 @@ impl StreetAddress {
         }
     }
 }
+
+impl std::fmt::Debug for StreetAddress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StreetAddress")
+            .field("street", &crate::redact::Redacted(&self.street))
+            .field("street2", &crate::redact::Redacted(&self.street2))
+            .field("city", &self.city)
+            .field("post_code", &crate::redact::Redacted(&self.post_code))
+            .field("state", &self.state)
+            .field("country", &self.country)
+            .finish()
+    }
+}
@@ -1,9 +1,9 @@
 use crate::models;
 use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct SumSubToken {
-    #[serde(rename = "Type")]
+    #[serde(rename = "Type", default, skip_serializing)]
     pub r#type: Type,
     /// Sumsub token to share applicant.
     #[serde(rename = "Token")]
@@ -15,6 +15,15 @@ impl SumSubToken {
         SumSubToken { r#type, token }
     }
 }
+
+impl std::fmt::Debug for SumSubToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SumSubToken")
+            .field("type", &self.r#type)
+            .field("token", &crate::redact::Redacted(&self.token))
+            .finish()
+    }
+}
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
 pub enum Type {
     #[serde(rename = "SumSubToken")]
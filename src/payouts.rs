@@ -0,0 +1,318 @@
+//! Paying out to many customers in one batch.
+//!
+//! [`BulkPayout`] sells crypto for each [`PayoutInstruction`] with a [`SellFlow`], several at a
+//! time, and reports what happened to each. The `ExternalID` of an instruction is both the nonce
+//! of its sell and the way to find it again, so running a batch a second time, such as after a
+//! crash, never pays anyone twice.
+
+use std::collections::{HashMap, HashSet};
+use std::error;
+use std::fmt;
+
+use futures_util::stream::{self, StreamExt};
+
+use crate::apis::configuration::Configuration;
+use crate::apis::utilities_api::{self, ChannelsChannelIdGetError, TransactionsGetError};
+use crate::apis::Error;
+use crate::models;
+use crate::sell::{SellError, SellFlow};
+
+/// One payout: `fiat_amount` to a customer's payment method through a sell channel.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PayoutInstruction {
+    pub customer_id: String,
+    pub channel_id: String,
+    pub payment_method_id: String,
+    pub fiat_amount: String,
+    /// Identifies the payout across runs. It must be unique, and stay the same when the batch is
+    /// run again.
+    pub external_id: String,
+}
+
+/// Why a payout was not made.
+#[derive(Debug)]
+pub enum PayoutError {
+    /// An earlier instruction in the batch has the same external ID.
+    DuplicateExternalId,
+    /// The channel could not be fetched.
+    Channel(Box<Error<ChannelsChannelIdGetError>>),
+    Sell(SellError),
+    /// An earlier run submitted the sell and it failed. Its nonce is spent, so paying the
+    /// instruction again needs a new external ID.
+    TransactionFailed,
+}
+
+impl fmt::Display for PayoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PayoutError::DuplicateExternalId => write!(f, "duplicate external ID"),
+            PayoutError::Channel(e) => write!(f, "could not fetch the channel: {e}"),
+            PayoutError::Sell(e) => write!(f, "the sell failed: {e}"),
+            PayoutError::TransactionFailed => write!(f, "the submitted transaction failed"),
+        }
+    }
+}
+
+impl error::Error for PayoutError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            PayoutError::DuplicateExternalId | PayoutError::TransactionFailed => None,
+            PayoutError::Channel(e) => Some(e.as_ref()),
+            PayoutError::Sell(e) => Some(e),
+        }
+    }
+}
+
+/// What happened to one [`PayoutInstruction`].
+#[derive(Debug)]
+pub struct PayoutResult {
+    pub external_id: String,
+    /// The sell transaction, if one was created.
+    pub transaction_id: Option<uuid::Uuid>,
+    pub status: Option<models::TransactionStatus>,
+    /// Whether the transaction was submitted by an earlier run and only found in this one.
+    pub resumed: bool,
+    pub error: Option<PayoutError>,
+}
+
+impl PayoutResult {
+    pub fn is_ok(&self) -> bool {
+        self.error.is_none()
+    }
+
+    fn submitted(external_id: &str, transaction: &models::Transaction, resumed: bool) -> Self {
+        let error = (transaction.status == models::TransactionStatus::Failed)
+            .then_some(PayoutError::TransactionFailed);
+        PayoutResult {
+            external_id: external_id.to_owned(),
+            transaction_id: Some(transaction.id),
+            status: Some(transaction.status),
+            resumed,
+            error,
+        }
+    }
+
+    fn failed(external_id: &str, error: PayoutError) -> Self {
+        let transaction = match &error {
+            PayoutError::Sell(SellError::Wait { transaction, .. }) => Some(transaction),
+            _ => None,
+        };
+        PayoutResult {
+            external_id: external_id.to_owned(),
+            transaction_id: transaction.map(|transaction| transaction.id),
+            status: transaction.map(|transaction| transaction.status),
+            resumed: false,
+            error: Some(error),
+        }
+    }
+}
+
+/// The results of a batch, in the order of its instructions.
+#[derive(Debug)]
+pub struct PayoutReport {
+    pub results: Vec<PayoutResult>,
+}
+
+impl PayoutReport {
+    /// Whether every instruction has a transaction.
+    pub fn is_complete(&self) -> bool {
+        self.results.iter().all(PayoutResult::is_ok)
+    }
+
+    pub fn failed(&self) -> impl Iterator<Item = &PayoutResult> {
+        self.results.iter().filter(|result| !result.is_ok())
+    }
+}
+
+/// Runs a batch of payouts, a few at a time.
+///
+/// For each instruction, the channel is fetched and a [`SellFlow`] prepares and executes the sell,
+/// with the instruction's external ID as its nonce and as the `ExternalID` of the transaction.
+/// Before selling, [`run`](BulkPayout::run) lists the transactions already made and skips the
+/// instructions they belong to, reporting them as `resumed`. A failed batch can so be run again
+/// as it is: the payouts it made are not repeated, and a sell that failed on the way back is
+/// replayed by its nonce rather than made twice. A transaction that Noah marked as failed is
+/// reported as [`PayoutError::TransactionFailed`], since its nonce can't be used again.
+///
+/// ```no_run
+/// # async fn example(
+/// #     config: &noah_sdk::apis::configuration::Configuration,
+/// # ) -> Result<(), Box<dyn std::error::Error>> {
+/// use noah_sdk::payouts::{BulkPayout, PayoutInstruction};
+///
+/// let instructions = vec![PayoutInstruction {
+///     customer_id: "customer-123".to_owned(),
+///     channel_id: "1f0b7b3a-5c3e-4a53-9a8f-6c0b3f1d2e4a".to_owned(),
+///     payment_method_id: "pm-456".to_owned(),
+///     fiat_amount: "2500.00".to_owned(),
+///     external_id: "payroll-2026-10/customer-123".to_owned(),
+/// }];
+/// let report = BulkPayout::new(config, "USDC")
+///     .concurrency(8)
+///     .max_slippage_bps(50)
+///     .run(&instructions)
+///     .await?;
+/// for failed in report.failed() {
+///     eprintln!("{}: {}", failed.external_id, failed.error.as_ref().unwrap());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct BulkPayout {
+    configuration: Configuration,
+    crypto_currency: String,
+    concurrency: usize,
+    max_slippage_bps: Option<u32>,
+    resume: bool,
+}
+
+impl BulkPayout {
+    pub fn new(configuration: &Configuration, crypto_currency: impl Into<String>) -> BulkPayout {
+        BulkPayout {
+            configuration: configuration.clone(),
+            crypto_currency: crypto_currency.into(),
+            concurrency: 4,
+            max_slippage_bps: None,
+            resume: true,
+        }
+    }
+
+    /// How many payouts run at once. Defaults to 4.
+    pub fn concurrency(mut self, concurrency: usize) -> BulkPayout {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Refuses each sell whose rate slipped more than `bps` below its channel rate, as
+    /// [`SellFlow::max_slippage_bps`] does.
+    pub fn max_slippage_bps(mut self, bps: u32) -> BulkPayout {
+        self.max_slippage_bps = Some(bps);
+        self
+    }
+
+    /// Whether to look for transactions submitted by earlier runs first. Defaults to `true`; turn
+    /// it off for a batch known to be new, to skip listing the transaction history.
+    pub fn resume(mut self, resume: bool) -> BulkPayout {
+        self.resume = resume;
+        self
+    }
+
+    /// Pays out `instructions`, returning one result for each.
+    ///
+    /// Only listing the transactions of earlier runs fails the whole batch. Any other error is
+    /// reported with the instruction it belongs to.
+    pub async fn run(
+        &self,
+        instructions: &[PayoutInstruction],
+    ) -> Result<PayoutReport, Error<TransactionsGetError>> {
+        let mut submitted = if self.resume {
+            self.submitted(instructions).await?
+        } else {
+            HashMap::new()
+        };
+        let mut seen = HashSet::new();
+        let mut results: Vec<Option<PayoutResult>> = Vec::with_capacity(instructions.len());
+        let mut pending = Vec::new();
+        for (index, instruction) in instructions.iter().enumerate() {
+            let external_id = &instruction.external_id;
+            if !seen.insert(external_id.as_str()) {
+                results.push(Some(PayoutResult::failed(
+                    external_id,
+                    PayoutError::DuplicateExternalId,
+                )));
+            } else if let Some(transaction) = submitted.remove(external_id) {
+                results.push(Some(PayoutResult::submitted(
+                    external_id,
+                    &transaction,
+                    true,
+                )));
+            } else {
+                results.push(None);
+                pending.push((index, instruction));
+            }
+        }
+
+        let mut paid = stream::iter(pending)
+            .map(|(index, instruction)| async move { (index, self.pay(instruction).await) })
+            .buffer_unordered(self.concurrency);
+        while let Some((index, result)) = paid.next().await {
+            results[index] = Some(result);
+        }
+        Ok(PayoutReport {
+            results: results.into_iter().flatten().collect(),
+        })
+    }
+
+    async fn pay(&self, instruction: &PayoutInstruction) -> PayoutResult {
+        let external_id = &instruction.external_id;
+        let channel = match utilities_api::channels_channel_id_get(
+            &self.configuration,
+            &instruction.channel_id,
+            &self.crypto_currency,
+            Some(&instruction.fiat_amount),
+            Some(&instruction.customer_id),
+            None,
+        )
+        .await
+        {
+            Ok(channel) => channel,
+            Err(e) => return PayoutResult::failed(external_id, PayoutError::Channel(Box::new(e))),
+        };
+        let mut flow = SellFlow::new(
+            &self.configuration,
+            &self.crypto_currency,
+            &instruction.fiat_amount,
+        )
+        .channel(channel)
+        .customer_id(&instruction.customer_id)
+        .payment_method_id(&instruction.payment_method_id)
+        .nonce(external_id)
+        .external_id(external_id);
+        if let Some(bps) = self.max_slippage_bps {
+            flow = flow.max_slippage_bps(bps);
+        }
+        match flow.run().await {
+            Ok(receipt) => PayoutResult::submitted(external_id, &receipt.transaction, false),
+            Err(e) => PayoutResult::failed(external_id, PayoutError::Sell(e)),
+        }
+    }
+
+    /// The transactions whose external IDs are among those of `instructions`.
+    async fn submitted(
+        &self,
+        instructions: &[PayoutInstruction],
+    ) -> Result<HashMap<String, models::Transaction>, Error<TransactionsGetError>> {
+        let wanted: HashSet<&str> = instructions
+            .iter()
+            .map(|instruction| instruction.external_id.as_str())
+            .collect();
+        let mut found = HashMap::new();
+        let mut page_token = None;
+        loop {
+            let page = utilities_api::transactions_get(
+                &self.configuration,
+                None,
+                page_token.as_deref(),
+                None,
+                None,
+            )
+            .await?;
+            for transaction in page.items {
+                if transaction.direction != models::TransactionDirection::Out {
+                    continue;
+                }
+                if let Some(external_id) = &transaction.external_id {
+                    if wanted.contains(external_id.as_str()) {
+                        found.entry(external_id.clone()).or_insert(transaction);
+                    }
+                }
+            }
+            match page.page_token {
+                Some(token) if !token.is_empty() => page_token = Some(token),
+                _ => break,
+            }
+        }
+        Ok(found)
+    }
+}
@@ -0,0 +1,245 @@
+//! Waiting for transactions to finish without webhooks.
+//!
+//! [`wait_for_transaction`] polls `GET /transactions/{TransactionID}` with a growing interval and
+//! yields the transaction each time its status changes, until it is `Settled` or `Failed`. Use it
+//! where Noah's webhooks can't reach the application.
+
+use std::error;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use futures_util::stream::{self, Stream};
+
+use crate::apis::configuration::Configuration;
+use crate::apis::utilities_api::{self, TransactionsTransactionIdGetError};
+use crate::apis::Error;
+use crate::models;
+
+/// How often and for how long to poll.
+///
+/// The interval starts at `initial_interval` and doubles after every poll up to
+/// `max_interval`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PollPolicy {
+    pub initial_interval: Duration,
+    pub max_interval: Duration,
+    /// How long to wait for the transaction to finish, from the first poll.
+    pub timeout: Duration,
+}
+
+impl PollPolicy {
+    pub fn new(timeout: Duration) -> PollPolicy {
+        PollPolicy {
+            timeout,
+            ..PollPolicy::default()
+        }
+    }
+
+    pub fn initial_interval(mut self, interval: Duration) -> PollPolicy {
+        self.initial_interval = interval;
+        self
+    }
+
+    pub fn max_interval(mut self, interval: Duration) -> PollPolicy {
+        self.max_interval = interval;
+        self
+    }
+
+    /// Delay before poll number `poll` (starting at 1 for the second poll).
+    pub fn interval(&self, poll: u32) -> Duration {
+        let factor = 2u32.saturating_pow(poll.saturating_sub(1));
+        self.initial_interval
+            .saturating_mul(factor)
+            .min(self.max_interval)
+    }
+}
+
+impl Default for PollPolicy {
+    fn default() -> Self {
+        PollPolicy {
+            initial_interval: Duration::from_secs(1),
+            max_interval: Duration::from_secs(30),
+            timeout: Duration::from_secs(15 * 60),
+        }
+    }
+}
+
+/// Why [`wait_for_transaction`] stopped before the transaction finished.
+#[derive(Debug)]
+pub enum PollError {
+    /// Fetching the transaction failed with an error that retrying won't fix, such as a 404.
+    Api(Error<TransactionsTransactionIdGetError>),
+    /// The policy's timeout passed. `status` is the last status seen, if any.
+    TimedOut {
+        status: Option<models::TransactionStatus>,
+    },
+}
+
+impl fmt::Display for PollError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PollError::Api(e) => write!(f, "could not fetch the transaction: {e}"),
+            PollError::TimedOut {
+                status: Some(status),
+            } => {
+                write!(f, "timed out waiting for the transaction, still {status}")
+            }
+            PollError::TimedOut { status: None } => {
+                write!(f, "timed out waiting for the transaction")
+            }
+        }
+    }
+}
+
+impl error::Error for PollError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            PollError::Api(e) => Some(e),
+            PollError::TimedOut { .. } => None,
+        }
+    }
+}
+
+impl From<Error<TransactionsTransactionIdGetError>> for PollError {
+    fn from(e: Error<TransactionsTransactionIdGetError>) -> Self {
+        PollError::Api(e)
+    }
+}
+
+fn is_final(status: models::TransactionStatus) -> bool {
+    status != models::TransactionStatus::Pending
+}
+
+struct Poller {
+    configuration: Configuration,
+    transaction_id: String,
+    policy: PollPolicy,
+    /// The configuration's own deadline for each request.
+    budget: Option<Duration>,
+    deadline: Instant,
+    polls: u32,
+    /// Whether the latest poll was made at the deadline.
+    last_poll: bool,
+    status: Option<models::TransactionStatus>,
+    done: bool,
+}
+
+impl Poller {
+    async fn next(&mut self) -> Option<Result<models::Transaction, PollError>> {
+        if self.done {
+            return None;
+        }
+        loop {
+            if self.polls > 0 {
+                if self.last_poll {
+                    return Some(Err(self.timed_out()));
+                }
+                let interval = self.policy.interval(self.polls);
+                let wake = (Instant::now() + interval).min(self.deadline);
+                tokio::time::sleep_until(wake.into()).await;
+            }
+            self.polls += 1;
+            // Polls before the deadline are cut off at it. The one at the deadline is the last, and
+            // only bounded by the configuration, so that a slow response can still finish it.
+            self.last_poll = Instant::now() >= self.deadline;
+            self.configuration.request_options.deadline = if self.last_poll {
+                self.budget
+            } else {
+                let remaining = self.deadline.saturating_duration_since(Instant::now());
+                Some(
+                    self.budget
+                        .map_or(remaining, |budget| budget.min(remaining)),
+                )
+            };
+            let transaction = match utilities_api::transactions_transaction_id_get(
+                &self.configuration,
+                &self.transaction_id,
+                None,
+            )
+            .await
+            {
+                Ok(transaction) => transaction,
+                Err(Error::DeadlineExceeded) if self.last_poll => {
+                    return Some(Err(self.timed_out()))
+                }
+                // Polled again at the next interval, like a pending transaction.
+                Err(Error::DeadlineExceeded) => continue,
+                Err(e) if self.is_transient(&e) => continue,
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(e.into()));
+                }
+            };
+            if self.status == Some(transaction.status) {
+                continue;
+            }
+            self.status = Some(transaction.status);
+            self.done = is_final(transaction.status);
+            return Some(Ok(transaction));
+        }
+    }
+
+    /// Whether `error` may go away by itself: a 429 or 5xx response, a connection error or timeout,
+    /// or an open circuit.
+    fn is_transient(&self, error: &Error<TransactionsTransactionIdGetError>) -> bool {
+        let retry = &self.configuration.retry;
+        match error {
+            Error::ResponseError(response) => retry.should_retry_status(response.status),
+            Error::Reqwest(e) => retry.should_retry_error(e),
+            Error::CircuitOpen { .. } => true,
+            _ => false,
+        }
+    }
+
+    fn timed_out(&mut self) -> PollError {
+        self.done = true;
+        PollError::TimedOut {
+            status: self.status,
+        }
+    }
+}
+
+/// Polls the transaction until it is `Settled` or `Failed`, or `policy.timeout` passes.
+///
+/// The stream yields the transaction on the first poll and then whenever its status changes, so
+/// the last item is the finished transaction. It ends after that, or after yielding a
+/// [`PollError`]. Requests retry according to the configuration. The wait before a poll is cut
+/// short at the timeout, where one last poll is made before giving up; earlier requests are
+/// bounded by the timeout. Transient errors, such as a 503 or a dropped connection, are polled
+/// through at the policy's interval until the timeout; only errors like a 404 end the stream early.
+///
+/// ```no_run
+/// use futures_util::StreamExt;
+/// use noah_sdk::apis::configuration::Configuration;
+/// use noah_sdk::polling::{wait_for_transaction, PollPolicy};
+///
+/// # async fn example(config: &Configuration, id: &str) -> Result<(), noah_sdk::polling::PollError> {
+/// let mut updates = wait_for_transaction(config, id, PollPolicy::default());
+/// while let Some(transaction) = updates.next().await {
+///     let transaction = transaction?;
+///     println!("{} is {}", transaction.id, transaction.status);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub fn wait_for_transaction(
+    configuration: &Configuration,
+    transaction_id: &str,
+    policy: PollPolicy,
+) -> impl Stream<Item = Result<models::Transaction, PollError>> + Send + Unpin + 'static {
+    let poller = Poller {
+        configuration: configuration.clone(),
+        transaction_id: transaction_id.to_owned(),
+        budget: configuration.request_options.deadline,
+        deadline: Instant::now() + policy.timeout,
+        policy,
+        polls: 0,
+        last_poll: false,
+        status: None,
+        done: false,
+    };
+    Box::pin(stream::unfold(poller, |mut poller| async move {
+        let item = poller.next().await?;
+        Some((item, poller))
+    }))
+}
@@ -0,0 +1,350 @@
+//! Comparing prices across payment method categories.
+//!
+//! [`prices_get`](crate::apis::utilities_api::prices_get) returns one [`PriceItem`] per
+//! `PaymentMethodCategory`, with amounts as strings. [`Price`] parses one and works out what the
+//! customer actually receives once fees are taken, and [`PriceComparison`] lines the categories up
+//! from the best route to the worst.
+//!
+//! `TotalFee` and the `CryptoFeeBreakdown` are in crypto, which is the source currency of a sell
+//! and the destination currency of a pay-in. [`CryptoSide`] says which, so the fees are taken
+//! from `SourceAmount` before it is converted at `Rate`, or from the converted amount.
+//!
+//! [`PriceItem`]: models::PriceItem
+
+use std::error;
+use std::fmt;
+
+use rust_decimal::Decimal;
+
+use crate::apis::configuration::Configuration;
+use crate::apis::utilities_api::{self, PricesGetError};
+use crate::apis::Error;
+use crate::models;
+
+/// Why prices could not be compared.
+#[derive(Debug)]
+pub enum PriceError {
+    Api(Box<Error<PricesGetError>>),
+    /// An amount of a price item is not a decimal number.
+    InvalidValue {
+        field: &'static str,
+        value: String,
+    },
+}
+
+impl fmt::Display for PriceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PriceError::Api(e) => write!(f, "could not fetch prices: {e}"),
+            PriceError::InvalidValue { field, value } => write!(f, "invalid {field} `{value}`"),
+        }
+    }
+}
+
+impl error::Error for PriceError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            PriceError::Api(e) => Some(e.as_ref()),
+            PriceError::InvalidValue { .. } => None,
+        }
+    }
+}
+
+/// A fee breakdown that doesn't add up.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FeeMismatch {
+    /// The breakdown items don't sum to `TotalFee`.
+    TotalFee {
+        total_fee: Decimal,
+        breakdown: Decimal,
+    },
+    /// The `BusinessFee` items don't sum to `BusinessFee`.
+    BusinessFee {
+        business_fee: Decimal,
+        breakdown: Decimal,
+    },
+}
+
+impl fmt::Display for FeeMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FeeMismatch::TotalFee {
+                total_fee,
+                breakdown,
+            } => write!(
+                f,
+                "fee breakdown sums to {breakdown}, not TotalFee {total_fee}"
+            ),
+            FeeMismatch::BusinessFee {
+                business_fee,
+                breakdown,
+            } => write!(
+                f,
+                "business fee breakdown sums to {breakdown}, not BusinessFee {business_fee}"
+            ),
+        }
+    }
+}
+
+impl error::Error for FeeMismatch {}
+
+/// Which currency of a price is crypto, and so which one its fees are in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CryptoSide {
+    /// Crypto is sold for fiat, as in a payout.
+    #[default]
+    Source,
+    /// Fiat is paid in for crypto.
+    Destination,
+}
+
+/// A [`PriceItem`](models::PriceItem) with its amounts parsed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Price {
+    item: models::PriceItem,
+    crypto_side: CryptoSide,
+    rate: Decimal,
+    total_fee: Decimal,
+    business_fee: Option<Decimal>,
+    source_amount: Option<Decimal>,
+    destination_amount: Option<Decimal>,
+    fees: Vec<(String, Decimal)>,
+}
+
+impl Price {
+    pub fn new(item: models::PriceItem, crypto_side: CryptoSide) -> Result<Price, PriceError> {
+        let rate = decimal("Rate", &item.rate)?;
+        let total_fee = optional("TotalFee", &item.total_fee)?.unwrap_or_default();
+        let business_fee = optional("BusinessFee", &item.business_fee)?;
+        let source_amount = optional("SourceAmount", &item.source_amount)?;
+        let destination_amount = optional("DestinationAmount", &item.destination_amount)?;
+        let mut fees = Vec::new();
+        for fee in item.crypto_fee_breakdown.iter().flatten() {
+            fees.push((fee.r#type.clone(), decimal("Amount", &fee.amount)?));
+        }
+        Ok(Price {
+            item,
+            crypto_side,
+            rate,
+            total_fee,
+            business_fee,
+            source_amount,
+            destination_amount,
+            fees,
+        })
+    }
+
+    pub fn item(&self) -> &models::PriceItem {
+        &self.item
+    }
+
+    pub fn crypto_side(&self) -> CryptoSide {
+        self.crypto_side
+    }
+
+    pub fn payment_method_category(&self) -> &str {
+        &self.item.payment_method_category
+    }
+
+    /// Destination currency per source currency, before fees.
+    pub fn rate(&self) -> Decimal {
+        self.rate
+    }
+
+    /// `TotalFee` in crypto, or zero when there is none.
+    pub fn total_fee(&self) -> Decimal {
+        self.total_fee
+    }
+
+    pub fn business_fee(&self) -> Option<Decimal> {
+        self.business_fee
+    }
+
+    pub fn source_amount(&self) -> Option<Decimal> {
+        self.source_amount
+    }
+
+    /// `DestinationAmount` as returned, the source amount converted before fees.
+    pub fn destination_amount(&self) -> Option<Decimal> {
+        self.destination_amount
+    }
+
+    /// The `CryptoFeeBreakdown` items, as `(Type, Amount)`.
+    pub fn fees(&self) -> &[(String, Decimal)] {
+        &self.fees
+    }
+
+    /// What the customer receives: the source amount at `Rate`, less `TotalFee` in whichever
+    /// currency is crypto. `None` without a `SourceAmount`.
+    pub fn net_destination_amount(&self) -> Option<Decimal> {
+        let source_amount = self.source_amount?;
+        Some(match self.crypto_side {
+            CryptoSide::Source => (source_amount - self.total_fee) * self.rate,
+            CryptoSide::Destination => source_amount * self.rate - self.total_fee,
+        })
+    }
+
+    /// The net destination amount per unit of source amount, which is what the customer
+    /// effectively gets for each unit once fees are taken.
+    pub fn effective_rate(&self) -> Option<Decimal> {
+        self.net_destination_amount()?
+            .checked_div(self.source_amount?)
+    }
+
+    /// Checks that the breakdown sums to `TotalFee`, and its `BusinessFee` items to `BusinessFee`.
+    /// A price without a breakdown has nothing to check.
+    pub fn reconcile(&self) -> Result<(), FeeMismatch> {
+        if self.fees.is_empty() {
+            return Ok(());
+        }
+        let breakdown: Decimal = self.fees.iter().map(|(_, amount)| amount).sum();
+        if breakdown != self.total_fee {
+            return Err(FeeMismatch::TotalFee {
+                total_fee: self.total_fee,
+                breakdown,
+            });
+        }
+        if let Some(business_fee) = self.business_fee {
+            let breakdown: Decimal = self
+                .fees
+                .iter()
+                .filter(|(kind, _)| kind == "BusinessFee")
+                .map(|(_, amount)| amount)
+                .sum();
+            if breakdown != business_fee {
+                return Err(FeeMismatch::BusinessFee {
+                    business_fee,
+                    breakdown,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Prices for the same conversion through different payment method categories, best first.
+///
+/// The best price pays out the largest net destination amount. Prices without a source amount
+/// can't be compared that way and come last, ordered by rate.
+///
+/// ```no_run
+/// use noah_sdk::prices::{CryptoSide, PriceComparison};
+///
+/// # async fn example(
+/// #     config: &noah_sdk::apis::configuration::Configuration,
+/// # ) -> Result<(), noah_sdk::prices::PriceError> {
+/// let comparison = PriceComparison::fetch(
+///     config,
+///     "USDC",
+///     "EUR",
+///     CryptoSide::Source,
+///     "100",
+///     &["Bank", "Card"],
+///     Some("DE"),
+/// )
+/// .await?;
+/// for price in comparison.prices() {
+///     println!(
+///         "{}: {:?} EUR, {:?} less than the best",
+///         price.payment_method_category(),
+///         price.net_destination_amount(),
+///         comparison.shortfall(price),
+///     );
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PriceComparison {
+    prices: Vec<Price>,
+}
+
+impl PriceComparison {
+    pub fn new(
+        items: impl IntoIterator<Item = models::PriceItem>,
+        crypto_side: CryptoSide,
+    ) -> Result<PriceComparison, PriceError> {
+        let mut prices = items
+            .into_iter()
+            .map(|item| Price::new(item, crypto_side))
+            .collect::<Result<Vec<_>, _>>()?;
+        prices.sort_by(|a, b| {
+            match (a.net_destination_amount(), b.net_destination_amount()) {
+                (Some(a), Some(b)) => b.cmp(&a),
+                (Some(_), None) => std::cmp::Ordering::Less,
+                (None, Some(_)) => std::cmp::Ordering::Greater,
+                (None, None) => b.rate.cmp(&a.rate),
+            }
+            .then_with(|| a.payment_method_category().cmp(b.payment_method_category()))
+        });
+        Ok(PriceComparison { prices })
+    }
+
+    /// Prices `source_amount` of `source_currency` in each of `categories`, or in every category
+    /// Noah returns when `categories` is empty. `crypto_side` says which of the currencies is
+    /// crypto.
+    pub async fn fetch(
+        configuration: &Configuration,
+        source_currency: &str,
+        destination_currency: &str,
+        crypto_side: CryptoSide,
+        source_amount: &str,
+        categories: &[&str],
+        country: Option<&str>,
+    ) -> Result<PriceComparison, PriceError> {
+        let categories: Vec<Option<&str>> = if categories.is_empty() {
+            vec![None]
+        } else {
+            categories.iter().copied().map(Some).collect()
+        };
+        let mut items = Vec::new();
+        for category in categories {
+            let response = utilities_api::prices_get(
+                configuration,
+                source_currency,
+                destination_currency,
+                Some(source_amount),
+                None,
+                category,
+                country,
+            )
+            .await
+            .map_err(|e| PriceError::Api(Box::new(e)))?;
+            items.extend(response.items);
+        }
+        PriceComparison::new(items, crypto_side)
+    }
+
+    pub fn prices(&self) -> &[Price] {
+        &self.prices
+    }
+
+    pub fn best(&self) -> Option<&Price> {
+        self.prices.first()
+    }
+
+    pub fn get(&self, payment_method_category: &str) -> Option<&Price> {
+        self.prices
+            .iter()
+            .find(|price| price.payment_method_category() == payment_method_category)
+    }
+
+    /// How much less `price` pays out than the best price, in the destination currency.
+    pub fn shortfall(&self, price: &Price) -> Option<Decimal> {
+        Some(self.best()?.net_destination_amount()? - price.net_destination_amount()?)
+    }
+}
+
+fn decimal(field: &'static str, value: &str) -> Result<Decimal, PriceError> {
+    value.parse().map_err(|_| PriceError::InvalidValue {
+        field,
+        value: value.to_owned(),
+    })
+}
+
+fn optional(field: &'static str, value: &Option<String>) -> Result<Option<Decimal>, PriceError> {
+    value
+        .as_deref()
+        .map(|value| decimal(field, value))
+        .transpose()
+}
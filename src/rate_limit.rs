@@ -0,0 +1,251 @@
+//! Keeping request rates under Noah's quotas.
+//!
+//! Setting [`Configuration::rate_limiter`](crate::apis::configuration::Configuration::rate_limiter)
+//! to a [`RateLimiter`] makes every request wait for a token before it is sent, instead of being
+//! answered with `429 Too Many Requests`. Clones of the configuration share the limiter.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use reqwest::header::HeaderMap;
+use reqwest::StatusCode;
+
+/// A number of requests per period, with bursts of up to `burst` requests.
+///
+/// `requests` and `burst` are at least 1. A zero period refills the bucket at once, so only the
+/// pauses asked for by responses hold requests back.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RateLimit {
+    requests: u32,
+    per: Duration,
+    /// How many tokens the bucket holds, and so how many requests can go out at once after a
+    /// quiet period. Defaults to `requests`.
+    burst: u32,
+}
+
+impl RateLimit {
+    pub fn new(requests: u32, per: Duration) -> RateLimit {
+        let requests = requests.max(1);
+        RateLimit {
+            requests,
+            per,
+            burst: requests,
+        }
+    }
+
+    pub fn per_second(requests: u32) -> RateLimit {
+        RateLimit::new(requests, Duration::from_secs(1))
+    }
+
+    pub fn per_minute(requests: u32) -> RateLimit {
+        RateLimit::new(requests, Duration::from_secs(60))
+    }
+
+    pub fn burst(mut self, burst: u32) -> RateLimit {
+        self.burst = burst.max(1);
+        self
+    }
+
+    /// The time it takes to earn one token, never zero so that refilling can divide by it.
+    fn interval(&self) -> Duration {
+        (self.per / self.requests).max(Duration::from_nanos(1))
+    }
+}
+
+/// Token buckets for the whole client and for groups of endpoints.
+///
+/// A request takes a token from its group's bucket, if its endpoint is in a group, and then from
+/// the global bucket, if there is one. Requests waiting for a bucket are served in the order they
+/// arrived, and each retry of a request takes a token of its own.
+///
+/// Responses adjust the bucket of their group, or the global bucket for endpoints outside any
+/// group. `Retry-After` on a `429` or `503`, and `X-RateLimit-Remaining: 0` with
+/// `X-RateLimit-Reset`, pause the bucket until the time given; a lower `X-RateLimit-Remaining`
+/// drains it to match. The `RateLimit-*` names are understood too.
+///
+/// ```
+/// use std::sync::Arc;
+///
+/// use noah_sdk::apis::configuration::Configuration;
+/// use noah_sdk::rate_limit::{RateLimit, RateLimiter};
+///
+/// let limiter = RateLimiter::new()
+///     .global(RateLimit::per_second(20))
+///     .group(
+///         "listings",
+///         &["transactions_get", "customers_get"],
+///         RateLimit::per_second(5).burst(10),
+///     );
+/// let config = Configuration {
+///     rate_limiter: Some(Arc::new(limiter)),
+///     ..Configuration::default()
+/// };
+/// ```
+#[derive(Debug, Default)]
+pub struct RateLimiter {
+    global: Option<Bucket>,
+    groups: HashMap<String, Bucket>,
+    /// Group name of each grouped operation.
+    operations: HashMap<String, String>,
+}
+
+impl RateLimiter {
+    pub fn new() -> RateLimiter {
+        RateLimiter::default()
+    }
+
+    /// Limits all requests together.
+    pub fn global(mut self, limit: RateLimit) -> RateLimiter {
+        self.global = Some(Bucket::new(limit));
+        self
+    }
+
+    /// Limits the requests to `operations`, the names of endpoint functions, together. An
+    /// operation belongs to the last group that lists it.
+    pub fn group(mut self, name: &str, operations: &[&str], limit: RateLimit) -> RateLimiter {
+        self.groups.insert(name.to_owned(), Bucket::new(limit));
+        for operation in operations {
+            self.operations
+                .insert((*operation).to_owned(), name.to_owned());
+        }
+        self
+    }
+
+    /// Waits until a request to `operation` may be sent.
+    pub(crate) async fn acquire(&self, operation: &str) {
+        if let Some(bucket) = self.group_of(operation) {
+            bucket.acquire().await;
+        }
+        if let Some(bucket) = &self.global {
+            bucket.acquire().await;
+        }
+    }
+
+    /// Adapts to the rate limit headers of a response to `operation`.
+    pub(crate) fn observe(&self, operation: &str, status: StatusCode, headers: &HeaderMap) {
+        let Some(bucket) = self.group_of(operation).or(self.global.as_ref()) else {
+            return;
+        };
+        let now = Instant::now();
+        let retry_after = matches!(
+            status,
+            StatusCode::TOO_MANY_REQUESTS | StatusCode::SERVICE_UNAVAILABLE
+        )
+        .then(|| seconds(headers, &["Retry-After"]))
+        .flatten();
+        let remaining = seconds(headers, &["X-RateLimit-Remaining", "RateLimit-Remaining"]);
+        let reset = seconds(headers, &["X-RateLimit-Reset", "RateLimit-Reset"]).map(until);
+        let mut state = bucket.state();
+        state.refill(&bucket.limit, now);
+        if let Some(remaining) = remaining {
+            state.tokens = state.tokens.min(remaining as f64);
+        }
+        let pause = match (retry_after, remaining, reset) {
+            (Some(retry_after), _, _) => Some(Duration::from_secs(retry_after)),
+            (None, Some(0), Some(reset)) => Some(reset),
+            _ => None,
+        };
+        if let Some(pause) = pause {
+            let until = now + pause;
+            state.paused_until = Some(state.paused_until.map_or(until, |p| p.max(until)));
+        }
+    }
+
+    fn group_of(&self, operation: &str) -> Option<&Bucket> {
+        self.groups.get(self.operations.get(operation)?)
+    }
+}
+
+struct Bucket {
+    limit: RateLimit,
+    state: Mutex<State>,
+    /// Held by the request at the head of the queue while it waits for a token. Tokio's mutex
+    /// is fair, so requests get their turn in the order they asked.
+    queue: tokio::sync::Mutex<()>,
+}
+
+#[derive(Debug)]
+struct State {
+    tokens: f64,
+    updated: Instant,
+    paused_until: Option<Instant>,
+}
+
+impl State {
+    fn refill(&mut self, limit: &RateLimit, now: Instant) {
+        let earned =
+            now.duration_since(self.updated).as_secs_f64() / limit.interval().as_secs_f64();
+        self.tokens = (self.tokens + earned).min(f64::from(limit.burst));
+        self.updated = now;
+    }
+}
+
+impl Bucket {
+    fn new(limit: RateLimit) -> Bucket {
+        Bucket {
+            limit,
+            state: Mutex::new(State {
+                tokens: f64::from(limit.burst),
+                updated: Instant::now(),
+                paused_until: None,
+            }),
+            queue: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    /// Locks the bucket's state. It is only changed by arithmetic that can't panic midway, so a
+    /// poisoned lock still holds a consistent state.
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    async fn acquire(&self) {
+        let _turn = self.queue.lock().await;
+        loop {
+            let wait = {
+                let now = Instant::now();
+                let mut state = self.state();
+                state.refill(&self.limit, now);
+                match state.paused_until {
+                    Some(until) if until > now => until - now,
+                    _ if state.tokens >= 1.0 => {
+                        state.tokens -= 1.0;
+                        return;
+                    }
+                    _ => self.limit.interval().mul_f64(1.0 - state.tokens),
+                }
+            };
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+impl fmt::Debug for Bucket {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Bucket")
+            .field("limit", &self.limit)
+            .field("state", &self.state)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The first of the `names` headers holding a whole number.
+fn seconds(headers: &HeaderMap, names: &[&str]) -> Option<u64> {
+    names
+        .iter()
+        .find_map(|name| headers.get(*name)?.to_str().ok()?.trim().parse().ok())
+}
+
+/// A reset header is either a number of seconds or a Unix timestamp.
+fn until(reset: u64) -> Duration {
+    const TIMESTAMPS_FROM: u64 = 1_000_000_000;
+    if reset < TIMESTAMPS_FROM {
+        return Duration::from_secs(reset);
+    }
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    Duration::from_secs(reset).saturating_sub(now)
+}
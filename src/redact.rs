@@ -0,0 +1,137 @@
+//! Redaction of credentials and PII in `Debug` output.
+//!
+//! Credentials in [`Configuration`](crate::apis::configuration::Configuration) are wrapped in
+//! [`Secret`], and models holding personal data (ID numbers, dates of birth, account numbers, ...)
+//! print `[REDACTED]` in place of those fields. For local debugging, [`set_unredacted`] turns
+//! redaction off for the whole process.
+
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+const REDACTED: &str = "[REDACTED]";
+
+/// Wire names of the fields holding personal data, as used in request and response bodies.
+pub const PII_FIELDS: &[&str] = &[
+    "AccountHolderName",
+    "AccountNumber",
+    "BankCode",
+    "DateOfBirth",
+    "Email",
+    "FirstName",
+    "FullName",
+    "IDNumber",
+    "LastName",
+    "MiddleName",
+    "PhoneNumber",
+    "PostCode",
+    "Street",
+    "Street2",
+    "TaxID",
+    "Token",
+];
+
+static UNREDACTED: AtomicBool = AtomicBool::new(false);
+
+/// Turns redaction off (`true`) or back on (`false`) for every `Debug` impl in this crate.
+///
+/// Meant for local debugging only. Never enable this where logs are shipped or stored.
+pub fn set_unredacted(unredacted: bool) {
+    UNREDACTED.store(unredacted, Ordering::Relaxed);
+}
+
+pub fn is_unredacted() -> bool {
+    UNREDACTED.load(Ordering::Relaxed)
+}
+
+/// A credential whose `Debug` output is redacted.
+///
+/// Serialization is transparent, so wrapping a field in `Secret` does not change the wire format.
+#[derive(Clone, Default, PartialEq, Eq, Hash)]
+pub struct Secret<T>(T);
+
+impl<T> Secret<T> {
+    pub fn new(value: T) -> Secret<T> {
+        Secret(value)
+    }
+
+    /// Returns the wrapped value. Every call site is a place where the secret may leak.
+    pub fn expose(&self) -> &T {
+        &self.0
+    }
+
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Secret<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Secret").field(&Redacted(&self.0)).finish()
+    }
+}
+
+impl<T> From<T> for Secret<T> {
+    fn from(value: T) -> Self {
+        Secret(value)
+    }
+}
+
+impl From<&str> for Secret<String> {
+    fn from(value: &str) -> Self {
+        Secret(value.to_owned())
+    }
+}
+
+impl<T: Serialize> Serialize for Secret<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Secret<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        T::deserialize(deserializer).map(Secret)
+    }
+}
+
+/// Formats the referenced value as `[REDACTED]` unless redaction is turned off.
+pub struct Redacted<'a, T: ?Sized>(pub &'a T);
+
+impl<T: fmt::Debug + ?Sized> fmt::Debug for Redacted<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if is_unredacted() {
+            self.0.fmt(f)
+        } else {
+            f.write_str(REDACTED)
+        }
+    }
+}
+
+/// Replaces every string under a [`PII_FIELDS`] key of a JSON payload with `[REDACTED]`, so the
+/// payload can be logged or stored. Numbers, booleans and the payload's shape are kept.
+pub fn redact_json(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(object) => {
+            for (key, value) in object.iter_mut() {
+                if PII_FIELDS.contains(&key.as_str()) {
+                    redact_strings(value);
+                } else {
+                    redact_json(value);
+                }
+            }
+        }
+        serde_json::Value::Array(items) => items.iter_mut().for_each(redact_json),
+        _ => {}
+    }
+}
+
+fn redact_strings(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::String(s) => *s = REDACTED.to_owned(),
+        serde_json::Value::Object(object) => object.values_mut().for_each(redact_strings),
+        serde_json::Value::Array(items) => items.iter_mut().for_each(redact_strings),
+        _ => {}
+    }
+}
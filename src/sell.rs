@@ -0,0 +1,621 @@
+//! Selling crypto for fiat in one call.
+//!
+//! A sell takes several requests: finding a channel, fetching and filling its form, preparing the
+//! sell to learn the fee and the crypto to authorize, and executing it with those values and a
+//! nonce. [`SellFlow`] runs them in order and refuses to execute a sell whose fee or rate is
+//! worse than the caller allows. In between, the prepared sell is a [`Quote`], which can be shown
+//! to the customer and executed later, unless it has gone stale.
+
+use std::collections::HashMap;
+use std::error;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use futures_util::StreamExt;
+use rust_decimal::Decimal;
+use serde_json::Value;
+
+use crate::apis::configuration::Configuration;
+use crate::apis::payout_api::{self, TransactionsSellPostError, TransactionsSellPreparePostError};
+use crate::apis::utilities_api::{
+    self, ChannelsChannelIdFormGetError, ChannelsChannelIdGetError, ChannelsSellGetError,
+};
+use crate::apis::Error;
+use crate::channels::{ChannelSelector, Excluded};
+use crate::models;
+use crate::polling::{self, PollError, PollPolicy};
+
+/// Why a [`SellFlow`] stopped. Nothing was sold unless the error is [`SellError::Wait`].
+#[derive(Debug)]
+pub enum SellError {
+    /// The sell channels could not be listed.
+    Channels(Box<Error<ChannelsSellGetError>>),
+    /// No channel supports the sell. `excluded` says why each listed channel was left out.
+    NoChannel {
+        excluded: Vec<Excluded>,
+    },
+    /// The channel form could not be fetched.
+    Form(Box<Error<ChannelsChannelIdFormGetError>>),
+    /// The channel form requires fields that were not given.
+    MissingFormFields(Vec<String>),
+    Prepare(Box<Error<TransactionsSellPreparePostError>>),
+    /// An amount given or returned is not a decimal number, or a channel ID is not a UUID.
+    InvalidValue {
+        field: &'static str,
+        value: String,
+    },
+    /// The prepared fee is above [`SellFlow::max_fee`].
+    FeeTooHigh {
+        fee: Decimal,
+        max: Decimal,
+    },
+    /// The quote is older than [`SellFlow::max_quote_age`] allows.
+    StaleQuote {
+        age: Duration,
+        max_age: Duration,
+    },
+    /// The channel could not be fetched to check its current rate.
+    Rate(Box<Error<ChannelsChannelIdGetError>>),
+    /// The prepared rate is further below the current channel rate than
+    /// [`SellFlow::max_slippage_bps`] allows.
+    Slippage {
+        current: Decimal,
+        rate: Decimal,
+        bps: Decimal,
+        max_bps: u32,
+    },
+    Sell(Box<Error<TransactionsSellPostError>>),
+    /// The sell was executed, but waiting for it to finish failed.
+    Wait {
+        transaction: Box<models::Transaction>,
+        error: Box<PollError>,
+    },
+}
+
+impl fmt::Display for SellError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SellError::Channels(e) => write!(f, "could not list sell channels: {e}"),
+            SellError::NoChannel { excluded } => {
+                write!(f, "no channel supports the sell")?;
+                for excluded in excluded {
+                    write!(f, "; {excluded}")?;
+                }
+                Ok(())
+            }
+            SellError::Form(e) => write!(f, "could not fetch the channel form: {e}"),
+            SellError::MissingFormFields(fields) => {
+                write!(f, "missing form fields: {}", fields.join(", "))
+            }
+            SellError::Prepare(e) => write!(f, "could not prepare the sell: {e}"),
+            SellError::InvalidValue { field, value } => {
+                write!(f, "invalid {field} `{value}`")
+            }
+            SellError::FeeTooHigh { fee, max } => {
+                write!(f, "fee {fee} is above the maximum of {max}")
+            }
+            SellError::StaleQuote { age, max_age } => write!(
+                f,
+                "quote is {}s old, older than {}s",
+                age.as_secs_f64(),
+                max_age.as_secs_f64()
+            ),
+            SellError::Rate(e) => write!(f, "could not fetch the current rate: {e}"),
+            SellError::Slippage {
+                current,
+                rate,
+                bps,
+                max_bps,
+            } => write!(
+                f,
+                "rate {rate} is {bps} bps below the current {current}, more than {max_bps} bps"
+            ),
+            SellError::Sell(e) => write!(f, "could not execute the sell: {e}"),
+            SellError::Wait { transaction, error } => {
+                write!(f, "transaction {} was created, but {error}", transaction.id)
+            }
+        }
+    }
+}
+
+impl error::Error for SellError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            SellError::Channels(e) => Some(e.as_ref()),
+            SellError::Form(e) => Some(e.as_ref()),
+            SellError::Rate(e) => Some(e.as_ref()),
+            SellError::Prepare(e) => Some(e.as_ref()),
+            SellError::Sell(e) => Some(e.as_ref()),
+            SellError::Wait { error, .. } => Some(error.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+/// A prepared sell, with the channel rate it was based on and when it was prepared.
+///
+/// The estimate is only good while the market doesn't move. [`is_stale`](Quote::is_stale) and
+/// [`within_slippage`](Quote::within_slippage) tell whether it is still worth executing.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Quote {
+    channel: models::Channel,
+    crypto_currency: String,
+    fiat_amount: String,
+    prepared: models::PrepareSellResponse,
+    created: Instant,
+    quoted_rate: Decimal,
+    rate: Decimal,
+    total_fee: Decimal,
+}
+
+impl Quote {
+    /// Wraps the response to preparing a sell of `fiat_amount` through `channel`, as prepared
+    /// now.
+    pub fn new(
+        channel: models::Channel,
+        crypto_currency: impl Into<String>,
+        fiat_amount: impl Into<String>,
+        prepared: models::PrepareSellResponse,
+    ) -> Result<Quote, SellError> {
+        let fiat_amount = fiat_amount.into();
+        let quoted_rate = decimal("Rate", &channel.rate)?;
+        let estimate = decimal("CryptoAmountEstimate", &prepared.crypto_amount_estimate)?;
+        let rate = decimal("FiatAmount", &fiat_amount)?
+            .checked_div(estimate)
+            .ok_or_else(|| SellError::InvalidValue {
+                field: "CryptoAmountEstimate",
+                value: prepared.crypto_amount_estimate.clone(),
+            })?;
+        if quoted_rate.is_zero() {
+            return Err(SellError::InvalidValue {
+                field: "Rate",
+                value: channel.rate,
+            });
+        }
+        Ok(Quote {
+            total_fee: decimal("TotalFee", &prepared.total_fee)?,
+            channel,
+            crypto_currency: crypto_currency.into(),
+            fiat_amount,
+            prepared,
+            created: Instant::now(),
+            quoted_rate,
+            rate,
+        })
+    }
+
+    pub fn channel(&self) -> &models::Channel {
+        &self.channel
+    }
+
+    pub fn prepared(&self) -> &models::PrepareSellResponse {
+        &self.prepared
+    }
+
+    pub fn crypto_currency(&self) -> &str {
+        &self.crypto_currency
+    }
+
+    pub fn fiat_amount(&self) -> &str {
+        &self.fiat_amount
+    }
+
+    /// The prepared `TotalFee`, in crypto.
+    pub fn total_fee(&self) -> Decimal {
+        self.total_fee
+    }
+
+    /// The channel `Rate` the sell was prepared at, in fiat per crypto.
+    pub fn quoted_rate(&self) -> Decimal {
+        self.quoted_rate
+    }
+
+    /// The rate the sell gets: the fiat amount per crypto of the `CryptoAmountEstimate`.
+    pub fn rate(&self) -> Decimal {
+        self.rate
+    }
+
+    /// How far [`rate`](Quote::rate) is below `current_rate`, the channel rate now, in basis
+    /// points. Negative when the sell gets a better rate than the channel. Against
+    /// [`quoted_rate`](Quote::quoted_rate), it is the slippage when the quote was prepared.
+    pub fn slippage_bps(&self, current_rate: Decimal) -> Decimal {
+        if current_rate.is_zero() {
+            return Decimal::MAX;
+        }
+        ((current_rate - self.rate) / current_rate * Decimal::from(10_000)).round_dp(2)
+    }
+
+    pub fn within_slippage(&self, bps: u32, current_rate: Decimal) -> bool {
+        self.slippage_bps(current_rate) <= Decimal::from(bps)
+    }
+
+    pub fn age(&self) -> Duration {
+        self.created.elapsed()
+    }
+
+    pub fn is_stale(&self, max_age: Duration) -> bool {
+        self.age() > max_age
+    }
+
+    /// The request executing this quote.
+    pub fn sell_request(&self, nonce: impl Into<String>) -> models::SellRequest {
+        models::SellRequest {
+            crypto_currency: self.crypto_currency.clone(),
+            fiat_amount: self.fiat_amount.clone(),
+            crypto_authorized_amount: self.prepared.crypto_authorized_amount.clone(),
+            form_session_id: self.prepared.form_session_id,
+            nonce: nonce.into(),
+            external_id: None,
+        }
+    }
+}
+
+/// What a completed [`SellFlow`] did.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SellReceipt {
+    pub quote: Quote,
+    /// The nonce the sell was executed with. Running the flow again with it returns the same
+    /// transaction instead of selling twice.
+    pub nonce: String,
+    /// The transaction as executed, or as finished when waiting for settlement.
+    pub transaction: models::Transaction,
+}
+
+/// Prepares, checks and executes a sell.
+///
+/// [`run`](SellFlow::run) does it all at once. [`quote`](SellFlow::quote) and
+/// [`execute`](SellFlow::execute) split it, to have the customer confirm the quote in between.
+///
+/// Without [`channel`](SellFlow::channel), the flow picks the first channel a
+/// [`ChannelSelector`] ranks among those [`channels_sell_get`](utilities_api::channels_sell_get)
+/// returns for the amount, by default the one costing the least crypto after fees. Form fields
+/// are filled from the schema defaults and [`form_field`](SellFlow::form_field), and every
+/// required field must be given.
+///
+/// The nonce is generated once per flow, so HTTP retries can't sell twice. To also survive
+/// restarts, pass the nonce of an [`IdempotencyManager`](crate::idempotency::IdempotencyManager):
+///
+/// ```no_run
+/// # async fn example(
+/// #     config: &noah_sdk::apis::configuration::Configuration,
+/// # ) -> Result<(), Box<dyn std::error::Error>> {
+/// use std::time::Duration;
+///
+/// use noah_sdk::idempotency::{FileStore, IdempotencyManager};
+/// use noah_sdk::polling::PollPolicy;
+/// use noah_sdk::sell::SellFlow;
+/// use noah_sdk::Decimal;
+///
+/// let idempotency = IdempotencyManager::new(FileStore::new("sells.json"));
+/// let receipt = SellFlow::new(config, "USDC", "100.00")
+///     .fiat_currency("EUR")
+///     .customer_id("customer-123")
+///     .max_fee(Decimal::new(150, 2))
+///     .max_slippage_bps(50)
+///     .nonce(idempotency.nonce("payout-42")?)
+///     .wait_for_settlement(PollPolicy::new(Duration::from_secs(600)))
+///     .run()
+///     .await?;
+/// println!("{} is {}", receipt.transaction.id, receipt.transaction.status);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct SellFlow {
+    configuration: Configuration,
+    crypto_currency: String,
+    fiat_amount: String,
+    country: Option<String>,
+    fiat_currency: Option<String>,
+    customer_id: Option<String>,
+    payment_method_id: Option<String>,
+    channel: Option<models::Channel>,
+    selector: Option<ChannelSelector>,
+    form: HashMap<String, Value>,
+    max_fee: Option<Decimal>,
+    max_slippage_bps: Option<u32>,
+    max_quote_age: Option<Duration>,
+    nonce: String,
+    external_id: Option<String>,
+    settlement: Option<PollPolicy>,
+}
+
+impl SellFlow {
+    pub fn new(
+        configuration: &Configuration,
+        crypto_currency: impl Into<String>,
+        fiat_amount: impl Into<String>,
+    ) -> SellFlow {
+        SellFlow {
+            configuration: configuration.clone(),
+            crypto_currency: crypto_currency.into(),
+            fiat_amount: fiat_amount.into(),
+            country: None,
+            fiat_currency: None,
+            customer_id: None,
+            payment_method_id: None,
+            channel: None,
+            selector: None,
+            form: HashMap::new(),
+            max_fee: None,
+            max_slippage_bps: None,
+            max_quote_age: None,
+            nonce: uuid::Uuid::new_v4().to_string(),
+            external_id: None,
+            settlement: None,
+        }
+    }
+
+    /// Only considers channels paying out in `country`, an ISO 3166-1 alpha-2 code.
+    pub fn country(mut self, country: impl Into<String>) -> SellFlow {
+        self.country = Some(country.into());
+        self
+    }
+
+    pub fn fiat_currency(mut self, fiat_currency: impl Into<String>) -> SellFlow {
+        self.fiat_currency = Some(fiat_currency.into());
+        self
+    }
+
+    pub fn customer_id(mut self, customer_id: impl Into<String>) -> SellFlow {
+        self.customer_id = Some(customer_id.into());
+        self
+    }
+
+    /// Pays out to an existing payment method instead of one described by the form.
+    pub fn payment_method_id(mut self, payment_method_id: impl Into<String>) -> SellFlow {
+        self.payment_method_id = Some(payment_method_id.into());
+        self
+    }
+
+    /// Sells through `channel`, as returned by the channel endpoints, instead of picking one.
+    /// Its `Rate` is the quote that [`max_slippage_bps`](SellFlow::max_slippage_bps) applies to.
+    pub fn channel(mut self, channel: models::Channel) -> SellFlow {
+        self.channel = Some(channel);
+        self
+    }
+
+    /// Picks the channel with `selector`, for instance to rank by processing time or only
+    /// consider bank channels. The flow's country, fiat currency and amount still apply.
+    pub fn selector(mut self, selector: ChannelSelector) -> SellFlow {
+        self.selector = Some(selector);
+        self
+    }
+
+    /// Sets a field of the channel form.
+    pub fn form_field(mut self, name: impl Into<String>, value: impl Into<Value>) -> SellFlow {
+        self.form.insert(name.into(), value.into());
+        self
+    }
+
+    /// Refuses to sell if the prepared `TotalFee`, in crypto, is above `max_fee`.
+    pub fn max_fee(mut self, max_fee: Decimal) -> SellFlow {
+        self.max_fee = Some(max_fee);
+        self
+    }
+
+    /// Refuses to sell if the prepared rate, the fiat amount per crypto of the estimate, is more
+    /// than `bps` hundredths of a percent below the channel rate.
+    pub fn max_slippage_bps(mut self, bps: u32) -> SellFlow {
+        self.max_slippage_bps = Some(bps);
+        self
+    }
+
+    /// Refuses to execute a quote prepared more than `max_age` ago.
+    pub fn max_quote_age(mut self, max_age: Duration) -> SellFlow {
+        self.max_quote_age = Some(max_age);
+        self
+    }
+
+    pub fn nonce(mut self, nonce: impl Into<String>) -> SellFlow {
+        self.nonce = nonce.into();
+        self
+    }
+
+    pub fn external_id(mut self, external_id: impl Into<String>) -> SellFlow {
+        self.external_id = Some(external_id.into());
+        self
+    }
+
+    /// Waits for the transaction to be `Settled` or `Failed` before returning, polling it with
+    /// `policy`.
+    pub fn wait_for_settlement(mut self, policy: PollPolicy) -> SellFlow {
+        self.settlement = Some(policy);
+        self
+    }
+
+    /// Quotes the sell, then executes the quote.
+    pub async fn run(self) -> Result<SellReceipt, SellError> {
+        let quote = self.quote().await?;
+        self.execute(quote).await
+    }
+
+    /// Picks the channel, fills the form and prepares the sell, without executing it.
+    pub async fn quote(&self) -> Result<Quote, SellError> {
+        let fiat_amount = decimal("FiatAmount", &self.fiat_amount)?;
+        let channel = match &self.channel {
+            Some(channel) => channel.clone(),
+            None => self.pick_channel(fiat_amount).await?,
+        };
+        let form = self.fill_form(&channel).await?;
+        let request = models::PrepareSellRequest {
+            channel_id: uuid::Uuid::parse_str(&channel.id).map_err(|_| {
+                SellError::InvalidValue {
+                    field: "ChannelID",
+                    value: channel.id.clone(),
+                }
+            })?,
+            payment_method_id: self.payment_method_id.clone(),
+            crypto_currency: self.crypto_currency.clone(),
+            customer_id: self.customer_id.clone(),
+            fiat_amount: self.fiat_amount.clone(),
+            form,
+            delayed_sell: None,
+        };
+        let prepared =
+            payout_api::transactions_sell_prepare_post(&self.configuration, request, None)
+                .await
+                .map_err(|e| SellError::Prepare(Box::new(e)))?;
+        let quote = Quote::new(
+            channel,
+            self.crypto_currency.clone(),
+            self.fiat_amount.clone(),
+            prepared,
+        )?;
+        if let Some(max) = self.max_fee {
+            if quote.total_fee() > max {
+                return Err(SellError::FeeTooHigh {
+                    fee: quote.total_fee(),
+                    max,
+                });
+            }
+        }
+        Ok(quote)
+    }
+
+    /// Executes `quote` unless it is stale or has slipped, then waits for settlement if asked to.
+    ///
+    /// With [`max_slippage_bps`](SellFlow::max_slippage_bps), the channel is fetched again and the
+    /// quote is measured against its current rate, since the market may have moved since quoting.
+    pub async fn execute(&self, quote: Quote) -> Result<SellReceipt, SellError> {
+        if let Some(max_age) = self.max_quote_age {
+            if quote.is_stale(max_age) {
+                return Err(SellError::StaleQuote {
+                    age: quote.age(),
+                    max_age,
+                });
+            }
+        }
+        if let Some(max_bps) = self.max_slippage_bps {
+            let current = self.current_rate(&quote).await?;
+            if !quote.within_slippage(max_bps, current) {
+                return Err(SellError::Slippage {
+                    current,
+                    rate: quote.rate(),
+                    bps: quote.slippage_bps(current),
+                    max_bps,
+                });
+            }
+        }
+
+        let request = models::SellRequest {
+            external_id: self.external_id.clone(),
+            ..quote.sell_request(self.nonce.clone())
+        };
+        let sold = payout_api::transactions_sell_post(&self.configuration, request, None)
+            .await
+            .map_err(|e| SellError::Sell(Box::new(e)))?;
+        let mut transaction = *sold.transaction;
+        if let Some(policy) = &self.settlement {
+            let id = transaction.id.to_string();
+            let mut updates =
+                polling::wait_for_transaction(&self.configuration, &id, policy.clone());
+            while let Some(update) = updates.next().await {
+                match update {
+                    Ok(update) => transaction = update,
+                    Err(error) => {
+                        return Err(SellError::Wait {
+                            transaction: Box::new(transaction),
+                            error: Box::new(error),
+                        })
+                    }
+                }
+            }
+        }
+        Ok(SellReceipt {
+            quote,
+            nonce: self.nonce.clone(),
+            transaction,
+        })
+    }
+
+    async fn current_rate(&self, quote: &Quote) -> Result<Decimal, SellError> {
+        let channel = utilities_api::channels_channel_id_get(
+            &self.configuration,
+            &quote.channel.id,
+            &quote.crypto_currency,
+            Some(&quote.fiat_amount),
+            self.customer_id.as_deref(),
+            None,
+        )
+        .await
+        .map_err(|e| SellError::Rate(Box::new(e)))?;
+        decimal("Rate", &channel.rate)
+    }
+
+    async fn pick_channel(&self, fiat_amount: Decimal) -> Result<models::Channel, SellError> {
+        let mut selector = self
+            .selector
+            .clone()
+            .unwrap_or_default()
+            .fiat_amount(fiat_amount);
+        if let Some(country) = &self.country {
+            selector = selector.country(country);
+        }
+        if let Some(fiat_currency) = &self.fiat_currency {
+            selector = selector.fiat_currency(fiat_currency);
+        }
+        let selection = selector
+            .fetch(
+                &self.configuration,
+                &self.crypto_currency,
+                self.customer_id.as_deref(),
+                self.payment_method_id.as_deref(),
+            )
+            .await
+            .map_err(|e| SellError::Channels(Box::new(e)))?;
+        match selection.ranked.into_iter().next() {
+            Some(ranked) => Ok(ranked.candidate.channel),
+            None => Err(SellError::NoChannel {
+                excluded: selection.excluded,
+            }),
+        }
+    }
+
+    async fn fill_form(
+        &self,
+        channel: &models::Channel,
+    ) -> Result<Option<HashMap<String, Value>>, SellError> {
+        if self.payment_method_id.is_some() && self.form.is_empty() {
+            return Ok(None);
+        }
+        let schema = utilities_api::channels_channel_id_form_get(
+            &self.configuration,
+            &channel.id,
+            self.customer_id.as_deref(),
+            self.payment_method_id.as_deref(),
+            None,
+        )
+        .await
+        .map_err(|e| SellError::Form(Box::new(e)))?
+        .form_schema;
+        let Some(schema) = schema else {
+            return Ok((!self.form.is_empty()).then(|| self.form.clone()));
+        };
+        let mut form: HashMap<String, Value> = schema
+            .properties
+            .iter()
+            .filter_map(|(name, property)| Some((name.clone(), property.get("default")?.clone())))
+            .collect();
+        form.extend(self.form.clone());
+        let mut missing: Vec<String> = schema
+            .required
+            .iter()
+            .flatten()
+            .filter(|name| !form.contains_key(*name))
+            .cloned()
+            .collect();
+        if !missing.is_empty() {
+            missing.sort();
+            return Err(SellError::MissingFormFields(missing));
+        }
+        Ok(Some(form))
+    }
+}
+
+fn decimal(field: &'static str, value: &str) -> Result<Decimal, SellError> {
+    value.parse().map_err(|_| SellError::InvalidValue {
+        field,
+        value: value.to_owned(),
+    })
+}
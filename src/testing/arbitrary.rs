@@ -0,0 +1,278 @@
+//! `proptest` strategies for the models, available with the `proptest` feature.
+//!
+//! Generated instances start from the [fixtures](super::fixtures) and vary the fields handlers
+//! usually branch on: IDs, statuses, directions, currencies, amounts and timestamps. They stay
+//! valid, so amounts are always positive decimal strings and timestamps are RFC 3339.
+
+use proptest::prelude::*;
+use proptest::sample::select;
+use uuid::Uuid;
+
+use super::fixtures;
+use crate::models;
+
+const CRYPTO_CURRENCIES: &[&str] = &["USDC_TEST", "USDT_TEST", "BTC_TEST", "ETH_TEST"];
+const FIAT_CURRENCIES: &[(&str, &str, &str)] = &[
+    ("EUR", "DE", "BankSepa"),
+    ("USD", "US", "BankFedwire"),
+    ("GBP", "GB", "BankLocal"),
+];
+
+fn uuid() -> impl Strategy<Value = Uuid> {
+    any::<u128>().prop_map(Uuid::from_u128)
+}
+
+/// Positive amounts with two decimal places, up to one million.
+fn amount() -> impl Strategy<Value = String> {
+    (1u64..100_000_000).prop_map(|cents| format!("{}.{:02}", cents / 100, cents % 100))
+}
+
+/// Timestamps between 2020 and 2030.
+fn timestamp() -> impl Strategy<Value = String> {
+    (1_577_836_800i64..1_893_456_000).prop_map(|seconds| {
+        chrono::DateTime::from_timestamp(seconds, 0)
+            .expect("in range")
+            .to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
+    })
+}
+
+fn customer_id() -> impl Strategy<Value = String> {
+    "[a-z0-9-]{1,36}"
+}
+
+impl Arbitrary for models::TransactionStatus {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with((): ()) -> Self::Strategy {
+        select(vec![
+            models::TransactionStatus::Pending,
+            models::TransactionStatus::Failed,
+            models::TransactionStatus::Settled,
+        ])
+        .boxed()
+    }
+}
+
+impl Arbitrary for models::TransactionDirection {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with((): ()) -> Self::Strategy {
+        select(vec![
+            models::TransactionDirection::In,
+            models::TransactionDirection::Out,
+        ])
+        .boxed()
+    }
+}
+
+impl Arbitrary for models::FiatDepositStatus {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with((): ()) -> Self::Strategy {
+        select(vec![
+            models::FiatDepositStatus::Pending,
+            models::FiatDepositStatus::Failed,
+            models::FiatDepositStatus::Settled,
+        ])
+        .boxed()
+    }
+}
+
+impl Arbitrary for models::Transaction {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with((): ()) -> Self::Strategy {
+        (
+            uuid(),
+            any::<models::TransactionStatus>(),
+            any::<models::TransactionDirection>(),
+            customer_id(),
+            select(CRYPTO_CURRENCIES),
+            select(FIAT_CURRENCIES),
+            amount(),
+            amount(),
+            timestamp(),
+        )
+            .prop_map(
+                |(
+                    id,
+                    status,
+                    direction,
+                    customer_id,
+                    crypto,
+                    (fiat, _, _),
+                    amount,
+                    fiat_amount,
+                    created,
+                )| {
+                    let mut transaction = fixtures::transaction();
+                    transaction.id = id;
+                    transaction.status = status;
+                    transaction.direction = direction;
+                    transaction.customer_id = Some(customer_id);
+                    transaction.external_id = Some(format!("payout-{}", id.simple()));
+                    transaction.crypto_currency = crypto.to_owned();
+                    transaction.amount = Some(amount);
+                    transaction.created = created;
+                    if let Some(payment) = transaction.fiat_payment.as_deref_mut() {
+                        payment.amount = fiat_amount;
+                        payment.fiat_currency = fiat.to_owned();
+                        payment.rate = None;
+                    }
+                    transaction
+                },
+            )
+            .boxed()
+    }
+}
+
+impl Arbitrary for models::CheckoutSession {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with((): ()) -> Self::Strategy {
+        (
+            uuid(),
+            select(vec!["Pending", "Failed", "Settled"]),
+            select(vec!["PayinCrypto", "PayinFiat", "PayoutFiat"]),
+            customer_id(),
+            select(CRYPTO_CURRENCIES),
+            select(FIAT_CURRENCIES),
+            amount(),
+            timestamp(),
+        )
+            .prop_map(
+                |(id, status, kind, customer_id, crypto, (fiat, _, _), amount, created)| {
+                    let mut session = fixtures::checkout_session();
+                    session.checkout_session_id = id.to_string();
+                    session.status = status.to_owned();
+                    session.r#type = kind.to_owned();
+                    session.customer_id = customer_id;
+                    let (source, destination) = match kind {
+                        "PayinCrypto" => (crypto, crypto),
+                        "PayinFiat" => (fiat, crypto),
+                        _ => (crypto, fiat),
+                    };
+                    session.source_currency = source.to_owned();
+                    session.destination_currency = destination.to_owned();
+                    session.source_amount = Some(amount.clone());
+                    for item in &mut session.line_items {
+                        item.unit_amount = amount.clone();
+                        item.total_amount = amount.clone();
+                    }
+                    session.created = created;
+                    session
+                },
+            )
+            .boxed()
+    }
+}
+
+impl Arbitrary for models::FiatDeposit {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with((): ()) -> Self::Strategy {
+        (
+            uuid(),
+            uuid(),
+            any::<models::FiatDepositStatus>(),
+            customer_id(),
+            select(FIAT_CURRENCIES),
+            amount(),
+            timestamp(),
+        )
+            .prop_map(
+                |(
+                    id,
+                    payment_method_id,
+                    status,
+                    customer_id,
+                    (fiat, _, method),
+                    amount,
+                    created,
+                )| {
+                    let mut deposit = fixtures::fiat_deposit();
+                    deposit.id = id.to_string();
+                    deposit.payment_method_id = payment_method_id.to_string();
+                    deposit.status = status;
+                    deposit.customer_id = Some(customer_id);
+                    deposit.fiat_currency = fiat.to_owned();
+                    deposit.payment_method_type = method.to_owned();
+                    deposit.fiat_amount = amount;
+                    deposit.created = created;
+                    deposit
+                },
+            )
+            .boxed()
+    }
+}
+
+impl Arbitrary for models::BusinessCustomer {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with((): ()) -> Self::Strategy {
+        (
+            customer_id(),
+            "[A-Z][a-z]{2,15}( [A-Z][a-z]{2,15})? (GmbH|Ltd|Inc|S\\.A\\.)",
+            select(FIAT_CURRENCIES),
+            select(vec![
+                models::verification::Status::Approved,
+                models::verification::Status::Pending,
+                models::verification::Status::Declined,
+            ]),
+            timestamp(),
+        )
+            .prop_map(|(customer_id, name, (_, country, _), status, created)| {
+                let mut customer = fixtures::business_customer();
+                customer.customer_id = customer_id;
+                customer.registered_name = name;
+                customer.registration_country = Some(country.to_owned());
+                if let Some(address) = customer.registered_address.as_deref_mut() {
+                    address.country = country.to_owned();
+                }
+                customer.verification.status = status;
+                customer.created = created;
+                customer
+            })
+            .boxed()
+    }
+}
+
+impl Arbitrary for models::Channel {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with((): ()) -> Self::Strategy {
+        (
+            uuid(),
+            select(FIAT_CURRENCIES),
+            1u32..10_000_000,
+            1u64..1_000,
+            60i32..604_800,
+            select(vec![
+                models::ProcessingTier::Standard,
+                models::ProcessingTier::Priority,
+            ]),
+        )
+            .prop_map(|(id, (fiat, country, method), rate, min, seconds, tier)| {
+                let mut channel = fixtures::channel();
+                channel.id = id.to_string();
+                channel.fiat_currency = fiat.to_owned();
+                channel.country = country.to_owned();
+                channel.payment_method_type = method.to_owned();
+                channel.rate = format!("{}.{:04}", rate / 10_000, rate % 10_000);
+                channel.limits.min_limit = min.to_string();
+                channel.limits.max_limit = Some((min * 1_000).to_string());
+                channel.processing_seconds = seconds;
+                channel.processing_tier = Some(tier);
+                channel
+            })
+            .boxed()
+    }
+}
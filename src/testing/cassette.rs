@@ -0,0 +1,401 @@
+//! Recording and replaying HTTP interactions.
+
+use std::collections::BTreeMap;
+use std::error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use bytes::Bytes;
+use http::{HeaderMap, HeaderValue, StatusCode};
+use serde::{Deserialize, Serialize};
+
+use super::routes;
+use super::server::lock;
+use crate::apis::configuration::Configuration;
+use crate::redact::redact_json;
+
+/// Headers whose values never reach a cassette.
+const SCRUBBED_HEADERS: &[&str] = &[
+    "api-signature",
+    "authorization",
+    "cookie",
+    "set-cookie",
+    "x-api-key",
+];
+const REDACTED: &str = "[REDACTED]";
+
+/// A VCR-style cassette: records the interactions of the endpoint functions to a JSON file, or
+/// replays them without network access.
+///
+/// A cassette is attached through [`Configuration::cassette`]. Retries, deadlines and the
+/// environment guards still apply; only the exchange with the server is recorded or replayed.
+/// `X-Api-Key`, `Api-Signature` and other credential headers are scrubbed before anything is
+/// stored, as are the personal data fields listed in [`PII_FIELDS`](crate::redact::PII_FIELDS).
+///
+/// During replay, requests are matched on method, templated path with its parameters, and
+/// query parameters regardless of their order. Interactions with the same key are replayed in
+/// the order they were recorded, each once. A request with nothing left to replay gets a `501`
+/// response describing it.
+///
+/// ```no_run
+/// use std::sync::Arc;
+/// use noah_sdk::apis::configuration::Configuration;
+/// use noah_sdk::testing::Cassette;
+///
+/// # fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// // Replays the file if it exists, otherwise records to it
+/// let cassette = Arc::new(Cassette::open("tests/cassettes/balances.json")?);
+/// let mut config = Configuration::from_env()?;
+/// config.cassette = Some(cassette.clone());
+/// // ... call endpoints ...
+/// cassette.save()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Cassette {
+    path: PathBuf,
+    mode: CassetteMode,
+    tape: Mutex<Tape>,
+}
+
+#[derive(Debug, Default)]
+struct Tape {
+    interactions: Vec<Interaction>,
+    /// Replay: which interactions were used.
+    played: Vec<bool>,
+    /// Record: whether there are interactions not yet saved.
+    dirty: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CassetteMode {
+    /// Calls the server and stores every exchange.
+    Record,
+    /// Answers from the stored exchanges without calling the server.
+    Replay,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Interaction {
+    /// The endpoint function that made the request.
+    pub operation: String,
+    pub request: RecordedCall,
+    pub response: RecordedResponse,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RecordedCall {
+    pub method: String,
+    /// Path relative to the base path, e.g. `/channels/sell`.
+    pub path: String,
+    /// Decoded query parameters, sorted.
+    pub query: Vec<(String, String)>,
+    pub headers: BTreeMap<String, String>,
+    pub body: Option<serde_json::Value>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RecordedResponse {
+    pub status: u16,
+    pub headers: BTreeMap<String, String>,
+    /// The JSON body, or the raw text for other content types.
+    pub body: Option<serde_json::Value>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CassetteFile {
+    interactions: Vec<Interaction>,
+}
+
+#[derive(Debug)]
+pub enum CassetteError {
+    Io(io::Error),
+    Serde(serde_json::Error),
+}
+
+impl fmt::Display for CassetteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CassetteError::Io(e) => write!(f, "cassette IO error: {e}"),
+            CassetteError::Serde(e) => write!(f, "cassette serde error: {e}"),
+        }
+    }
+}
+
+impl error::Error for CassetteError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            CassetteError::Io(e) => Some(e),
+            CassetteError::Serde(e) => Some(e),
+        }
+    }
+}
+
+impl From<io::Error> for CassetteError {
+    fn from(e: io::Error) -> Self {
+        CassetteError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for CassetteError {
+    fn from(e: serde_json::Error) -> Self {
+        CassetteError::Serde(e)
+    }
+}
+
+impl Cassette {
+    /// Starts an empty recording that will be written to `path`, replacing any existing file.
+    pub fn record(path: impl AsRef<Path>) -> Cassette {
+        Cassette::new(path.as_ref(), CassetteMode::Record, Vec::new())
+    }
+
+    /// Loads the cassette at `path` for replay.
+    pub fn replay(path: impl AsRef<Path>) -> Result<Cassette, CassetteError> {
+        let file: CassetteFile = serde_json::from_slice(&fs::read(path.as_ref())?)?;
+        Ok(Cassette::new(
+            path.as_ref(),
+            CassetteMode::Replay,
+            file.interactions,
+        ))
+    }
+
+    /// Replays `path` if it exists and records to it otherwise. Delete the file to re-record.
+    pub fn open(path: impl AsRef<Path>) -> Result<Cassette, CassetteError> {
+        if path.as_ref().exists() {
+            Cassette::replay(path)
+        } else {
+            Ok(Cassette::record(path))
+        }
+    }
+
+    fn new(path: &Path, mode: CassetteMode, interactions: Vec<Interaction>) -> Cassette {
+        Cassette {
+            path: path.to_owned(),
+            mode,
+            tape: Mutex::new(Tape {
+                played: vec![false; interactions.len()],
+                interactions,
+                dirty: false,
+            }),
+        }
+    }
+
+    pub fn mode(&self) -> CassetteMode {
+        self.mode
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn interactions(&self) -> Vec<Interaction> {
+        lock(&self.tape).interactions.clone()
+    }
+
+    /// Interactions that have not been replayed yet.
+    pub fn unplayed(&self) -> Vec<Interaction> {
+        let tape = lock(&self.tape);
+        tape.interactions
+            .iter()
+            .zip(&tape.played)
+            .filter(|(_, played)| !**played)
+            .map(|(interaction, _)| interaction.clone())
+            .collect()
+    }
+
+    /// Writes the recording. Also done on drop, ignoring errors.
+    pub fn save(&self) -> Result<(), CassetteError> {
+        if self.mode != CassetteMode::Record {
+            return Ok(());
+        }
+        let mut tape = lock(&self.tape);
+        let file = CassetteFile {
+            interactions: tape.interactions.clone(),
+        };
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut tmp = self.path.clone().into_os_string();
+        tmp.push(".tmp");
+        fs::write(&tmp, serde_json::to_vec_pretty(&file)?)?;
+        fs::rename(&tmp, &self.path)?;
+        tape.dirty = false;
+        Ok(())
+    }
+
+    pub(crate) async fn send(
+        &self,
+        configuration: &Configuration,
+        operation: &str,
+        req: reqwest::Request,
+    ) -> Result<reqwest::Response, reqwest::Error> {
+        let call = record_call(configuration, &req);
+        let response = match self.mode {
+            CassetteMode::Record => {
+                let response = crate::apis::send(&configuration.client, req).await?;
+                let mut tape = lock(&self.tape);
+                tape.interactions.push(Interaction {
+                    operation: operation.to_owned(),
+                    request: call,
+                    response: record_response(&response),
+                });
+                tape.dirty = true;
+                response
+            }
+            CassetteMode::Replay => match self.take(&call) {
+                Some(recorded) => replay_response(&recorded),
+                None => missing(operation, &call),
+            },
+        };
+        Ok(reqwest::Response::from(response))
+    }
+
+    /// Marks the first unplayed interaction matching `call` as played and returns its response.
+    fn take(&self, call: &RecordedCall) -> Option<RecordedResponse> {
+        let key = match_key(call);
+        let mut tape = lock(&self.tape);
+        let index = (0..tape.interactions.len())
+            .find(|&i| !tape.played[i] && match_key(&tape.interactions[i].request) == key)?;
+        tape.played[index] = true;
+        Some(tape.interactions[index].response.clone())
+    }
+}
+
+impl Drop for Cassette {
+    fn drop(&mut self) {
+        if lock(&self.tape).dirty {
+            let _ = self.save();
+        }
+    }
+}
+
+/// Method, route template, decoded path parameters and sorted query parameters.
+type MatchKey = (String, String, Vec<(String, String)>, Vec<(String, String)>);
+
+fn match_key(call: &RecordedCall) -> MatchKey {
+    let method = call.method.to_uppercase();
+    let mut query = call.query.clone();
+    query.sort();
+    let (template, mut params) = match method
+        .parse()
+        .ok()
+        .and_then(|method| routes::find(&method, &call.path))
+    {
+        Some((route, params)) => (
+            route.template.to_owned(),
+            params
+                .into_iter()
+                .map(|(name, value)| (name.to_owned(), value))
+                .collect(),
+        ),
+        None => (call.path.clone(), Vec::new()),
+    };
+    params.sort();
+    (method, template, params, query)
+}
+
+fn record_call(configuration: &Configuration, req: &reqwest::Request) -> RecordedCall {
+    let base = url::Url::parse(&configuration.base_path)
+        .map(|url| url.path().trim_end_matches('/').to_owned())
+        .unwrap_or_default();
+    let path = req.url().path();
+    let path = path.strip_prefix(base.as_str()).unwrap_or(path).to_owned();
+    let mut query: Vec<(String, String)> = req.url().query_pairs().into_owned().collect();
+    query.sort();
+    let body = req
+        .body()
+        .and_then(|body| body.as_bytes())
+        .and_then(|bytes| serde_json::from_slice(bytes).ok())
+        .map(|mut body| {
+            redact_json(&mut body);
+            body
+        });
+    RecordedCall {
+        method: req.method().to_string(),
+        path,
+        query,
+        headers: record_headers(req.headers()),
+        body,
+    }
+}
+
+fn record_response(response: &http::Response<Bytes>) -> RecordedResponse {
+    let body = if response.body().is_empty() {
+        None
+    } else if is_json(response.headers()) {
+        serde_json::from_slice(response.body())
+            .ok()
+            .map(|mut body| {
+                redact_json(&mut body);
+                body
+            })
+    } else {
+        Some(serde_json::Value::String(
+            String::from_utf8_lossy(response.body()).into_owned(),
+        ))
+    };
+    RecordedResponse {
+        status: response.status().as_u16(),
+        headers: record_headers(response.headers()),
+        body,
+    }
+}
+
+fn record_headers(headers: &HeaderMap) -> BTreeMap<String, String> {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            let value = if SCRUBBED_HEADERS.contains(&name.as_str()) {
+                REDACTED.to_owned()
+            } else {
+                String::from_utf8_lossy(value.as_bytes()).into_owned()
+            };
+            (name.to_string(), value)
+        })
+        .collect()
+}
+
+fn is_json(headers: &HeaderMap) -> bool {
+    headers
+        .get(http::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.contains("json"))
+}
+
+fn replay_response(recorded: &RecordedResponse) -> http::Response<Bytes> {
+    let mut headers = HeaderMap::new();
+    for (name, value) in &recorded.headers {
+        // The body is re-serialized, so its framing headers no longer apply.
+        if name == "content-length" || name == "transfer-encoding" {
+            continue;
+        }
+        if let (Ok(name), Ok(value)) = (
+            http::HeaderName::from_bytes(name.as_bytes()),
+            HeaderValue::from_str(value),
+        ) {
+            headers.append(name, value);
+        }
+    }
+    let body = match &recorded.body {
+        None => Bytes::new(),
+        Some(serde_json::Value::String(text)) if !is_json(&headers) => Bytes::from(text.clone()),
+        Some(body) => Bytes::from(serde_json::to_vec(body).expect("JSON values serialize")),
+    };
+    let mut response = http::Response::new(body);
+    *response.status_mut() = StatusCode::from_u16(recorded.status).unwrap_or(StatusCode::OK);
+    *response.headers_mut() = headers;
+    response
+}
+
+fn missing(operation: &str, call: &RecordedCall) -> http::Response<Bytes> {
+    let mut response = http::Response::new(Bytes::from(format!(
+        "no recorded interaction left for {operation}: {} {} {:?}",
+        call.method, call.path, call.query
+    )));
+    *response.status_mut() = StatusCode::NOT_IMPLEMENTED;
+    response
+}
@@ -0,0 +1,171 @@
+//! Factories for valid, realistic model instances.
+//!
+//! Every factory returns a complete instance with fresh IDs, sandbox currencies and amounts that
+//! agree with each other. Override fields with struct update syntax:
+//!
+//! ```
+//! use noah_sdk::models::{Transaction, TransactionStatus};
+//! use noah_sdk::testing::fixtures;
+//!
+//! let settled = Transaction {
+//!     status: TransactionStatus::Settled,
+//!     ..fixtures::transaction()
+//! };
+//! assert_eq!(settled.customer_id.as_deref(), Some(fixtures::CUSTOMER_ID));
+//! ```
+
+use uuid::Uuid;
+
+use super::state::now;
+use crate::models;
+
+/// The customer every factory refers to.
+pub const CUSTOMER_ID: &str = "cust-1";
+
+fn approved() -> Box<models::Verification> {
+    Box::new(models::Verification {
+        model: "Reliance".to_owned(),
+        status: models::verification::Status::Approved,
+    })
+}
+
+fn address() -> Box<models::StreetAddress> {
+    Box::new(models::StreetAddress {
+        street: "Friedrichstraße 68".to_owned(),
+        street2: None,
+        city: "Berlin".to_owned(),
+        post_code: "10117".to_owned(),
+        state: "BE".to_owned(),
+        country: "DE".to_owned(),
+    })
+}
+
+fn bank_display() -> Box<models::PaymentMethodDisplayDetails> {
+    Box::new(
+        models::PaymentMethodDisplayDetails::FiatPaymentMethodBankDisplay(Box::new(
+            models::FiatPaymentMethodBankDisplay {
+                account_number: Some("DE89370400440532013000".to_owned()),
+                bank_code: Some("COBADEFFXXX".to_owned()),
+                ..Default::default()
+            },
+        )),
+    )
+}
+
+/// An approved individual customer with ID [`CUSTOMER_ID`].
+pub fn individual_customer() -> models::IndividualCustomer {
+    models::IndividualCustomer {
+        customer_id: CUSTOMER_ID.to_owned(),
+        created: now(),
+        date_of_birth: "1990-01-01".to_owned(),
+        full_name: Box::new(models::FullName {
+            first_name: "Jane".to_owned(),
+            last_name: "Doe".to_owned(),
+            middle_name: None,
+        }),
+        primary_residence: address(),
+        verification: approved(),
+        ..Default::default()
+    }
+}
+
+/// An approved business customer with ID [`CUSTOMER_ID`], registered in Germany.
+pub fn business_customer() -> models::BusinessCustomer {
+    models::BusinessCustomer {
+        customer_id: CUSTOMER_ID.to_owned(),
+        created: now(),
+        registered_name: "Acme GmbH".to_owned(),
+        email: Some("finance@acme.example".to_owned()),
+        registration_number: Some("HRB 123456".to_owned()),
+        registration_country: Some("DE".to_owned()),
+        registered_address: Some(address()),
+        incorporation_date: Some("2015-06-01".to_owned()),
+        verification: approved(),
+        ..Default::default()
+    }
+}
+
+/// A SEPA sell channel for EUR in Germany, at 0.92 EUR per `USDC_TEST`.
+pub fn channel() -> models::Channel {
+    models::Channel {
+        id: Uuid::new_v4().to_string(),
+        payment_method_category: "Bank".to_owned(),
+        payment_method_type: "BankSepa".to_owned(),
+        fiat_currency: "EUR".to_owned(),
+        country: "DE".to_owned(),
+        limits: Box::new(models::ChannelLimits {
+            min_limit: "10".to_owned(),
+            max_limit: Some("10000".to_owned()),
+        }),
+        rate: "0.92".to_owned(),
+        processing_seconds: 86400,
+        processing_tier: Some(models::ProcessingTier::Standard),
+        ..Default::default()
+    }
+}
+
+/// A pending sell of 101 `USDC_TEST` for 92 EUR, of which 1 `USDC_TEST` is fee.
+pub fn transaction() -> models::Transaction {
+    models::Transaction {
+        id: Uuid::new_v4(),
+        network: "OffNetwork".to_owned(),
+        created: now(),
+        status: models::TransactionStatus::Pending,
+        direction: models::TransactionDirection::Out,
+        customer_id: Some(CUSTOMER_ID.to_owned()),
+        external_id: Some(format!("payout-{}", Uuid::new_v4().simple())),
+        amount: Some("101.00".to_owned()),
+        crypto_currency: "USDC_TEST".to_owned(),
+        fiat_payment: Some(Box::new(models::FiatPayment {
+            amount: "92".to_owned(),
+            fee_amount: "1.00".to_owned(),
+            rate: Some("0.92".to_owned()),
+            fiat_currency: "EUR".to_owned(),
+            ..Default::default()
+        })),
+        ..Default::default()
+    }
+}
+
+/// A pending fiat pay-in session for 100 EUR into `USDC_TEST`.
+pub fn checkout_session() -> models::CheckoutSession {
+    models::CheckoutSession {
+        checkout_session_id: Uuid::new_v4().to_string(),
+        payment_method_category: Some("Bank".to_owned()),
+        source_currency: "EUR".to_owned(),
+        destination_currency: "USDC_TEST".to_owned(),
+        source_amount: Some("100".to_owned()),
+        status: "Pending".to_owned(),
+        customer_id: CUSTOMER_ID.to_owned(),
+        return_url: "https://example.com/return".to_owned(),
+        line_items: vec![models::LineItem {
+            description: "Account top-up".to_owned(),
+            quantity: "1".to_owned(),
+            unit_amount: "100".to_owned(),
+            total_amount: "100".to_owned(),
+        }],
+        r#type: "PayinFiat".to_owned(),
+        created: now(),
+        ..Default::default()
+    }
+}
+
+/// A pending SEPA deposit of 100 EUR with no refunds.
+pub fn fiat_deposit() -> models::FiatDeposit {
+    models::FiatDeposit {
+        id: Uuid::new_v4().to_string(),
+        created: now(),
+        fiat_amount: "100".to_owned(),
+        fiat_currency: "EUR".to_owned(),
+        reference: Some("SIM-1".to_owned()),
+        status: models::FiatDepositStatus::Pending,
+        customer_id: Some(CUSTOMER_ID.to_owned()),
+        payment_method_id: Uuid::new_v4().to_string(),
+        payment_method_type: "BankSepa".to_owned(),
+        sender: Box::new(models::SenderPaymentMethod {
+            full_name: Some("Jane Doe".to_owned()),
+            details: bank_display(),
+        }),
+        ..Default::default()
+    }
+}
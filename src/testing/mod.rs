@@ -0,0 +1,38 @@
+//! Test helpers, available with the `test-util` feature.
+//!
+//! [`MockNoah`] is a local stand-in for the Noah API: an HTTP server on `127.0.0.1` that serves
+//! every endpoint from in-memory state, so integration tests run without network access or
+//! sandbox credentials. Sells debit balances and create `Pending` transactions, checkout
+//! sessions are created from the request, and `Nonce`-bearing writes are idempotent. Tests can
+//! inspect and change the state directly, and [`Failure`] scripts error responses and delays.
+//!
+//! [`Simulator`] moves fiat deposits and transactions through their lifecycle, on the mock or
+//! the sandbox, and delivers the resulting webhooks to a local URL. With the `webhooks` feature,
+//! [`webhook_signer`] signs them like Noah does.
+//!
+//! [`Cassette`] records the interactions of the endpoint functions, against the sandbox or any
+//! other server, and replays them in CI without network access.
+//!
+//! [`fixtures`] builds valid model instances for tests. With the `proptest` feature, the main
+//! models also implement `proptest::arbitrary::Arbitrary`.
+
+#[cfg(feature = "proptest")]
+mod arbitrary;
+mod cassette;
+pub mod fixtures;
+mod routes;
+mod server;
+#[cfg(feature = "webhooks")]
+mod signing;
+mod simulator;
+mod state;
+
+pub use cassette::{
+    Cassette, CassetteError, CassetteMode, Interaction, RecordedCall, RecordedResponse,
+};
+pub use http::StatusCode;
+pub use server::{Failure, MockNoah, RecordedRequest};
+#[cfg(feature = "webhooks")]
+pub use signing::webhook_signer;
+pub use simulator::{Simulator, SimulatorError};
+pub use state::MockState;
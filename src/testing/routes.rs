@@ -0,0 +1,150 @@
+//! The operation table: which endpoint function serves which method and path template.
+
+use http::Method;
+
+pub(crate) struct Route {
+    pub operation: &'static str,
+    pub method: Method,
+    /// Path relative to the base path, with `{Param}` placeholders as in the API reference.
+    pub template: &'static str,
+}
+
+const fn route(operation: &'static str, method: Method, template: &'static str) -> Route {
+    Route {
+        operation,
+        method,
+        template,
+    }
+}
+
+pub(crate) const ROUTES: &[Route] = &[
+    route(
+        "customers_customer_id_put",
+        Method::PUT,
+        "/customers/{CustomerID}",
+    ),
+    route(
+        "onboarding_customer_id_post",
+        Method::POST,
+        "/onboarding/{CustomerID}",
+    ),
+    route(
+        "onboarding_customer_id_prefill_documents_upload_url_get",
+        Method::GET,
+        "/onboarding/{CustomerID}/prefill/documents/upload-url",
+    ),
+    route(
+        "onboarding_customer_id_prefill_post",
+        Method::POST,
+        "/onboarding/{CustomerID}/prefill",
+    ),
+    route(
+        "checkout_payin_crypto_post",
+        Method::POST,
+        "/checkout/payin/crypto",
+    ),
+    route(
+        "checkout_payin_fiat_post",
+        Method::POST,
+        "/checkout/payin/fiat",
+    ),
+    route(
+        "checkout_payout_fiat_post",
+        Method::POST,
+        "/checkout/payout/fiat",
+    ),
+    route(
+        "hosted_workflows_bank_deposit_to_onchain_address_post",
+        Method::POST,
+        "/hosted-workflows/bank-deposit-to-onchain-address",
+    ),
+    route(
+        "sandbox_fiat_deposit_simulate_post",
+        Method::POST,
+        "/sandbox/fiat-deposit/simulate",
+    ),
+    route(
+        "workflows_bank_deposit_to_onchain_address_post",
+        Method::POST,
+        "/workflows/bank-deposit-to-onchain-address",
+    ),
+    route("transactions_sell_post", Method::POST, "/transactions/sell"),
+    route(
+        "transactions_sell_prepare_post",
+        Method::POST,
+        "/transactions/sell/prepare",
+    ),
+    route(
+        "workflows_onchain_deposit_to_payment_method_post",
+        Method::POST,
+        "/workflows/onchain-deposit-to-payment-method",
+    ),
+    route("balances_get", Method::GET, "/balances"),
+    route(
+        "channels_channel_id_form_get",
+        Method::GET,
+        "/channels/{ChannelID}/form",
+    ),
+    route(
+        "channels_channel_id_get",
+        Method::GET,
+        "/channels/{ChannelID}",
+    ),
+    route(
+        "channels_sell_countries_get",
+        Method::GET,
+        "/channels/sell/countries",
+    ),
+    route("channels_sell_get", Method::GET, "/channels/sell"),
+    route(
+        "customers_customer_id_get",
+        Method::GET,
+        "/customers/{CustomerID}",
+    ),
+    route("customers_get", Method::GET, "/customers"),
+    route("payment_methods_get", Method::GET, "/payment-methods"),
+    route("prices_get", Method::GET, "/prices"),
+    route("transactions_get", Method::GET, "/transactions"),
+    route(
+        "transactions_transaction_id_get",
+        Method::GET,
+        "/transactions/{TransactionID}",
+    ),
+];
+
+/// Finds the route for `method` and `path` (relative to the base path) along with its decoded
+/// path parameters. Literal segments win over placeholders, so `/channels/sell` is not read as a
+/// channel ID.
+pub(crate) fn find(
+    method: &Method,
+    path: &str,
+) -> Option<(&'static Route, Vec<(&'static str, String)>)> {
+    ROUTES
+        .iter()
+        .filter(|route| route.method == *method)
+        .filter_map(|route| Some((route, match_template(route.template, path)?)))
+        .min_by_key(|(_, params)| params.len())
+}
+
+fn match_template(template: &'static str, path: &str) -> Option<Vec<(&'static str, String)>> {
+    let mut params = Vec::new();
+    let mut segments = path.trim_end_matches('/').split('/');
+    for expected in template.split('/') {
+        let segment = segments.next()?;
+        match expected.strip_prefix('{').and_then(|p| p.strip_suffix('}')) {
+            Some(name) if !segment.is_empty() => params.push((name, decode(segment))),
+            Some(_) => return None,
+            None if expected == segment => {}
+            None => return None,
+        }
+    }
+    segments.next().is_none().then_some(params)
+}
+
+/// Reverses [`crate::apis::urlencode`].
+fn decode(segment: &str) -> String {
+    url::form_urlencoded::parse(format!("v={segment}").as_bytes())
+        .next()
+        .map(|(_, value)| value.into_owned())
+        .unwrap_or_default()
+}
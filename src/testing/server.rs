@@ -0,0 +1,319 @@
+//! The HTTP side of the mock: listener, request recording and failure injection.
+
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+use bytes::Bytes;
+use http::{HeaderMap, Method, StatusCode};
+use http_body_util::{BodyExt, Full};
+use hyper::body::Incoming;
+use hyper::server::conn::http1;
+use hyper::service::service_fn;
+use hyper_util::rt::TokioIo;
+use tokio::net::TcpListener;
+use tokio::sync::oneshot;
+
+use super::routes;
+use super::state::{Call, MockState, Reply};
+use crate::apis::configuration::{Configuration, Environment};
+
+/// Prefix of every API path, matching the `/v1` of the real base paths.
+const BASE_PATH: &str = "/v1";
+
+/// An in-process HTTP server that emulates the Noah API.
+///
+/// Each server has its own [`MockState`], seeded with a `USDC_TEST` balance, a few sell channels
+/// and prices. Requests without an `X-Api-Key` header get `401`, like the real API. The server
+/// stops when the value is dropped.
+///
+/// ```
+/// use noah_sdk::apis::utilities_api;
+/// use noah_sdk::testing::{Failure, MockNoah, StatusCode};
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let noah = MockNoah::start().await?;
+/// let config = noah.configuration();
+///
+/// noah.inject(Failure::status("balances_get", StatusCode::SERVICE_UNAVAILABLE));
+/// assert!(utilities_api::balances_get(&config, None, None, None).await.is_err());
+///
+/// let balances = utilities_api::balances_get(&config, None, None, None).await?;
+/// assert_eq!(balances.items[0].crypto_currency, "USDC_TEST");
+/// # Ok(())
+/// # }
+/// ```
+pub struct MockNoah {
+    addr: SocketAddr,
+    shared: Arc<Shared>,
+    _shutdown: oneshot::Sender<()>,
+}
+
+pub(crate) struct Shared {
+    pub(crate) state: Mutex<MockState>,
+    failures: Mutex<Vec<Failure>>,
+}
+
+impl MockNoah {
+    /// Binds a random local port and starts serving on the current Tokio runtime.
+    pub async fn start() -> std::io::Result<MockNoah> {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).await?;
+        let addr = listener.local_addr()?;
+        let shared = Arc::new(Shared {
+            state: Mutex::new(MockState::seeded(format!("http://{addr}"))),
+            failures: Mutex::new(Vec::new()),
+        });
+        let (shutdown, mut stopped) = oneshot::channel();
+
+        let server = shared.clone();
+        tokio::spawn(async move {
+            loop {
+                let stream = tokio::select! {
+                    _ = &mut stopped => break,
+                    accepted = listener.accept() => match accepted {
+                        Ok((stream, _)) => stream,
+                        Err(_) => continue,
+                    },
+                };
+                let shared = server.clone();
+                tokio::spawn(async move {
+                    let service = service_fn(move |req| serve(shared.clone(), req));
+                    let _ = http1::Builder::new()
+                        .serve_connection(TokioIo::new(stream), service)
+                        .await;
+                });
+            }
+        });
+
+        Ok(MockNoah {
+            addr,
+            shared,
+            _shutdown: shutdown,
+        })
+    }
+
+    /// The base path to point a [`Configuration`] at, ending in `/v1`.
+    pub fn url(&self) -> String {
+        format!("http://{}{BASE_PATH}", self.addr)
+    }
+
+    /// A configuration for this server with a test API key. Being a custom environment, it is
+    /// not subject to the sandbox/production guards.
+    pub fn configuration(&self) -> Configuration {
+        let url = self.url().parse().expect("mock URL is valid");
+        let mut configuration = Configuration::for_environment(Environment::Custom(url));
+        configuration.api_key = Some(crate::apis::configuration::ApiKey {
+            prefix: None,
+            key: "mock-api-key".into(),
+        });
+        configuration
+    }
+
+    /// Locks the server state for inspection or changes. Requests wait while the guard is held.
+    pub fn state(&self) -> MutexGuard<'_, MockState> {
+        lock(&self.shared.state)
+    }
+
+    /// Queues a failure. Failures apply in the order they were injected, each to the next
+    /// `times` matching requests.
+    pub fn inject(&self, failure: Failure) {
+        lock(&self.shared.failures).push(failure);
+    }
+
+    /// Drops any injected failures that have not fired yet.
+    pub fn clear_failures(&self) {
+        lock(&self.shared.failures).clear();
+    }
+
+    pub(crate) fn shared(&self) -> Arc<Shared> {
+        self.shared.clone()
+    }
+}
+
+pub(crate) fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    // A test that panicked while holding the lock should not take the server down with it.
+    mutex
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// A scripted failure for one operation, or for all of them with `"*"`.
+///
+/// ```
+/// use std::time::Duration;
+/// use noah_sdk::testing::{Failure, StatusCode};
+///
+/// let outage = Failure::status("transactions_sell_post", StatusCode::BAD_GATEWAY).times(2);
+/// let slow = Failure::delay("*", Duration::from_secs(2));
+/// ```
+#[derive(Clone, Debug)]
+pub struct Failure {
+    operation: String,
+    status: Option<StatusCode>,
+    delay: Duration,
+    detail: String,
+    headers: HeaderMap,
+    times: usize,
+}
+
+impl Failure {
+    /// Answers the next matching request with `status` and a [`models::Error`] body instead of
+    /// running the operation.
+    ///
+    /// [`models::Error`]: crate::models::Error
+    pub fn status(operation: &str, status: StatusCode) -> Failure {
+        Failure {
+            operation: operation.to_owned(),
+            status: Some(status),
+            delay: Duration::ZERO,
+            detail: "injected failure".to_owned(),
+            headers: HeaderMap::new(),
+            times: 1,
+        }
+    }
+
+    /// Holds the next matching request for `delay` before answering it normally.
+    pub fn delay(operation: &str, delay: Duration) -> Failure {
+        Failure {
+            status: None,
+            delay,
+            ..Failure::status(operation, StatusCode::OK)
+        }
+    }
+
+    /// Applies the failure to this many requests instead of one.
+    pub fn times(mut self, times: usize) -> Failure {
+        self.times = times;
+        self
+    }
+
+    /// Also waits `delay` before sending the failure status.
+    pub fn after(mut self, delay: Duration) -> Failure {
+        self.delay = delay;
+        self
+    }
+
+    /// Adds a header to the failure response, such as `Retry-After`.
+    pub fn header(mut self, name: &'static str, value: &str) -> Failure {
+        let value = value.parse().expect("header value is valid");
+        self.headers.insert(name, value);
+        self
+    }
+
+    /// Sets the `Detail` of the error body.
+    pub fn detail(mut self, detail: &str) -> Failure {
+        self.detail = detail.to_owned();
+        self
+    }
+
+    fn matches(&self, operation: &str) -> bool {
+        self.times > 0 && (self.operation == "*" || self.operation == operation)
+    }
+}
+
+/// A request as received by [`MockNoah`].
+#[derive(Clone, Debug)]
+pub struct RecordedRequest {
+    /// The endpoint function the request maps to, if any.
+    pub operation: Option<&'static str>,
+    pub method: Method,
+    /// Path relative to the base path, e.g. `/transactions/sell`.
+    pub path: String,
+    pub query: Vec<(String, String)>,
+    pub headers: HeaderMap,
+    pub body: Option<serde_json::Value>,
+}
+
+async fn serve(
+    shared: Arc<Shared>,
+    req: http::Request<Incoming>,
+) -> Result<http::Response<Full<Bytes>>, Infallible> {
+    let (parts, body) = req.into_parts();
+    let body = body
+        .collect()
+        .await
+        .map(|b| b.to_bytes())
+        .unwrap_or_default();
+    let path = parts.uri.path();
+    let path = path.strip_prefix(BASE_PATH).unwrap_or(path).to_owned();
+    let query: Vec<(String, String)> =
+        url::form_urlencoded::parse(parts.uri.query().unwrap_or_default().as_bytes())
+            .into_owned()
+            .collect();
+    let route = routes::find(&parts.method, &path);
+
+    lock(&shared.state).requests.push(RecordedRequest {
+        operation: route.as_ref().map(|(route, _)| route.operation),
+        method: parts.method.clone(),
+        path: path.clone(),
+        query: query.clone(),
+        headers: parts.headers.clone(),
+        body: serde_json::from_slice(&body).ok(),
+    });
+
+    let mut headers = HeaderMap::new();
+    let reply = match route {
+        None => Reply::error(
+            StatusCode::NOT_FOUND,
+            "ResourceNotFound",
+            format!("no route for {} {path}", parts.method),
+        ),
+        Some(_) if !parts.headers.contains_key("X-Api-Key") => Reply::error(
+            StatusCode::UNAUTHORIZED,
+            "Unauthorized",
+            "missing X-Api-Key header",
+        ),
+        Some((route, params)) => {
+            let failure = take_failure(&shared, route.operation);
+            if let Some(failure) = &failure {
+                tokio::time::sleep(failure.delay).await;
+            }
+            match failure.and_then(|f| f.status.map(|status| (status, f.detail, f.headers))) {
+                Some((status, detail, failure_headers)) => {
+                    headers = failure_headers;
+                    Reply::error(status, "Unexpected", detail)
+                }
+                None => {
+                    let call = Call {
+                        operation: route.operation,
+                        params,
+                        query,
+                        body,
+                    };
+                    lock(&shared.state).handle(&call)
+                }
+            }
+        }
+    };
+    let mut response = respond(reply);
+    response.headers_mut().extend(headers);
+    Ok(response)
+}
+
+/// Consumes one use of the first injected failure matching `operation`.
+fn take_failure(shared: &Shared, operation: &str) -> Option<Failure> {
+    let mut failures = lock(&shared.failures);
+    let index = failures.iter().position(|f| f.matches(operation))?;
+    failures[index].times -= 1;
+    let failure = failures[index].clone();
+    if failure.times == 0 {
+        failures.remove(index);
+    }
+    Some(failure)
+}
+
+fn respond(reply: Reply) -> http::Response<Full<Bytes>> {
+    let mut builder = http::Response::builder().status(reply.status);
+    let body = match reply.body {
+        Some(body) => {
+            builder = builder.header(http::header::CONTENT_TYPE, "application/json");
+            Bytes::from(serde_json::to_vec(&body).expect("JSON values serialize"))
+        }
+        None => Bytes::new(),
+    };
+    builder
+        .body(Full::new(body))
+        .expect("response parts are valid")
+}
@@ -0,0 +1,22 @@
+//! Signing webhooks the way Noah does.
+
+use jsonwebtoken::{Algorithm, EncodingKey, Header};
+use sha2::{Digest, Sha256};
+
+use crate::webhooks::WebhookError;
+
+/// Returns a signer for [`Simulator::webhook_signer`](super::Simulator::webhook_signer) that
+/// produces `Webhook-Signature` values with the PEM-encoded ES384 private key, for verification
+/// with [`WebhookVerifier::from_pem`](crate::webhooks::WebhookVerifier::from_pem) and the matching
+/// public key.
+pub fn webhook_signer(
+    private_key: &[u8],
+) -> Result<impl Fn(&[u8]) -> String + Send + Sync + 'static, WebhookError> {
+    let key = EncodingKey::from_ec_pem(private_key)
+        .map_err(|e| WebhookError::InvalidKey(e.to_string()))?;
+    Ok(move |body: &[u8]| {
+        let claims = serde_json::json!({ "bodyHash": format!("{:x}", Sha256::digest(body)) });
+        jsonwebtoken::encode(&Header::new(Algorithm::ES384), &claims, &key)
+            .expect("ES384 signing with a valid key")
+    })
+}
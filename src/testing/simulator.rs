@@ -0,0 +1,496 @@
+//! Drives fiat deposits and transactions through their lifecycle and fires the webhooks Noah
+//! would send.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+use super::server::{lock, MockNoah, Shared};
+use super::state::{now, MockState};
+use crate::apis::configuration::Configuration;
+use crate::apis::{payin_api, utilities_api};
+use crate::models;
+
+type Signer = Arc<dyn Fn(&[u8]) -> String + Send + Sync>;
+
+/// Moves fiat deposits from `Pending` to `Settled` or `Failed`, refunds them, and settles or
+/// fails transactions, delivering a [`models::Webhook`] for every change.
+///
+/// With [`Simulator::mock`] the changes are applied to the [`MockNoah`] state, including their
+/// effects: a settled deposit to a bank-deposit workflow creates the incoming `Transaction` and
+/// credits the balance, and settling or failing a sell consumes or releases the held funds.
+///
+/// With [`Simulator::sandbox`] deposits are created through
+/// [`payin_api::sandbox_fiat_deposit_simulate_post`] and transactions are read from the API, but
+/// the sandbox moves them on its own schedule. The simulator keeps a local copy and fires the
+/// webhooks for the transitions you ask for, so handlers can be exercised at chosen moments.
+///
+/// ```
+/// use noah_sdk::models::{FiatDepositSimulateRequest, FiatDepositStatus};
+/// use noah_sdk::testing::{MockNoah, Simulator};
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// # let noah = MockNoah::start().await?;
+/// # noah.state().payment_methods.push(noah_sdk::models::PaymentMethod {
+/// #     id: "pm-1".to_string(),
+/// #     ..Default::default()
+/// # });
+/// let simulator = Simulator::mock(&noah);
+/// let deposit = simulator
+///     .deposit(FiatDepositSimulateRequest::new("pm-1".into(), "100".into(), "EUR".into()))
+///     .await?;
+/// let settled = simulator.settle_deposit(&deposit.id).await?;
+/// assert_eq!(settled.status, FiatDepositStatus::Settled);
+/// assert_eq!(simulator.webhooks().len(), 2);
+/// # Ok(())
+/// # }
+/// ```
+pub struct Simulator {
+    configuration: Configuration,
+    backend: Backend,
+    client: reqwest::Client,
+    webhook_url: Option<String>,
+    signer: Option<Signer>,
+    user_id: String,
+    webhooks: Mutex<Vec<models::Webhook>>,
+}
+
+enum Backend {
+    Mock(Arc<Shared>),
+    Sandbox {
+        deposits: Mutex<HashMap<String, models::FiatDeposit>>,
+    },
+}
+
+/// Why a simulation step failed.
+#[derive(Debug)]
+pub enum SimulatorError {
+    /// The API call made by the simulator failed.
+    Api(Box<dyn std::error::Error + Send + Sync>),
+    UnknownFiatDeposit(String),
+    UnknownTransaction(String),
+    /// Only `Pending` deposits and transactions can be settled or failed.
+    InvalidTransition {
+        id: String,
+        from: String,
+        to: String,
+    },
+    /// The refund is larger than what is left of the deposit, or the deposit is not settled.
+    InvalidRefund {
+        id: String,
+        reason: String,
+    },
+    Webhook(reqwest::Error),
+    /// The webhook endpoint answered with a non-success status.
+    WebhookRejected {
+        status: reqwest::StatusCode,
+    },
+}
+
+impl fmt::Display for SimulatorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SimulatorError::Api(e) => write!(f, "API call failed: {e}"),
+            SimulatorError::UnknownFiatDeposit(id) => write!(f, "unknown fiat deposit {id}"),
+            SimulatorError::UnknownTransaction(id) => write!(f, "unknown transaction {id}"),
+            SimulatorError::InvalidTransition { id, from, to } => {
+                write!(f, "cannot move {id} from {from} to {to}")
+            }
+            SimulatorError::InvalidRefund { id, reason } => {
+                write!(f, "cannot refund {id}: {reason}")
+            }
+            SimulatorError::Webhook(e) => write!(f, "webhook delivery failed: {e}"),
+            SimulatorError::WebhookRejected { status } => {
+                write!(f, "webhook endpoint answered {status}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for SimulatorError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SimulatorError::Api(e) => Some(e.as_ref()),
+            SimulatorError::Webhook(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl Simulator {
+    /// A simulator acting on `noah`'s state.
+    pub fn mock(noah: &MockNoah) -> Simulator {
+        Simulator::new(noah.configuration(), Backend::Mock(noah.shared()))
+    }
+
+    /// A simulator for the Noah sandbox, or any server `configuration` points at.
+    pub fn sandbox(configuration: Configuration) -> Simulator {
+        let backend = Backend::Sandbox {
+            deposits: Mutex::new(HashMap::new()),
+        };
+        Simulator::new(configuration, backend)
+    }
+
+    fn new(configuration: Configuration, backend: Backend) -> Simulator {
+        Simulator {
+            configuration,
+            backend,
+            client: reqwest::Client::new(),
+            webhook_url: None,
+            signer: None,
+            user_id: "mock-user".to_owned(),
+            webhooks: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// POSTs every webhook to `url`, typically the handler under test. Without it, webhooks are
+    /// only recorded.
+    pub fn webhook_url(mut self, url: impl Into<String>) -> Simulator {
+        self.webhook_url = Some(url.into());
+        self
+    }
+
+    /// Computes the `Webhook-Signature` header from the request body.
+    pub fn webhook_signer(
+        mut self,
+        signer: impl Fn(&[u8]) -> String + Send + Sync + 'static,
+    ) -> Simulator {
+        self.signer = Some(Arc::new(signer));
+        self
+    }
+
+    /// Sets the `UserID` of the webhooks.
+    pub fn user_id(mut self, user_id: impl Into<String>) -> Simulator {
+        self.user_id = user_id.into();
+        self
+    }
+
+    /// The webhooks fired so far, oldest first.
+    pub fn webhooks(&self) -> Vec<models::Webhook> {
+        lock(&self.webhooks).clone()
+    }
+
+    /// Creates a `Pending` deposit to `request.payment_method_id` and fires its webhook.
+    pub async fn deposit(
+        &self,
+        request: models::FiatDepositSimulateRequest,
+    ) -> Result<models::FiatDeposit, SimulatorError> {
+        let response = payin_api::sandbox_fiat_deposit_simulate_post(
+            &self.configuration,
+            request.clone(),
+            None,
+        )
+        .await
+        .map_err(|e| SimulatorError::Api(Box::new(e)))?;
+        let deposit = match &self.backend {
+            Backend::Mock(shared) => lock(&shared.state)
+                .fiat_deposits
+                .iter()
+                .find(|deposit| deposit.id == response.fiat_deposit_id)
+                .cloned()
+                .ok_or_else(|| SimulatorError::UnknownFiatDeposit(response.fiat_deposit_id))?,
+            Backend::Sandbox { deposits } => {
+                let deposit = models::FiatDeposit {
+                    id: response.fiat_deposit_id,
+                    created: now(),
+                    fiat_amount: request.fiat_amount,
+                    fiat_currency: request.fiat_currency,
+                    status: models::FiatDepositStatus::Pending,
+                    payment_method_id: request.payment_method_id,
+                    ..Default::default()
+                };
+                lock(deposits).insert(deposit.id.clone(), deposit.clone());
+                deposit
+            }
+        };
+        self.fire_deposit(&deposit).await?;
+        Ok(deposit)
+    }
+
+    /// Settles a pending deposit. On the mock, a deposit to a bank-deposit workflow also creates
+    /// the settled incoming transaction, whose webhook follows the deposit's.
+    pub async fn settle_deposit(&self, id: &str) -> Result<models::FiatDeposit, SimulatorError> {
+        let (deposit, transaction) = self.with_deposit(id, |deposit, state| {
+            transition_deposit(deposit, models::FiatDepositStatus::Settled)?;
+            Ok(state.and_then(|state| credit_deposit(state, deposit)))
+        })?;
+        self.fire_deposit(&deposit).await?;
+        if let Some(transaction) = transaction {
+            self.fire_transaction(&transaction).await?;
+        }
+        Ok(deposit)
+    }
+
+    pub async fn fail_deposit(&self, id: &str) -> Result<models::FiatDeposit, SimulatorError> {
+        let (deposit, ()) = self.with_deposit(id, |deposit, _| {
+            transition_deposit(deposit, models::FiatDepositStatus::Failed)
+        })?;
+        self.fire_deposit(&deposit).await?;
+        Ok(deposit)
+    }
+
+    /// Refunds `amount` of a settled deposit to the sender. A failed deposit never arrived, so
+    /// there is nothing to refund.
+    pub async fn refund_deposit(
+        &self,
+        id: &str,
+        amount: &str,
+    ) -> Result<models::FiatPaymentRefund, SimulatorError> {
+        let (deposit, refund) = self.with_deposit(id, |deposit, _| refund(deposit, amount))?;
+        self.fire_deposit(&deposit).await?;
+        Ok(refund)
+    }
+
+    /// Settles a pending transaction, e.g. a sell, consuming its held funds on the mock.
+    pub async fn settle_transaction(
+        &self,
+        id: &str,
+    ) -> Result<models::Transaction, SimulatorError> {
+        self.finish_transaction(id, models::TransactionStatus::Settled)
+            .await
+    }
+
+    /// Fails a pending transaction, releasing its held funds on the mock.
+    pub async fn fail_transaction(&self, id: &str) -> Result<models::Transaction, SimulatorError> {
+        self.finish_transaction(id, models::TransactionStatus::Failed)
+            .await
+    }
+
+    async fn finish_transaction(
+        &self,
+        id: &str,
+        to: models::TransactionStatus,
+    ) -> Result<models::Transaction, SimulatorError> {
+        let transaction = match &self.backend {
+            Backend::Mock(shared) => finish_transaction(&mut lock(&shared.state), id, to)?,
+            Backend::Sandbox { .. } => {
+                let mut transaction =
+                    utilities_api::transactions_transaction_id_get(&self.configuration, id, None)
+                        .await
+                        .map_err(|e| SimulatorError::Api(Box::new(e)))?;
+                transition_transaction(&mut transaction, to)?;
+                transaction
+            }
+        };
+        self.fire_transaction(&transaction).await?;
+        Ok(transaction)
+    }
+
+    /// Runs `change` on the stored deposit and returns the updated copy. The mock state is
+    /// passed along on the mock backend.
+    fn with_deposit<R>(
+        &self,
+        id: &str,
+        change: impl FnOnce(
+            &mut models::FiatDeposit,
+            Option<&mut MockState>,
+        ) -> Result<R, SimulatorError>,
+    ) -> Result<(models::FiatDeposit, R), SimulatorError> {
+        let unknown = || SimulatorError::UnknownFiatDeposit(id.to_owned());
+        match &self.backend {
+            Backend::Mock(shared) => {
+                let mut state = lock(&shared.state);
+                let mut deposit = state.fiat_deposit_mut(id).ok_or_else(unknown)?.clone();
+                let result = change(&mut deposit, Some(&mut state))?;
+                *state.fiat_deposit_mut(id).ok_or_else(unknown)? = deposit.clone();
+                Ok((deposit, result))
+            }
+            Backend::Sandbox { deposits } => {
+                let mut deposits = lock(deposits);
+                let deposit = deposits.get_mut(id).ok_or_else(unknown)?;
+                let result = change(deposit, None)?;
+                Ok((deposit.clone(), result))
+            }
+        }
+    }
+
+    async fn fire_deposit(&self, deposit: &models::FiatDeposit) -> Result<(), SimulatorError> {
+        let data = models::WebhookData::FiatDeposit(Box::new(deposit.clone()));
+        self.fire("FiatDeposit", data).await
+    }
+
+    async fn fire_transaction(
+        &self,
+        transaction: &models::Transaction,
+    ) -> Result<(), SimulatorError> {
+        let data = models::WebhookData::Transaction(Box::new(transaction.clone()));
+        self.fire("Transaction", data).await
+    }
+
+    async fn fire(
+        &self,
+        event_type: &str,
+        data: models::WebhookData,
+    ) -> Result<(), SimulatorError> {
+        let webhook =
+            models::Webhook::new(event_type.to_owned(), 1, now(), data, self.user_id.clone());
+        lock(&self.webhooks).push(webhook.clone());
+        let Some(url) = &self.webhook_url else {
+            return Ok(());
+        };
+        let body = serde_json::to_vec(&webhook).expect("webhooks serialize to JSON");
+        let mut request = self
+            .client
+            .post(url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json");
+        if let Some(signer) = &self.signer {
+            request = request.header("Webhook-Signature", signer(&body));
+        }
+        let response = request
+            .body(body)
+            .send()
+            .await
+            .map_err(SimulatorError::Webhook)?;
+        if !response.status().is_success() {
+            return Err(SimulatorError::WebhookRejected {
+                status: response.status(),
+            });
+        }
+        Ok(())
+    }
+}
+
+fn transition_deposit(
+    deposit: &mut models::FiatDeposit,
+    to: models::FiatDepositStatus,
+) -> Result<(), SimulatorError> {
+    if deposit.status != models::FiatDepositStatus::Pending {
+        return Err(SimulatorError::InvalidTransition {
+            id: deposit.id.clone(),
+            from: deposit.status.to_string(),
+            to: to.to_string(),
+        });
+    }
+    deposit.status = to;
+    Ok(())
+}
+
+fn transition_transaction(
+    transaction: &mut models::Transaction,
+    to: models::TransactionStatus,
+) -> Result<(), SimulatorError> {
+    if transaction.status != models::TransactionStatus::Pending {
+        return Err(SimulatorError::InvalidTransition {
+            id: transaction.id.to_string(),
+            from: transaction.status.to_string(),
+            to: to.to_string(),
+        });
+    }
+    transaction.status = to;
+    Ok(())
+}
+
+fn refund(
+    deposit: &mut models::FiatDeposit,
+    amount: &str,
+) -> Result<models::FiatPaymentRefund, SimulatorError> {
+    let invalid = |reason: String| SimulatorError::InvalidRefund {
+        id: deposit.id.clone(),
+        reason,
+    };
+    if deposit.status != models::FiatDepositStatus::Settled {
+        return Err(invalid(format!("the deposit is {}", deposit.status)));
+    }
+    let requested = Decimal::from_str(amount)
+        .ok()
+        .filter(|amount| amount.is_sign_positive() && !amount.is_zero())
+        .ok_or_else(|| invalid(format!("{amount} is not a positive amount")))?;
+    let refunded: Decimal = deposit
+        .refunds
+        .iter()
+        .filter(|refund| refund.status != models::FiatPaymentStatus::Failed)
+        .filter_map(|refund| Decimal::from_str(&refund.refunded_amount.amount).ok())
+        .sum();
+    let deposited = Decimal::from_str(&deposit.fiat_amount).unwrap_or_default();
+    if refunded + requested > deposited {
+        return Err(invalid(format!(
+            "{requested} exceeds the {} left",
+            deposited - refunded
+        )));
+    }
+    let refund = models::FiatPaymentRefund {
+        refund_id: Uuid::new_v4(),
+        refunded_amount: Box::new(models::FiatAmount {
+            amount: requested.to_string(),
+            fiat_currency: deposit.fiat_currency.clone(),
+        }),
+        requested_time: now(),
+        status: models::FiatPaymentStatus::Successful,
+    };
+    deposit.refunds.push(refund.clone());
+    Ok(refund)
+}
+
+/// Converts a settled deposit to a bank-deposit workflow into crypto, as Noah does.
+fn credit_deposit(
+    state: &mut MockState,
+    deposit: &models::FiatDeposit,
+) -> Option<models::Transaction> {
+    let workflow = state
+        .bank_deposit_workflows
+        .get(&deposit.payment_method_id)?
+        .clone();
+    let rate = state.rate(&workflow.crypto_currency, &deposit.fiat_currency)?;
+    let amount = Decimal::from_str(&deposit.fiat_amount)
+        .ok()?
+        .checked_div(rate)?
+        .round_dp(8);
+    let transaction = models::Transaction {
+        id: Uuid::new_v4(),
+        network: workflow.network,
+        created: now(),
+        status: models::TransactionStatus::Settled,
+        direction: models::TransactionDirection::In,
+        customer_id: deposit.customer_id.clone(),
+        amount: Some(amount.to_string()),
+        crypto_currency: workflow.crypto_currency,
+        fiat_payment: Some(Box::new(models::FiatPayment {
+            amount: deposit.fiat_amount.clone(),
+            fee_amount: "0".to_owned(),
+            rate: Some(rate.normalize().to_string()),
+            fiat_currency: deposit.fiat_currency.clone(),
+            fiat_deposit_id: Some(deposit.id.clone()),
+            ..Default::default()
+        })),
+        ..Default::default()
+    };
+    state.adjust_balance(&transaction.crypto_currency, amount, amount);
+    state.transactions.push(transaction.clone());
+    Some(transaction)
+}
+
+fn finish_transaction(
+    state: &mut MockState,
+    id: &str,
+    to: models::TransactionStatus,
+) -> Result<models::Transaction, SimulatorError> {
+    let transaction = state
+        .transaction_mut(id)
+        .ok_or_else(|| SimulatorError::UnknownTransaction(id.to_owned()))?;
+    transition_transaction(transaction, to)?;
+    let transaction = transaction.clone();
+    let amount = transaction
+        .amount
+        .as_deref()
+        .and_then(|amount| Decimal::from_str(amount).ok())
+        .unwrap_or_default();
+    match (transaction.direction, to) {
+        // Outgoing funds were held when the transaction was created.
+        (models::TransactionDirection::Out, models::TransactionStatus::Settled) => {
+            state.adjust_balance(&transaction.crypto_currency, Decimal::ZERO, -amount)
+        }
+        (models::TransactionDirection::Out, _) => {
+            state.adjust_balance(&transaction.crypto_currency, amount, Decimal::ZERO)
+        }
+        (models::TransactionDirection::In, models::TransactionStatus::Settled) => {
+            state.adjust_balance(&transaction.crypto_currency, amount, amount)
+        }
+        (models::TransactionDirection::In, _) => {}
+    }
+    Ok(transaction)
+}
@@ -0,0 +1,915 @@
+//! State held by [`MockNoah`](super::MockNoah) and the endpoint behaviour built on it.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::str::FromStr;
+
+use bytes::Bytes;
+use http::StatusCode;
+use rust_decimal::Decimal;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use uuid::Uuid;
+
+use super::server::RecordedRequest;
+use crate::models;
+
+const DEFAULT_PAGE_SIZE: usize = 20;
+/// Fee charged on sells, as a fraction of the crypto amount.
+const SELL_FEE: Decimal = Decimal::from_parts(1, 0, 0, false, 2);
+
+/// Everything the mock server knows, in API model form.
+///
+/// Lists keep creation order; listing endpoints return the newest first unless the request
+/// asks for `SortDirection=Asc`. Tests may read and change any of it through
+/// [`MockNoah::state`](super::MockNoah::state).
+#[derive(Debug, Default)]
+pub struct MockState {
+    pub customers: Vec<models::Customer>,
+    pub balances: Vec<models::BalanceResponse>,
+    pub channels: Vec<models::Channel>,
+    pub payment_methods: Vec<models::PaymentMethod>,
+    pub transactions: Vec<models::Transaction>,
+    pub checkout_sessions: Vec<models::CheckoutSession>,
+    pub fiat_deposits: Vec<models::FiatDeposit>,
+    /// Conversion rates keyed by `(SourceCurrency, DestinationCurrency)`, used by `/prices`
+    /// and sell quotes. The inverse pair is derived when only one direction is present.
+    pub rates: BTreeMap<(String, String), String>,
+    /// Every request received, in order, including rejected ones.
+    pub requests: Vec<RecordedRequest>,
+    pub(crate) base_url: String,
+    pub(crate) prepared_sells: HashMap<Uuid, PreparedSell>,
+    pub(crate) bank_deposit_workflows: HashMap<String, models::BankDepositToOnchainAddressRequest>,
+    replays: HashMap<(&'static str, String), Reply>,
+}
+
+/// A quote from `/transactions/sell/prepare`, redeemed by `/transactions/sell`.
+#[derive(Clone, Debug)]
+pub(crate) struct PreparedSell {
+    pub customer_id: Option<String>,
+    pub crypto_currency: String,
+    pub fiat_currency: String,
+    pub fiat_amount: Decimal,
+    pub rate: String,
+    pub fee: Decimal,
+    pub crypto_amount: Decimal,
+}
+
+/// A request matched to its operation.
+pub(crate) struct Call {
+    pub operation: &'static str,
+    pub params: Vec<(&'static str, String)>,
+    pub query: Vec<(String, String)>,
+    pub body: Bytes,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct Reply {
+    pub status: StatusCode,
+    pub body: Option<serde_json::Value>,
+}
+
+impl Reply {
+    fn ok<T: Serialize>(value: &T) -> Reply {
+        Reply {
+            status: StatusCode::OK,
+            body: Some(serde_json::to_value(value).expect("models serialize to JSON")),
+        }
+    }
+
+    fn empty() -> Reply {
+        Reply {
+            status: StatusCode::OK,
+            body: None,
+        }
+    }
+
+    /// A [`models::Error`] body; `kind` is one of the documented error types.
+    pub(crate) fn error(status: StatusCode, kind: &str, detail: impl Into<String>) -> Reply {
+        let error = models::Error {
+            r#type: Some(kind.to_owned()),
+            instance: Some(Uuid::new_v4().to_string()),
+            detail: Some(detail.into()),
+            ..Default::default()
+        };
+        Reply {
+            status,
+            ..Reply::ok(&error)
+        }
+    }
+}
+
+fn invalid(detail: impl Into<String>) -> Reply {
+    Reply::error(StatusCode::BAD_REQUEST, "InvalidMessage", detail)
+}
+
+fn not_found(what: &str, id: &str) -> Reply {
+    Reply::error(
+        StatusCode::NOT_FOUND,
+        "ResourceNotFound",
+        format!("{what} {id} not found"),
+    )
+}
+
+impl Call {
+    fn param(&self, name: &str) -> &str {
+        self.params
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map_or("", |(_, value)| value)
+    }
+
+    fn query(&self, name: &str) -> Option<&str> {
+        self.query
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn required(&self, name: &str) -> Result<&str, Reply> {
+        self.query(name)
+            .ok_or_else(|| invalid(format!("missing query parameter {name}")))
+    }
+
+    fn json<T: DeserializeOwned>(&self) -> Result<T, Reply> {
+        serde_json::from_slice(&self.body).map_err(|e| invalid(format!("invalid body: {e}")))
+    }
+
+    /// Applies `PageSize`/`PageToken` to `items`, returning the page and the next token.
+    fn page<T: Clone>(&self, items: &[T]) -> Result<(Vec<T>, Option<String>), Reply> {
+        let size = match self.query("PageSize") {
+            Some(size) => size
+                .parse()
+                .ok()
+                .filter(|size| *size > 0)
+                .ok_or_else(|| invalid("PageSize must be a positive integer"))?,
+            None => DEFAULT_PAGE_SIZE,
+        };
+        let start: usize = match self.query("PageToken") {
+            Some(token) => token.parse().map_err(|_| invalid("invalid PageToken"))?,
+            None => 0,
+        };
+        let end = items.len().min(start.saturating_add(size));
+        let page = items.get(start..end).unwrap_or_default().to_vec();
+        let next = (end < items.len()).then(|| end.to_string());
+        Ok((page, next))
+    }
+
+    /// Orders `items` (oldest first) by the requested `SortDirection`, newest first by default.
+    fn sorted<T: Clone>(&self, items: &[T]) -> Vec<T> {
+        let mut items = items.to_vec();
+        let ascending = self
+            .query("SortDirection")
+            .is_some_and(|direction| direction.eq_ignore_ascii_case("Asc"));
+        if !ascending {
+            items.reverse();
+        }
+        items
+    }
+}
+
+fn decimal(field: &str, value: &str) -> Result<Decimal, Reply> {
+    Decimal::from_str(value).map_err(|_| invalid(format!("{field} is not a decimal: {value}")))
+}
+
+pub(crate) fn now() -> String {
+    timestamp(chrono::Utc::now())
+}
+
+fn timestamp(time: chrono::DateTime<chrono::Utc>) -> String {
+    time.to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
+}
+
+pub(crate) fn customer_id(customer: &models::Customer) -> &str {
+    match customer {
+        models::Customer::Individual(customer) => &customer.customer_id,
+        models::Customer::Business(customer) => &customer.customer_id,
+    }
+}
+
+fn payment_method_type(fiat_currency: &str) -> &'static str {
+    match fiat_currency {
+        "EUR" => "BankSepa",
+        "USD" => "BankFedwire",
+        _ => "BankLocal",
+    }
+}
+
+fn channel(
+    payment_method_type: &str,
+    fiat_currency: &str,
+    country: &str,
+    limits: (&str, &str),
+    rate: &str,
+    processing_seconds: i32,
+) -> models::Channel {
+    models::Channel {
+        id: Uuid::new_v4().to_string(),
+        payment_method_category: "Bank".to_owned(),
+        payment_method_type: payment_method_type.to_owned(),
+        fiat_currency: fiat_currency.to_owned(),
+        country: country.to_owned(),
+        limits: Box::new(models::ChannelLimits {
+            min_limit: limits.0.to_owned(),
+            max_limit: Some(limits.1.to_owned()),
+        }),
+        rate: rate.to_owned(),
+        processing_seconds,
+        ..Default::default()
+    }
+}
+
+impl MockState {
+    /// The state a fresh server starts with: a `USDC_TEST` balance, sell channels for EUR, USD
+    /// and GBP, and rates for those pairs.
+    pub(crate) fn seeded(base_url: String) -> MockState {
+        let rates = [
+            ("USDC_TEST", "EUR", "0.92"),
+            ("USDC_TEST", "USD", "1.00"),
+            ("USDC_TEST", "GBP", "0.79"),
+            ("BTC_TEST", "USD", "60000.00"),
+        ];
+        MockState {
+            balances: vec![models::BalanceResponse {
+                account_type: "Current".to_owned(),
+                crypto_currency: "USDC_TEST".to_owned(),
+                available: "1000.00".to_owned(),
+                total: "1000.00".to_owned(),
+            }],
+            channels: vec![
+                channel("BankSepa", "EUR", "DE", ("10", "10000"), "0.92", 86400),
+                channel("BankFedwire", "USD", "US", ("10", "50000"), "1.00", 3600),
+                channel("BankLocal", "GBP", "GB", ("10", "10000"), "0.79", 600),
+            ],
+            rates: rates
+                .iter()
+                .map(|(source, destination, rate)| {
+                    (
+                        (source.to_string(), destination.to_string()),
+                        rate.to_string(),
+                    )
+                })
+                .collect(),
+            base_url,
+            ..Default::default()
+        }
+    }
+
+    pub(crate) fn handle(&mut self, call: &Call) -> Reply {
+        let result = match call.operation {
+            "customers_customer_id_put" => self.put_customer(call),
+            "onboarding_customer_id_post" => self.hosted_onboarding(call),
+            "onboarding_customer_id_prefill_documents_upload_url_get" => self.upload_url(call),
+            "onboarding_customer_id_prefill_post" => self.prefill(call),
+            "checkout_payin_crypto_post" => self.checkout_payin_crypto(call),
+            "checkout_payin_fiat_post" => self.checkout_payin_fiat(call),
+            "checkout_payout_fiat_post" => self.checkout_payout_fiat(call),
+            "hosted_workflows_bank_deposit_to_onchain_address_post" => {
+                self.hosted_bank_deposit(call)
+            }
+            "sandbox_fiat_deposit_simulate_post" => self.simulate_fiat_deposit(call),
+            "workflows_bank_deposit_to_onchain_address_post" => self.bank_deposit(call),
+            "transactions_sell_post" => self.sell(call),
+            "transactions_sell_prepare_post" => self.prepare_sell(call),
+            "workflows_onchain_deposit_to_payment_method_post" => self.onchain_deposit(call),
+            "balances_get" => self.list_balances(call),
+            "channels_channel_id_form_get" => self.channel_form(call),
+            "channels_channel_id_get" => self.get_channel(call),
+            "channels_sell_countries_get" => Ok(self.sell_countries()),
+            "channels_sell_get" => self.list_channels(call),
+            "customers_customer_id_get" => self.get_customer(call),
+            "customers_get" => self.list_customers(call),
+            "payment_methods_get" => self.list_payment_methods(call),
+            "prices_get" => self.prices(call),
+            "transactions_get" => self.list_transactions(call),
+            "transactions_transaction_id_get" => self.get_transaction(call),
+            operation => unreachable!("no handler for {operation}"),
+        };
+        result.unwrap_or_else(|reply| reply)
+    }
+
+    pub fn customer(&self, customer_id: &str) -> Option<&models::Customer> {
+        self.customers
+            .iter()
+            .find(|customer| self::customer_id(customer) == customer_id)
+    }
+
+    pub fn transaction(&self, transaction_id: &str) -> Option<&models::Transaction> {
+        self.transactions
+            .iter()
+            .find(|transaction| transaction.id.to_string() == transaction_id)
+    }
+
+    pub fn transaction_mut(&mut self, transaction_id: &str) -> Option<&mut models::Transaction> {
+        self.transactions
+            .iter_mut()
+            .find(|transaction| transaction.id.to_string() == transaction_id)
+    }
+
+    pub fn fiat_deposit_mut(&mut self, fiat_deposit_id: &str) -> Option<&mut models::FiatDeposit> {
+        self.fiat_deposits
+            .iter_mut()
+            .find(|deposit| deposit.id == fiat_deposit_id)
+    }
+
+    /// Adds the deltas to the `crypto_currency` balance, opening it if needed.
+    pub(crate) fn adjust_balance(
+        &mut self,
+        crypto_currency: &str,
+        available: Decimal,
+        total: Decimal,
+    ) {
+        let index = match self
+            .balances
+            .iter()
+            .position(|balance| balance.crypto_currency == crypto_currency)
+        {
+            Some(index) => index,
+            None => {
+                self.balances.push(models::BalanceResponse {
+                    account_type: "Current".to_owned(),
+                    crypto_currency: crypto_currency.to_owned(),
+                    available: "0".to_owned(),
+                    total: "0".to_owned(),
+                });
+                self.balances.len() - 1
+            }
+        };
+        let balance = &mut self.balances[index];
+        let add = |amount: &str, delta: Decimal| {
+            (Decimal::from_str(amount).unwrap_or_default() + delta).to_string()
+        };
+        balance.available = add(&balance.available, available);
+        balance.total = add(&balance.total, total);
+    }
+
+    fn available(&self, crypto_currency: &str) -> Decimal {
+        self.balances
+            .iter()
+            .find(|balance| balance.crypto_currency == crypto_currency)
+            .and_then(|balance| Decimal::from_str(&balance.available).ok())
+            .unwrap_or_default()
+    }
+
+    fn channel(&self, channel_id: &str) -> Result<&models::Channel, Reply> {
+        self.channels
+            .iter()
+            .find(|channel| channel.id == channel_id)
+            .ok_or_else(|| not_found("channel", channel_id))
+    }
+
+    fn require_customer(&self, customer_id: &str) -> Result<(), Reply> {
+        match self.customer(customer_id) {
+            Some(_) => Ok(()),
+            None => Err(not_found("customer", customer_id)),
+        }
+    }
+
+    /// Converts `source` amounts to `destination`, trying the inverse pair when needed.
+    pub(crate) fn rate(&self, source: &str, destination: &str) -> Option<Decimal> {
+        if source == destination {
+            return Some(Decimal::ONE);
+        }
+        let key = |a: &str, b: &str| (a.to_owned(), b.to_owned());
+        if let Some(rate) = self.rates.get(&key(source, destination)) {
+            return Decimal::from_str(rate).ok();
+        }
+        let inverse = Decimal::from_str(self.rates.get(&key(destination, source))?).ok()?;
+        Decimal::ONE.checked_div(inverse)
+    }
+
+    /// Returns the stored reply for a repeated `Nonce`, so retried writes are not applied twice.
+    fn replay(&self, operation: &'static str, nonce: &str) -> Option<Reply> {
+        self.replays.get(&(operation, nonce.to_owned())).cloned()
+    }
+
+    fn remember(&mut self, operation: &'static str, nonce: String, reply: Reply) -> Reply {
+        self.replays.insert((operation, nonce), reply.clone());
+        reply
+    }
+
+    fn upsert_customer(&mut self, customer_id: &str, input: models::CustomerInput) {
+        let existing = self
+            .customers
+            .iter()
+            .position(|customer| self::customer_id(customer) == customer_id);
+        let created = match existing {
+            Some(index) => match &self.customers[index] {
+                models::Customer::Individual(customer) => customer.created.clone(),
+                models::Customer::Business(customer) => customer.created.clone(),
+            },
+            None => now(),
+        };
+        let verification = Box::new(models::Verification {
+            model: "Reliance".to_owned(),
+            status: models::verification::Status::Approved,
+        });
+        let customer = match input {
+            models::CustomerInput::Individual(input) => {
+                models::Customer::Individual(Box::new(models::IndividualCustomer {
+                    customer_id: customer_id.to_owned(),
+                    created,
+                    date_of_birth: input.date_of_birth,
+                    full_name: input.full_name,
+                    identities: input.identities,
+                    primary_residence: input.primary_residence,
+                    verification,
+                    ..Default::default()
+                }))
+            }
+            models::CustomerInput::Business(input) => {
+                models::Customer::Business(Box::new(models::BusinessCustomer {
+                    customer_id: customer_id.to_owned(),
+                    created,
+                    registered_name: input.registered_name,
+                    email: input.email,
+                    registration_number: Some(input.registration_number),
+                    registration_country: Some(input.registration_country),
+                    registered_address: Some(input.registered_address),
+                    incorporation_date: Some(input.incorporation_date),
+                    verification,
+                    ..Default::default()
+                }))
+            }
+        };
+        match existing {
+            Some(index) => self.customers[index] = customer,
+            None => self.customers.push(customer),
+        }
+    }
+
+    fn put_customer(&mut self, call: &Call) -> Result<Reply, Reply> {
+        let input: models::CustomerInput = call.json()?;
+        self.upsert_customer(call.param("CustomerID"), input);
+        Ok(Reply::empty())
+    }
+
+    fn hosted_onboarding(&mut self, call: &Call) -> Result<Reply, Reply> {
+        let _: models::HostedOnboardingRequest = call.json()?;
+        Ok(Reply::ok(&models::HostedSessionResponse {
+            hosted_url: format!(
+                "{}/hosted/onboarding/{}",
+                self.base_url,
+                call.param("CustomerID")
+            ),
+            form_schema: None,
+        }))
+    }
+
+    fn upload_url(&mut self, call: &Call) -> Result<Reply, Reply> {
+        call.required("Type")?;
+        call.required("CountryCode")?;
+        let expires_at = chrono::Utc::now() + chrono::Duration::minutes(15);
+        Ok(Reply::ok(&models::PrefillDocumentUploadUrlResponse {
+            presigned_url: format!("{}/uploads/{}", self.base_url, Uuid::new_v4()),
+            expires_at: timestamp(expires_at),
+        }))
+    }
+
+    fn prefill(&mut self, call: &Call) -> Result<Reply, Reply> {
+        let _: models::PrefillOnboardingRequest = call.json()?;
+        Ok(Reply::empty())
+    }
+
+    fn open_checkout(
+        &mut self,
+        operation: &'static str,
+        nonce: String,
+        customer: Option<Box<models::CustomerInput>>,
+        mut session: models::CheckoutSession,
+    ) -> Reply {
+        if let Some(reply) = self.replay(operation, &nonce) {
+            return reply;
+        }
+        if let Some(customer) = customer {
+            self.upsert_customer(&session.customer_id.clone(), *customer);
+        }
+        let expiry = chrono::Utc::now() + chrono::Duration::hours(1);
+        session.checkout_session_id = Uuid::new_v4().to_string();
+        session.status = "Pending".to_owned();
+        session.created = now();
+        session.expiry = Some(timestamp(expiry));
+        let reply = Reply::ok(&models::CheckoutSessionResponse {
+            hosted_url: format!(
+                "{}/hosted/checkout/{}",
+                self.base_url, session.checkout_session_id
+            ),
+            checkout_session: Box::new(session.clone()),
+        });
+        self.checkout_sessions.push(session);
+        self.remember(operation, nonce, reply)
+    }
+
+    fn checkout_payin_crypto(&mut self, call: &Call) -> Result<Reply, Reply> {
+        let request: models::CheckoutPayinCryptoPostRequest = call.json()?;
+        decimal("CryptoAmount", &request.crypto_amount)?;
+        let session = models::CheckoutSession {
+            source_currency: request.crypto_currency.clone(),
+            destination_currency: request.crypto_currency,
+            source_amount: Some(request.crypto_amount),
+            external_id: request.external_id,
+            customer_id: request.customer_id,
+            return_url: request.return_url,
+            line_items: request.line_items,
+            r#type: "PayinCrypto".to_owned(),
+            ..Default::default()
+        };
+        Ok(self.open_checkout(call.operation, request.nonce, request.customer, session))
+    }
+
+    fn checkout_payin_fiat(&mut self, call: &Call) -> Result<Reply, Reply> {
+        let request: models::CheckoutPayinFiatPostRequest = call.json()?;
+        decimal("FiatAmount", &request.fiat_amount)?;
+        let session = models::CheckoutSession {
+            payment_method_category: Some(request.payment_method_category),
+            source_currency: request.fiat_currency,
+            destination_currency: request.crypto_currency,
+            source_amount: Some(request.fiat_amount),
+            external_id: request.external_id,
+            customer_id: request.customer_id,
+            return_url: request.return_url,
+            line_items: request.line_items,
+            r#type: "PayinFiat".to_owned(),
+            ..Default::default()
+        };
+        Ok(self.open_checkout(call.operation, request.nonce, request.customer, session))
+    }
+
+    fn checkout_payout_fiat(&mut self, call: &Call) -> Result<Reply, Reply> {
+        let request: models::CheckoutPayoutFiatPostRequest = call.json()?;
+        decimal("FiatAmount", &request.fiat_amount)?;
+        decimal("CryptoAuthorizedAmount", &request.crypto_authorized_amount)?;
+        let session = models::CheckoutSession {
+            source_currency: request.crypto_currency,
+            destination_currency: request.fiat_currency,
+            destination_amount: Some(request.fiat_amount),
+            authorized_amount: Some(request.crypto_authorized_amount),
+            external_id: request.external_id,
+            customer_id: request.customer_id,
+            return_url: request.return_url,
+            line_items: request.line_items,
+            r#type: "PayoutFiat".to_owned(),
+            ..Default::default()
+        };
+        Ok(self.open_checkout(call.operation, request.nonce, request.customer, session))
+    }
+
+    fn hosted_bank_deposit(&mut self, call: &Call) -> Result<Reply, Reply> {
+        let request: models::BankDepositToOnchainAddressHostedRequest = call.json()?;
+        self.require_customer(&request.customer_id)?;
+        Ok(Reply::ok(&models::HostedSessionResponse {
+            hosted_url: format!("{}/hosted/bank-deposit/{}", self.base_url, Uuid::new_v4()),
+            form_schema: None,
+        }))
+    }
+
+    fn bank_deposit(&mut self, call: &Call) -> Result<Reply, Reply> {
+        let request: models::BankDepositToOnchainAddressRequest = call.json()?;
+        self.require_customer(&request.customer_id)?;
+        let id = Uuid::new_v4();
+        let account_number = format!("{:010}", id.as_u128() % 10_000_000_000);
+        let bank_code = "MOCKBANK".to_owned();
+        let country = match request.fiat_currency.as_str() {
+            "EUR" => "DE",
+            "USD" => "US",
+            "GBP" => "GB",
+            _ => "ZZ",
+        };
+        self.payment_methods.push(models::PaymentMethod {
+            id: id.to_string(),
+            customer_id: Some(request.customer_id.clone()),
+            country: country.to_owned(),
+            payment_method_category: "Bank".to_owned(),
+            display_details: Box::new(
+                models::PaymentMethodDisplayDetails::FiatPaymentMethodBankDisplay(Box::new(
+                    models::FiatPaymentMethodBankDisplay {
+                        account_number: Some(account_number.clone()),
+                        bank_code: Some(bank_code.clone()),
+                        ..Default::default()
+                    },
+                )),
+            ),
+        });
+        let response = models::BankDepositToOnchainAddressResponse {
+            payment_method_id: id.to_string(),
+            payment_method_type: payment_method_type(&request.fiat_currency).to_owned(),
+            reference: Some(format!("NOAH-{}", &id.simple().to_string()[..8])),
+            account_number,
+            account_holder_name: Some("Noah Mock".to_owned()),
+            bank_code: Some(bank_code),
+            bank_name: Some("Mock Bank".to_owned()),
+            ..Default::default()
+        };
+        self.bank_deposit_workflows.insert(id.to_string(), request);
+        Ok(Reply::ok(&response))
+    }
+
+    fn simulate_fiat_deposit(&mut self, call: &Call) -> Result<Reply, Reply> {
+        let request: models::FiatDepositSimulateRequest = call.json()?;
+        decimal("FiatAmount", &request.fiat_amount)?;
+        let method = self
+            .payment_methods
+            .iter()
+            .find(|method| method.id == request.payment_method_id)
+            .ok_or_else(|| not_found("payment method", &request.payment_method_id))?;
+        let deposit = models::FiatDeposit {
+            id: Uuid::new_v4().to_string(),
+            created: now(),
+            fiat_amount: request.fiat_amount,
+            reference: Some(format!("SIM-{}", self.fiat_deposits.len() + 1)),
+            status: models::FiatDepositStatus::Pending,
+            customer_id: method.customer_id.clone(),
+            payment_method_id: request.payment_method_id,
+            payment_method_type: payment_method_type(&request.fiat_currency).to_owned(),
+            fiat_currency: request.fiat_currency,
+            sender: Box::new(models::SenderPaymentMethod {
+                full_name: Some("Sandbox Sender".to_owned()),
+                details: Default::default(),
+            }),
+            ..Default::default()
+        };
+        let response = models::FiatDepositSimulateResponse {
+            fiat_deposit_id: deposit.id.clone(),
+        };
+        self.fiat_deposits.push(deposit);
+        Ok(Reply::ok(&response))
+    }
+
+    fn prepare_sell(&mut self, call: &Call) -> Result<Reply, Reply> {
+        let request: models::PrepareSellRequest = call.json()?;
+        if let Some(customer_id) = &request.customer_id {
+            self.require_customer(customer_id)?;
+        }
+        let channel = self.channel(&request.channel_id.to_string())?;
+        let fiat_amount = decimal("FiatAmount", &request.fiat_amount)?;
+        let min = decimal("MinLimit", &channel.limits.min_limit)?;
+        let max = match &channel.limits.max_limit {
+            Some(max) => Some(decimal("MaxLimit", max)?),
+            None => None,
+        };
+        if fiat_amount < min || max.is_some_and(|max| fiat_amount > max) {
+            return Err(invalid(format!(
+                "FiatAmount {fiat_amount} is outside the channel limits"
+            )));
+        }
+        let rate = decimal("Rate", &channel.rate)?;
+        let estimate = fiat_amount
+            .checked_div(rate)
+            .ok_or_else(|| invalid("channel has no rate"))?
+            .round_dp(8);
+        let fee = (estimate * SELL_FEE).round_dp(8);
+        let prepared = PreparedSell {
+            customer_id: request.customer_id,
+            crypto_currency: request.crypto_currency,
+            fiat_currency: channel.fiat_currency.clone(),
+            fiat_amount,
+            rate: channel.rate.clone(),
+            fee,
+            crypto_amount: estimate + fee,
+        };
+        let form_session_id = Uuid::new_v4();
+        let response = models::PrepareSellResponse {
+            total_fee: fee.to_string(),
+            crypto_amount_estimate: estimate.to_string(),
+            crypto_authorized_amount: prepared.crypto_amount.to_string(),
+            form_session_id,
+        };
+        self.prepared_sells.insert(form_session_id, prepared);
+        Ok(Reply::ok(&response))
+    }
+
+    fn sell(&mut self, call: &Call) -> Result<Reply, Reply> {
+        let request: models::SellRequest = call.json()?;
+        if let Some(reply) = self.replay(call.operation, &request.nonce) {
+            return Ok(reply);
+        }
+        let prepared = self
+            .prepared_sells
+            .get(&request.form_session_id)
+            .cloned()
+            .ok_or_else(|| invalid(format!("unknown FormSessionID {}", request.form_session_id)))?;
+        if request.crypto_currency != prepared.crypto_currency {
+            return Err(invalid("CryptoCurrency does not match the prepared sell"));
+        }
+        if decimal("FiatAmount", &request.fiat_amount)? != prepared.fiat_amount {
+            return Err(invalid("FiatAmount does not match the prepared sell"));
+        }
+        let authorized = decimal("CryptoAuthorizedAmount", &request.crypto_authorized_amount)?;
+        if authorized < prepared.crypto_amount {
+            return Err(invalid(format!(
+                "CryptoAuthorizedAmount {authorized} is below the required {}",
+                prepared.crypto_amount
+            )));
+        }
+        if self.available(&prepared.crypto_currency) < prepared.crypto_amount {
+            return Err(Reply::error(
+                StatusCode::BAD_REQUEST,
+                "InsufficientBalance",
+                format!("insufficient {} balance", prepared.crypto_currency),
+            ));
+        }
+        // Funds are held until the transaction settles or fails.
+        self.adjust_balance(
+            &prepared.crypto_currency,
+            -prepared.crypto_amount,
+            Decimal::ZERO,
+        );
+        self.prepared_sells.remove(&request.form_session_id);
+
+        let transaction = models::Transaction {
+            id: Uuid::new_v4(),
+            network: "OffNetwork".to_owned(),
+            created: now(),
+            status: models::TransactionStatus::Pending,
+            direction: models::TransactionDirection::Out,
+            customer_id: prepared.customer_id,
+            external_id: request.external_id,
+            amount: Some(prepared.crypto_amount.to_string()),
+            crypto_currency: prepared.crypto_currency,
+            fiat_payment: Some(Box::new(models::FiatPayment {
+                amount: prepared.fiat_amount.to_string(),
+                fee_amount: prepared.fee.to_string(),
+                rate: Some(prepared.rate),
+                fiat_currency: prepared.fiat_currency,
+                ..Default::default()
+            })),
+            ..Default::default()
+        };
+        self.transactions.push(transaction.clone());
+        let reply = Reply::ok(&models::SellResponse {
+            transaction: Box::new(transaction),
+        });
+        Ok(self.remember(call.operation, request.nonce, reply))
+    }
+
+    fn onchain_deposit(&mut self, call: &Call) -> Result<Reply, Reply> {
+        let request: models::OnchainDepositToPaymentMethodRequest = call.json()?;
+        self.require_customer(&request.customer_id)?;
+        if !self.prepared_sells.contains_key(&request.form_session_id) {
+            return Err(invalid(format!(
+                "unknown FormSessionID {}",
+                request.form_session_id
+            )));
+        }
+        let conditions = request
+            .trigger
+            .conditions
+            .iter()
+            .map(|condition| models::DepositSourceTriggerCondition {
+                amount_conditions: condition.amount_conditions.clone(),
+                crypto_currency: request.crypto_currency.clone(),
+                network: condition.network.clone(),
+                destination_address: Box::new(models::DestinationAddress {
+                    address: format!("mock-{}", Uuid::new_v4().simple()),
+                }),
+            })
+            .collect();
+        Ok(Reply::ok(&models::OnchainDepositToPaymentMethodResponse {
+            conditions,
+            source_address: Some(request.trigger.source_address),
+        }))
+    }
+
+    fn list_balances(&mut self, call: &Call) -> Result<Reply, Reply> {
+        let (items, page_token) = call.page(&self.balances)?;
+        Ok(Reply::ok(&models::GetBalancesResponse {
+            items,
+            page_token,
+        }))
+    }
+
+    fn channel_form(&mut self, call: &Call) -> Result<Reply, Reply> {
+        let channel = self.channel(call.param("ChannelID"))?;
+        Ok(Reply::ok(&models::GetFormResponse {
+            form_schema: channel.form_schema.clone(),
+        }))
+    }
+
+    fn get_channel(&mut self, call: &Call) -> Result<Reply, Reply> {
+        call.required("CryptoCurrency")?;
+        Ok(Reply::ok(self.channel(call.param("ChannelID"))?))
+    }
+
+    fn sell_countries(&self) -> Reply {
+        let mut countries: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+        for channel in &self.channels {
+            countries
+                .entry(&channel.country)
+                .or_default()
+                .insert(&channel.fiat_currency);
+        }
+        Reply::ok(&countries)
+    }
+
+    fn list_channels(&mut self, call: &Call) -> Result<Reply, Reply> {
+        call.required("CryptoCurrency")?;
+        let fiat_amount = match call.query("FiatAmount") {
+            Some(amount) => Some(decimal("FiatAmount", amount)?),
+            None => None,
+        };
+        let within_limits = |channel: &models::Channel| {
+            let Some(amount) = fiat_amount else {
+                return true;
+            };
+            let min = Decimal::from_str(&channel.limits.min_limit).unwrap_or_default();
+            let max = channel
+                .limits
+                .max_limit
+                .as_deref()
+                .and_then(|max| Decimal::from_str(max).ok());
+            amount >= min && max.is_none_or(|max| amount <= max)
+        };
+        let channels: Vec<_> = self
+            .channels
+            .iter()
+            .filter(|channel| call.query("Country").is_none_or(|c| c == channel.country))
+            .filter(|channel| {
+                call.query("FiatCurrency")
+                    .is_none_or(|c| c == channel.fiat_currency)
+            })
+            .filter(|channel| within_limits(channel))
+            .cloned()
+            .collect();
+        let (items, page_token) = call.page(&channels)?;
+        Ok(Reply::ok(&models::GetChannelsResponse {
+            items,
+            page_token,
+        }))
+    }
+
+    fn get_customer(&mut self, call: &Call) -> Result<Reply, Reply> {
+        let customer_id = call.param("CustomerID");
+        let customer = self
+            .customer(customer_id)
+            .ok_or_else(|| not_found("customer", customer_id))?;
+        Ok(Reply::ok(customer))
+    }
+
+    fn list_customers(&mut self, call: &Call) -> Result<Reply, Reply> {
+        let (items, page_token) = call.page(&call.sorted(&self.customers))?;
+        Ok(Reply::ok(&models::GetCustomersResponse {
+            items,
+            page_token,
+        }))
+    }
+
+    fn list_payment_methods(&mut self, call: &Call) -> Result<Reply, Reply> {
+        let customer_id = call.required("CustomerID")?;
+        let methods: Vec<_> = self
+            .payment_methods
+            .iter()
+            .filter(|method| method.customer_id.as_deref() == Some(customer_id))
+            .cloned()
+            .collect();
+        let (items, page_token) = call.page(&methods)?;
+        Ok(Reply::ok(&models::GetPaymentMethodsResponse {
+            items,
+            page_token,
+        }))
+    }
+
+    fn prices(&mut self, call: &Call) -> Result<Reply, Reply> {
+        let source = call.required("SourceCurrency")?;
+        let destination = call.required("DestinationCurrency")?;
+        let rate = self
+            .rate(source, destination)
+            .ok_or_else(|| not_found("price", &format!("{source}/{destination}")))?;
+        let mut item = models::PriceItem {
+            payment_method_category: call
+                .query("PaymentMethodCategory")
+                .unwrap_or("Bank")
+                .to_owned(),
+            rate: rate.round_dp(8).normalize().to_string(),
+            updated_at: now(),
+            ..Default::default()
+        };
+        if let Some(amount) = call.query("SourceAmount") {
+            let amount = decimal("SourceAmount", amount)?;
+            item.source_amount = Some(amount.to_string());
+            item.destination_amount = Some((amount * rate).round_dp(8).to_string());
+        } else if let Some(amount) = call.query("DestinationAmount") {
+            let amount = decimal("DestinationAmount", amount)?;
+            let source_amount = amount
+                .checked_div(rate)
+                .ok_or_else(|| invalid("price has no rate"))?;
+            item.source_amount = Some(source_amount.round_dp(8).to_string());
+            item.destination_amount = Some(amount.to_string());
+        }
+        Ok(Reply::ok(&models::GetPricesResponse { items: vec![item] }))
+    }
+
+    fn list_transactions(&mut self, call: &Call) -> Result<Reply, Reply> {
+        let (items, page_token) = call.page(&call.sorted(&self.transactions))?;
+        Ok(Reply::ok(&models::GetTransactionsResponse {
+            items,
+            page_token,
+        }))
+    }
+
+    fn get_transaction(&mut self, call: &Call) -> Result<Reply, Reply> {
+        let transaction_id = call.param("TransactionID");
+        let transaction = self
+            .transaction(transaction_id)
+            .ok_or_else(|| not_found("transaction", transaction_id))?;
+        Ok(Reply::ok(transaction))
+    }
+}
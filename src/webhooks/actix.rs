@@ -0,0 +1,76 @@
+//! The actix-web extractor.
+
+use std::future::Future;
+use std::pin::Pin;
+
+use actix_web::body::BoxBody;
+use actix_web::dev::Payload;
+use actix_web::http::StatusCode;
+use actix_web::web::{Bytes, Data};
+use actix_web::{FromRequest, HttpRequest, HttpResponse, Responder, ResponseError};
+use serde::de::DeserializeOwned;
+
+use super::{NoahWebhook, Outcome, WebhookError, WebhookVerifier, SIGNATURE_HEADER};
+
+/// Registered with `App::app_data`, either as is or wrapped in `web::Data`.
+fn verifier(req: &HttpRequest) -> Option<WebhookVerifier> {
+    req.app_data::<WebhookVerifier>()
+        .or_else(|| {
+            req.app_data::<Data<WebhookVerifier>>()
+                .map(|data| data.get_ref())
+        })
+        .cloned()
+}
+
+impl<T> FromRequest for NoahWebhook<T>
+where
+    T: DeserializeOwned + 'static,
+{
+    type Error = WebhookError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let verifier = verifier(req);
+        // actix-web is on http 0.2, so its headers can't go through `super::signature`.
+        let signature = req
+            .headers()
+            .get(SIGNATURE_HEADER)
+            .map(|value| {
+                value
+                    .to_str()
+                    .map(str::to_owned)
+                    .map_err(|e| WebhookError::InvalidSignature(e.to_string()))
+            })
+            .transpose();
+        let body = Bytes::from_request(req, payload);
+        Box::pin(async move {
+            let verifier = verifier.ok_or(WebhookError::MissingVerifier)?;
+            let signature = signature?;
+            let body = body.await.map_err(|e| WebhookError::Body(e.to_string()))?;
+            verifier.parse(&body, signature.as_deref()).map(NoahWebhook)
+        })
+    }
+}
+
+/// Converts from the `http` 1.x status the rest of the crate uses.
+fn status(status: http::StatusCode) -> StatusCode {
+    StatusCode::from_u16(status.as_u16()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+impl ResponseError for WebhookError {
+    fn status_code(&self) -> StatusCode {
+        status(self.status())
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        HttpResponse::build(self.status_code()).body(self.to_string())
+    }
+}
+
+impl Responder for Outcome {
+    type Body = BoxBody;
+
+    fn respond_to(self, _: &HttpRequest) -> HttpResponse {
+        HttpResponse::build(status(self.status())).body(self.to_string())
+    }
+}
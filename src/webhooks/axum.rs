@@ -0,0 +1,38 @@
+//! The axum extractor.
+
+use axum::body::Bytes;
+use axum::extract::{FromRef, FromRequest, Request};
+use axum::response::{IntoResponse, Response};
+use serde::de::DeserializeOwned;
+
+use super::{signature, NoahWebhook, Outcome, WebhookError, WebhookVerifier};
+
+impl<S, T> FromRequest<S> for NoahWebhook<T>
+where
+    S: Send + Sync,
+    T: DeserializeOwned,
+    WebhookVerifier: FromRef<S>,
+{
+    type Rejection = WebhookError;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let verifier = WebhookVerifier::from_ref(state);
+        let signature = signature(req.headers())?.map(str::to_owned);
+        let body = Bytes::from_request(req, state)
+            .await
+            .map_err(|e| WebhookError::Body(e.body_text()))?;
+        verifier.parse(&body, signature.as_deref()).map(NoahWebhook)
+    }
+}
+
+impl IntoResponse for WebhookError {
+    fn into_response(self) -> Response {
+        (self.status(), self.to_string()).into_response()
+    }
+}
+
+impl IntoResponse for Outcome {
+    fn into_response(self) -> Response {
+        (self.status(), self.to_string()).into_response()
+    }
+}
@@ -0,0 +1,272 @@
+//! Recognizing redelivered events.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use sha2::{Digest, Sha256};
+
+use super::Event;
+use crate::idempotency::sync_dir;
+
+/// Identifies one event across deliveries.
+///
+/// Webhooks carry no delivery ID, so the key is made of the `EventType`, the ID of the entity the
+/// event is about, `Occurred`, and a SHA-256 of the whole event. Two deliveries of the same event
+/// get the same key, while two updates to the same entity that happen to share a timestamp don't.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct EventKey(String);
+
+impl EventKey {
+    pub fn of(event: &Event) -> EventKey {
+        let payload = serde_json::to_vec(event).expect("events serialize to JSON");
+        EventKey(format!(
+            "{}:{}:{}:{:x}",
+            event.event_type(),
+            event.entity_id(),
+            event.occurred,
+            Sha256::digest(payload)
+        ))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for EventKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Remembers which events have been handled.
+///
+/// Implement it over a database table with a unique key to share deduplication between
+/// instances.
+pub trait DedupStore: Send + Sync {
+    /// Records `key`, returning `false` if it was already recorded.
+    fn insert(&self, key: &EventKey) -> io::Result<bool>;
+
+    /// Forgets `key`, so that a redelivery of an event whose handler failed runs it again.
+    fn remove(&self, key: &EventKey) -> io::Result<()>;
+}
+
+/// Keeps the most recently seen keys in memory, up to a capacity.
+///
+/// Keys are lost on restart, and an event redelivered after `capacity` newer ones is handled
+/// again.
+#[derive(Debug)]
+pub struct MemoryStore {
+    capacity: usize,
+    lru: Mutex<Lru>,
+}
+
+#[derive(Debug, Default)]
+struct Lru {
+    tick: u64,
+    keys: HashMap<EventKey, u64>,
+    order: BTreeMap<u64, EventKey>,
+}
+
+impl MemoryStore {
+    pub fn new(capacity: usize) -> MemoryStore {
+        MemoryStore {
+            capacity: capacity.max(1),
+            lru: Mutex::default(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        // Only reads, so a lock poisoned by a panic elsewhere still gives a count.
+        let lru = self.lru.lock().unwrap_or_else(PoisonError::into_inner);
+        lru.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl DedupStore for MemoryStore {
+    fn insert(&self, key: &EventKey) -> io::Result<bool> {
+        let mut lru = lock(&self.lru)?;
+        lru.tick += 1;
+        let tick = lru.tick;
+        if let Some(seen) = lru.keys.insert(key.clone(), tick) {
+            lru.order.remove(&seen);
+            lru.order.insert(tick, key.clone());
+            return Ok(false);
+        }
+        lru.order.insert(tick, key.clone());
+        if lru.keys.len() > self.capacity {
+            if let Some((_, oldest)) = lru.order.pop_first() {
+                lru.keys.remove(&oldest);
+            }
+        }
+        Ok(true)
+    }
+
+    fn remove(&self, key: &EventKey) -> io::Result<()> {
+        let mut lru = lock(&self.lru)?;
+        if let Some(tick) = lru.keys.remove(key) {
+            lru.order.remove(&tick);
+        }
+        Ok(())
+    }
+}
+
+/// Keeps keys in a file, one per line with the time it was recorded, so they survive restarts.
+///
+/// The file is only appended to, except when a key is removed or the file is compacted, and is
+/// meant for a single process. With a [`ttl`](FileStore::ttl), keys older than it count as unseen
+/// and are dropped from the file now and then, so it doesn't grow forever.
+#[derive(Debug)]
+pub struct FileStore {
+    path: PathBuf,
+    ttl: Option<Duration>,
+    state: Mutex<FileState>,
+}
+
+#[derive(Debug)]
+struct FileState {
+    /// Each key with when it was recorded, in milliseconds since the Unix epoch.
+    keys: HashMap<EventKey, u64>,
+    file: File,
+    next_compaction: u64,
+}
+
+impl FileStore {
+    /// Opens the store at `path`, creating the file if needed.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<FileStore> {
+        let path = path.as_ref().to_owned();
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let now = unix_millis();
+        let mut keys = HashMap::new();
+        for line in BufReader::new(File::open(&path)?).lines() {
+            let line = line?;
+            // Files written before keys had times hold the bare key.
+            let (recorded, key) = match line.split_once(' ') {
+                Some((recorded, key)) => match recorded.parse() {
+                    Ok(recorded) => (recorded, key),
+                    Err(_) => (now, line.as_str()),
+                },
+                None => (now, line.as_str()),
+            };
+            if !key.is_empty() {
+                keys.insert(EventKey(key.to_owned()), recorded);
+            }
+        }
+        Ok(FileStore {
+            path,
+            ttl: None,
+            state: Mutex::new(FileState {
+                keys,
+                file,
+                next_compaction: 0,
+            }),
+        })
+    }
+
+    /// Forgets keys `ttl` after they were recorded. Set it longer than Noah keeps redelivering.
+    pub fn ttl(mut self, ttl: Duration) -> FileStore {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Drops expired keys from the file, returning how many. Inserting does this on its own at
+    /// most every half `ttl`.
+    pub fn compact(&self) -> io::Result<usize> {
+        let mut state = lock(&self.state)?;
+        self.compact_locked(&mut state, unix_millis())
+    }
+
+    fn compact_locked(&self, state: &mut FileState, now: u64) -> io::Result<usize> {
+        let Some(ttl) = self.ttl else {
+            return Ok(0);
+        };
+        let before = state.keys.len();
+        state
+            .keys
+            .retain(|_, recorded| !expired(*recorded, ttl, now));
+        let dropped = before - state.keys.len();
+        if dropped > 0 {
+            self.rewrite(state)?;
+        }
+        state.next_compaction = now.saturating_add(millis(ttl / 2).max(1));
+        Ok(dropped)
+    }
+
+    /// Replaces the file with the keys in `state`. They are written aside, synced and renamed over
+    /// the file, and the directory is synced after the rename, so a crash leaves either version.
+    fn rewrite(&self, state: &mut FileState) -> io::Result<()> {
+        let mut contents = String::new();
+        for (key, recorded) in &state.keys {
+            contents.push_str(&format!("{recorded} {key}\n"));
+        }
+        let mut temporary = self.path.clone().into_os_string();
+        temporary.push(".tmp");
+        let mut file = File::create(&temporary)?;
+        file.write_all(contents.as_bytes())?;
+        file.sync_all()?;
+        drop(file);
+        fs::rename(&temporary, &self.path)?;
+        sync_dir(&self.path)?;
+        state.file = OpenOptions::new().append(true).open(&self.path)?;
+        Ok(())
+    }
+}
+
+impl DedupStore for FileStore {
+    fn insert(&self, key: &EventKey) -> io::Result<bool> {
+        let now = unix_millis();
+        let mut state = lock(&self.state)?;
+        if self.ttl.is_some() && now >= state.next_compaction {
+            self.compact_locked(&mut state, now)?;
+        }
+        if let Some(&recorded) = state.keys.get(key) {
+            if self.ttl.is_none_or(|ttl| !expired(recorded, ttl, now)) {
+                return Ok(false);
+            }
+        }
+        // A later line for the same key wins when the file is read again.
+        writeln!(state.file, "{now} {key}")?;
+        state.file.sync_data()?;
+        state.keys.insert(key.clone(), now);
+        Ok(true)
+    }
+
+    fn remove(&self, key: &EventKey) -> io::Result<()> {
+        let mut state = lock(&self.state)?;
+        if state.keys.remove(key).is_none() {
+            return Ok(());
+        }
+        self.rewrite(&mut state)
+    }
+}
+
+/// Locks `mutex`, failing if a thread panicked while holding it, since the keys may then be out of
+/// step with the file.
+fn lock<T>(mutex: &Mutex<T>) -> io::Result<MutexGuard<'_, T>> {
+    mutex
+        .lock()
+        .map_err(|_| io::Error::other("dedup store lock poisoned"))
+}
+
+fn expired(recorded: u64, ttl: Duration, now: u64) -> bool {
+    now.saturating_sub(recorded) >= millis(ttl)
+}
+
+fn millis(duration: Duration) -> u64 {
+    duration.as_millis().try_into().unwrap_or(u64::MAX)
+}
+
+fn unix_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, millis)
+}
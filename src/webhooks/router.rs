@@ -0,0 +1,314 @@
+//! Dispatching events to handlers by type and status.
+
+use std::error;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use http::StatusCode;
+
+use super::{DedupStore, Event, EventData, EventKey, StateTracker};
+use crate::models;
+
+type BoxError = Box<dyn error::Error + Send + Sync>;
+type BoxFuture = Pin<Box<dyn Future<Output = Result<(), BoxError>> + Send>>;
+
+/// The data of an event that [`WebhookRouter`] can route on.
+pub trait EventPayload: Sized + Send + 'static {
+    #[doc(hidden)]
+    fn peek(data: &EventData) -> Option<&Self>;
+    #[doc(hidden)]
+    fn take(data: EventData) -> Option<Self>;
+}
+
+macro_rules! event_payload {
+    ($($variant:ident => $model:ty),* $(,)?) => {$(
+        impl EventPayload for $model {
+            fn peek(data: &EventData) -> Option<&Self> {
+                match data {
+                    EventData::$variant(data) => Some(data),
+                    _ => None,
+                }
+            }
+
+            fn take(data: EventData) -> Option<Self> {
+                match data {
+                    EventData::$variant(data) => Some(*data),
+                    _ => None,
+                }
+            }
+        }
+    )*};
+}
+
+event_payload!(
+    Transaction => models::Transaction,
+    FiatDeposit => models::FiatDeposit,
+    CheckoutSession => models::CheckoutSession,
+    Customer => models::Customer,
+);
+
+/// What became of a dispatched event, and so how to answer Noah.
+#[derive(Debug)]
+pub enum Outcome {
+    /// A handler processed the event.
+    Handled,
+    /// No handler or fallback matched. The event is still acknowledged, since redelivering it
+    /// would not change that.
+    Ignored,
+    /// The [`DedupStore`] has already seen the event, so its handler was not run again.
+    Duplicate,
+    /// The [`StateTracker`] already knows a later status for the entity, so the event was not
+    /// dispatched.
+    Stale,
+    /// The handler failed. Noah should redeliver the event.
+    Failed(BoxError),
+}
+
+impl Outcome {
+    /// Whether to acknowledge the event with a 2xx response.
+    pub fn is_ack(&self) -> bool {
+        !matches!(self, Outcome::Failed(_))
+    }
+
+    /// `200 OK` to acknowledge, `500 Internal Server Error` to have Noah redeliver.
+    pub fn status(&self) -> StatusCode {
+        if self.is_ack() {
+            StatusCode::OK
+        } else {
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Outcome::Handled => write!(f, "handled"),
+            Outcome::Ignored => write!(f, "ignored"),
+            Outcome::Duplicate => write!(f, "duplicate"),
+            Outcome::Stale => write!(f, "stale"),
+            Outcome::Failed(e) => write!(f, "failed: {e}"),
+        }
+    }
+}
+
+struct Route {
+    matches: Box<dyn Fn(&EventData) -> bool + Send + Sync>,
+    handler: Box<dyn Fn(EventData) -> BoxFuture + Send + Sync>,
+}
+
+/// Routes verified webhook events to async handlers.
+///
+/// Routes are tried in the order they were added and the first match handles the event, so add
+/// status-filtered routes before the catch-all route for the same type. Events that match no
+/// route go to the fallback. A handler error makes the [`Outcome`] ask Noah to redeliver, so
+/// handlers should be idempotent, or the router given a [`DedupStore`] with
+/// [`deduplicate`](WebhookRouter::deduplicate).
+///
+/// ```
+/// use noah_sdk::models::{CheckoutSession, Transaction, TransactionStatus};
+/// use noah_sdk::webhooks::{Event, WebhookRouter};
+///
+/// # #[derive(Debug)] struct LedgerError;
+/// # impl std::fmt::Display for LedgerError {
+/// #     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result { write!(f, "ledger") }
+/// # }
+/// # impl std::error::Error for LedgerError {}
+/// let router = WebhookRouter::new()
+///     .on_transaction_status(TransactionStatus::Settled, |transaction: Transaction| async move {
+///         println!("settled {}", transaction.id);
+///         Ok::<_, LedgerError>(())
+///     })
+///     .on(|session: CheckoutSession| async move {
+///         println!("checkout {} is {}", session.checkout_session_id, session.status);
+///         Ok::<_, LedgerError>(())
+///     })
+///     .fallback(|event: Event| async move {
+///         println!("unhandled {}", event.event_type());
+///         Ok::<_, LedgerError>(())
+///     });
+/// ```
+#[derive(Default)]
+pub struct WebhookRouter {
+    routes: Vec<Route>,
+    fallback: Option<Box<dyn Fn(Event) -> BoxFuture + Send + Sync>>,
+    dedup: Option<Box<dyn DedupStore>>,
+    tracker: Option<Arc<StateTracker>>,
+}
+
+impl fmt::Debug for WebhookRouter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WebhookRouter")
+            .field("routes", &self.routes.len())
+            .field("fallback", &self.fallback.is_some())
+            .field("dedup", &self.dedup.is_some())
+            .field("tracker", &self.tracker.is_some())
+            .finish()
+    }
+}
+
+fn boxed<Fut, E>(future: Fut) -> BoxFuture
+where
+    Fut: Future<Output = Result<(), E>> + Send + 'static,
+    E: Into<BoxError>,
+{
+    Box::pin(async move { future.await.map_err(Into::into) })
+}
+
+impl WebhookRouter {
+    pub fn new() -> WebhookRouter {
+        WebhookRouter::default()
+    }
+
+    /// Handles every event carrying a `T`: [`models::Transaction`], [`models::FiatDeposit`],
+    /// [`models::CheckoutSession`] or [`models::Customer`].
+    pub fn on<T, F, Fut, E>(self, handler: F) -> WebhookRouter
+    where
+        T: EventPayload,
+        F: Fn(T) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), E>> + Send + 'static,
+        E: Into<BoxError>,
+    {
+        self.on_if(|_: &T| true, handler)
+    }
+
+    /// Handles the events carrying a `T` for which `filter` returns `true`.
+    pub fn on_if<T, P, F, Fut, E>(mut self, filter: P, handler: F) -> WebhookRouter
+    where
+        T: EventPayload,
+        P: Fn(&T) -> bool + Send + Sync + 'static,
+        F: Fn(T) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), E>> + Send + 'static,
+        E: Into<BoxError>,
+    {
+        self.routes.push(Route {
+            matches: Box::new(move |data| T::peek(data).is_some_and(&filter)),
+            handler: Box::new(move |data| {
+                let data = T::take(data).expect("route matched the event type");
+                boxed(handler(data))
+            }),
+        });
+        self
+    }
+
+    pub fn on_transaction_status<F, Fut, E>(
+        self,
+        status: models::TransactionStatus,
+        handler: F,
+    ) -> WebhookRouter
+    where
+        F: Fn(models::Transaction) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), E>> + Send + 'static,
+        E: Into<BoxError>,
+    {
+        self.on_if(
+            move |transaction: &models::Transaction| transaction.status == status,
+            handler,
+        )
+    }
+
+    pub fn on_fiat_deposit_status<F, Fut, E>(
+        self,
+        status: models::FiatDepositStatus,
+        handler: F,
+    ) -> WebhookRouter
+    where
+        F: Fn(models::FiatDeposit) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), E>> + Send + 'static,
+        E: Into<BoxError>,
+    {
+        self.on_if(
+            move |deposit: &models::FiatDeposit| deposit.status == status,
+            handler,
+        )
+    }
+
+    pub fn on_checkout_session_status<F, Fut, E>(
+        self,
+        status: impl Into<String>,
+        handler: F,
+    ) -> WebhookRouter
+    where
+        F: Fn(models::CheckoutSession) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), E>> + Send + 'static,
+        E: Into<BoxError>,
+    {
+        let status = status.into();
+        self.on_if(
+            move |session: &models::CheckoutSession| session.status == status,
+            handler,
+        )
+    }
+
+    /// Handles the events no route matched, instead of ignoring them.
+    pub fn fallback<F, Fut, E>(mut self, handler: F) -> WebhookRouter
+    where
+        F: Fn(Event) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), E>> + Send + 'static,
+        E: Into<BoxError>,
+    {
+        self.fallback = Some(Box::new(move |event| boxed(handler(event))));
+        self
+    }
+
+    /// Records the [`EventKey`] of every event in `store` before dispatching it, and answers
+    /// [`Outcome::Duplicate`] instead of dispatching an event whose key is already there.
+    ///
+    /// The key is removed again when the handler fails, so that Noah's redelivery is handled.
+    pub fn deduplicate(mut self, store: impl DedupStore + 'static) -> WebhookRouter {
+        self.dedup = Some(Box::new(store));
+        self
+    }
+
+    /// Passes every status update through `tracker`, and answers [`Outcome::Stale`] instead of
+    /// dispatching the ones it drops. Keep a clone of the `Arc` to read the latest states.
+    pub fn reconcile(mut self, tracker: Arc<StateTracker>) -> WebhookRouter {
+        self.tracker = Some(tracker);
+        self
+    }
+
+    /// Runs the handler for `event`.
+    pub async fn dispatch(&self, event: Event) -> Outcome {
+        let Some(store) = &self.dedup else {
+            return self.route(event).await;
+        };
+        let key = EventKey::of(&event);
+        match store.insert(&key) {
+            Ok(true) => {}
+            Ok(false) => return Outcome::Duplicate,
+            Err(e) => return Outcome::Failed(e.into()),
+        }
+        let outcome = self.route(event).await;
+        if let Outcome::Failed(e) = outcome {
+            if let Err(remove) = store.remove(&key) {
+                return Outcome::Failed(format!("{e}; could not forget {key}: {remove}").into());
+            }
+            return Outcome::Failed(e);
+        }
+        outcome
+    }
+
+    async fn route(&self, event: Event) -> Outcome {
+        let transition = self.tracker.as_ref().and_then(|t| t.observe_event(&event));
+        if transition.is_some_and(|transition| !transition.is_current()) {
+            return Outcome::Stale;
+        }
+        let result = match self
+            .routes
+            .iter()
+            .find(|route| (route.matches)(&event.data))
+        {
+            Some(route) => (route.handler)(event.data).await,
+            None => match &self.fallback {
+                Some(fallback) => fallback(event).await,
+                None => return Outcome::Ignored,
+            },
+        };
+        match result {
+            Ok(()) => Outcome::Handled,
+            Err(e) => Outcome::Failed(e),
+        }
+    }
+}
@@ -0,0 +1,235 @@
+//! Reconciling status updates that arrive out of order.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use chrono::{DateTime, FixedOffset};
+
+use super::{Event, EventData};
+use crate::models;
+
+/// The status of a transaction, fiat deposit or checkout session.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Status {
+    Transaction(models::TransactionStatus),
+    FiatDeposit(models::FiatDepositStatus),
+    /// Noah may add checkout session statuses, so they are kept as strings.
+    CheckoutSession(String),
+}
+
+impl Status {
+    /// The status carried by `event`, or `None` for customer events.
+    pub fn of(event: &Event) -> Option<Status> {
+        match &event.data {
+            EventData::Transaction(transaction) => Some(Status::Transaction(transaction.status)),
+            EventData::FiatDeposit(deposit) => Some(Status::FiatDeposit(deposit.status)),
+            EventData::CheckoutSession(session) => {
+                Some(Status::CheckoutSession(session.status.clone()))
+            }
+            EventData::Customer(_) => None,
+        }
+    }
+
+    /// How far along its lifecycle the status is: `0` for `Pending`, `1` for the final `Settled`
+    /// and `Failed`, and `None` for checkout session statuses this version doesn't know.
+    pub fn stage(&self) -> Option<u8> {
+        let name = match self {
+            Status::Transaction(status) => return Some(transaction_stage(*status)),
+            Status::FiatDeposit(status) => return Some(deposit_stage(*status)),
+            Status::CheckoutSession(status) => status.as_str(),
+        };
+        match name {
+            "Pending" => Some(0),
+            "Settled" | "Failed" => Some(1),
+            _ => None,
+        }
+    }
+
+    /// Whether no further transition is legal.
+    pub fn is_final(&self) -> bool {
+        self.stage() == Some(1)
+    }
+
+    fn kind(&self) -> Kind {
+        match self {
+            Status::Transaction(_) => Kind::Transaction,
+            Status::FiatDeposit(_) => Kind::FiatDeposit,
+            Status::CheckoutSession(_) => Kind::CheckoutSession,
+        }
+    }
+}
+
+fn transaction_stage(status: models::TransactionStatus) -> u8 {
+    match status {
+        models::TransactionStatus::Pending => 0,
+        models::TransactionStatus::Settled | models::TransactionStatus::Failed => 1,
+    }
+}
+
+fn deposit_stage(status: models::FiatDepositStatus) -> u8 {
+    match status {
+        models::FiatDepositStatus::Pending => 0,
+        models::FiatDepositStatus::Settled | models::FiatDepositStatus::Failed => 1,
+    }
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Status::Transaction(status) => write!(f, "{status}"),
+            Status::FiatDeposit(status) => write!(f, "{status}"),
+            Status::CheckoutSession(status) => write!(f, "{status}"),
+        }
+    }
+}
+
+/// What [`StateTracker::observe`] made of a status update.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Transition {
+    /// The update moved the entity forward and is now its latest known state.
+    Applied { previous: Option<Status> },
+    /// The entity already has this status.
+    Unchanged,
+    /// The update is behind the latest known state, such as a `Pending` arriving after its
+    /// `Settled`, and was dropped.
+    Stale { current: Status },
+    /// The update contradicts a final state, such as a `Failed` after a `Settled`, and was
+    /// dropped.
+    Conflict { current: Status },
+}
+
+impl Transition {
+    /// Whether the update is the latest known state, as opposed to one that was dropped.
+    pub fn is_current(&self) -> bool {
+        matches!(self, Transition::Applied { .. } | Transition::Unchanged)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum Kind {
+    Transaction,
+    FiatDeposit,
+    CheckoutSession,
+}
+
+#[derive(Debug)]
+struct Latest {
+    status: Status,
+    occurred: String,
+}
+
+/// Tracks the latest status of each transaction, fiat deposit and checkout session from status
+/// updates that may arrive in any order.
+///
+/// Updates are ordered by the lifecycle first: `Pending` comes before `Settled` and `Failed`,
+/// which are final. `Occurred` orders the updates the lifecycle can't, those with checkout
+/// session statuses this version doesn't know, and the older of them is dropped.
+///
+/// Use it on its own through [`observe`](StateTracker::observe), or give it to
+/// [`WebhookRouter::reconcile`](super::WebhookRouter::reconcile) to drop stale events before they
+/// reach a handler. It remembers every entity it has seen.
+#[derive(Debug, Default)]
+pub struct StateTracker {
+    latest: Mutex<HashMap<(Kind, String), Latest>>,
+}
+
+impl StateTracker {
+    pub fn new() -> StateTracker {
+        StateTracker::default()
+    }
+
+    /// Records that entity `id` had `status` at `occurred`, an RFC 3339 timestamp, unless that
+    /// is behind what is already known.
+    pub fn observe(&self, id: &str, status: Status, occurred: &str) -> Transition {
+        let mut latest = self.latest();
+        let Some(current) = latest.get_mut(&(status.kind(), id.to_owned())) else {
+            latest.insert(
+                (status.kind(), id.to_owned()),
+                Latest {
+                    status,
+                    occurred: occurred.to_owned(),
+                },
+            );
+            return Transition::Applied { previous: None };
+        };
+        if current.status == status {
+            if is_after(occurred, &current.occurred) {
+                current.occurred = occurred.to_owned();
+            }
+            return Transition::Unchanged;
+        }
+        let forward = match (status.stage(), current.status.stage()) {
+            // Two different statuses at the same stage are both final.
+            (Some(stage), Some(current_stage)) if stage == current_stage => {
+                return Transition::Conflict {
+                    current: current.status.clone(),
+                };
+            }
+            (Some(stage), Some(current_stage)) => stage > current_stage,
+            _ => is_after(occurred, &current.occurred),
+        };
+        if !forward {
+            return Transition::Stale {
+                current: current.status.clone(),
+            };
+        }
+        let previous = std::mem::replace(&mut current.status, status);
+        current.occurred = occurred.to_owned();
+        Transition::Applied {
+            previous: Some(previous),
+        }
+    }
+
+    /// Observes the status carried by `event`, or returns `None` for customer events.
+    pub fn observe_event(&self, event: &Event) -> Option<Transition> {
+        let status = Status::of(event)?;
+        Some(self.observe(&event.entity_id(), status, &event.occurred))
+    }
+
+    pub fn transaction(&self, id: &str) -> Option<models::TransactionStatus> {
+        match self.get(Kind::Transaction, id)? {
+            Status::Transaction(status) => Some(status),
+            _ => None,
+        }
+    }
+
+    pub fn fiat_deposit(&self, id: &str) -> Option<models::FiatDepositStatus> {
+        match self.get(Kind::FiatDeposit, id)? {
+            Status::FiatDeposit(status) => Some(status),
+            _ => None,
+        }
+    }
+
+    pub fn checkout_session(&self, id: &str) -> Option<String> {
+        match self.get(Kind::CheckoutSession, id)? {
+            Status::CheckoutSession(status) => Some(status),
+            _ => None,
+        }
+    }
+
+    /// Locks the latest statuses. Entries are replaced whole, so a poisoned lock still holds
+    /// consistent ones.
+    fn latest(&self) -> MutexGuard<'_, HashMap<(Kind, String), Latest>> {
+        self.latest.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn get(&self, kind: Kind, id: &str) -> Option<Status> {
+        self.latest()
+            .get(&(kind, id.to_owned()))
+            .map(|latest| latest.status.clone())
+    }
+}
+
+/// Whether `occurred` is later than `current`. A timestamp that doesn't parse counts as later,
+/// so that it can't hold an entity back.
+fn is_after(occurred: &str, current: &str) -> bool {
+    match (parse(occurred), parse(current)) {
+        (Some(occurred), Some(current)) => occurred > current,
+        _ => true,
+    }
+}
+
+fn parse(timestamp: &str) -> Option<DateTime<FixedOffset>> {
+    DateTime::parse_from_rfc3339(timestamp).ok()
+}
@@ -0,0 +1,192 @@
+//! Tests for bulk payouts
+
+use std::time::{Duration, Instant};
+
+use noah_sdk::models::{self, TransactionStatus};
+use noah_sdk::payouts::{BulkPayout, PayoutError, PayoutInstruction};
+use noah_sdk::testing::{fixtures, Failure, MockNoah, StatusCode};
+
+fn with_customer(noah: &MockNoah) {
+    noah.state()
+        .customers
+        .push(models::Customer::Individual(Box::new(
+            fixtures::individual_customer(),
+        )));
+}
+
+fn channel_id(noah: &MockNoah, fiat_currency: &str) -> String {
+    let state = noah.state();
+    let channel = state
+        .channels
+        .iter()
+        .find(|channel| channel.fiat_currency == fiat_currency);
+    channel.unwrap().id.clone()
+}
+
+fn instruction(channel_id: &str, fiat_amount: &str, external_id: &str) -> PayoutInstruction {
+    PayoutInstruction {
+        customer_id: fixtures::CUSTOMER_ID.to_owned(),
+        channel_id: channel_id.to_owned(),
+        payment_method_id: "pm-1".to_owned(),
+        fiat_amount: fiat_amount.to_owned(),
+        external_id: external_id.to_owned(),
+    }
+}
+
+fn calls(noah: &MockNoah, operation: &str) -> usize {
+    noah.state()
+        .requests
+        .iter()
+        .filter(|request| request.operation == Some(operation))
+        .count()
+}
+
+#[tokio::test]
+async fn test_reports_each_instruction_in_order() {
+    let noah = MockNoah::start().await.unwrap();
+    with_customer(&noah);
+    let eur = channel_id(&noah, "EUR");
+    let usd = channel_id(&noah, "USD");
+    let instructions = [
+        instruction(&eur, "46", "payroll/1"),
+        instruction(&usd, "50", "payroll/2"),
+        instruction(&uuid::Uuid::new_v4().to_string(), "50", "payroll/3"),
+        instruction(&usd, "20", "payroll/2"),
+    ];
+
+    let report = BulkPayout::new(&noah.configuration(), "USDC_TEST")
+        .run(&instructions)
+        .await
+        .unwrap();
+
+    let external_ids: Vec<_> = report
+        .results
+        .iter()
+        .map(|result| result.external_id.as_str())
+        .collect();
+    assert_eq!(
+        external_ids,
+        ["payroll/1", "payroll/2", "payroll/3", "payroll/2"]
+    );
+    assert!(!report.is_complete());
+    assert!(report.results[..2].iter().all(|result| result.is_ok()
+        && result.status == Some(TransactionStatus::Pending)
+        && !result.resumed));
+    assert!(matches!(
+        report.results[2].error,
+        Some(PayoutError::Channel(_))
+    ));
+    assert!(matches!(
+        report.results[3].error,
+        Some(PayoutError::DuplicateExternalId)
+    ));
+    assert_eq!(report.failed().count(), 2);
+
+    let state = noah.state();
+    assert_eq!(state.transactions.len(), 2);
+    let paid = state
+        .transactions
+        .iter()
+        .find(|transaction| Some(transaction.id) == report.results[1].transaction_id)
+        .unwrap();
+    assert_eq!(paid.external_id.as_deref(), Some("payroll/2"));
+    let sell = state
+        .requests
+        .iter()
+        .filter(|request| request.operation == Some("transactions_sell_post"))
+        .filter_map(|request| request.body.as_ref())
+        .find(|body| body["ExternalID"] == "payroll/2")
+        .unwrap();
+    assert_eq!(sell["Nonce"], "payroll/2");
+}
+
+#[tokio::test]
+async fn test_resumes_from_submitted_transactions() {
+    let noah = MockNoah::start().await.unwrap();
+    with_customer(&noah);
+    let usd = channel_id(&noah, "USD");
+    let instructions: Vec<_> = (1..=4)
+        .map(|n| instruction(&usd, "25", &format!("payroll/{n}")))
+        .collect();
+    let payout = BulkPayout::new(&noah.configuration(), "USDC_TEST").concurrency(1);
+
+    noah.inject(Failure::status(
+        "transactions_sell_post",
+        StatusCode::SERVICE_UNAVAILABLE,
+    ));
+    let first = payout.run(&instructions).await.unwrap();
+    assert_eq!(first.failed().count(), 1);
+    assert_eq!(noah.state().transactions.len(), 3);
+
+    let second = payout.run(&instructions).await.unwrap();
+    assert!(second.is_complete());
+    let resumed: Vec<_> = second.results.iter().map(|result| result.resumed).collect();
+    let failed_first: Vec<_> = first.results.iter().map(|result| result.is_ok()).collect();
+    assert_eq!(resumed, failed_first);
+    for (before, after) in first.results.iter().zip(&second.results) {
+        if before.is_ok() {
+            assert_eq!(before.transaction_id, after.transaction_id);
+        }
+    }
+    assert_eq!(noah.state().transactions.len(), 4);
+    assert_eq!(calls(&noah, "transactions_sell_post"), 5);
+}
+
+#[tokio::test]
+async fn test_bounds_concurrency() {
+    let noah = MockNoah::start().await.unwrap();
+    with_customer(&noah);
+    let gbp = channel_id(&noah, "GBP");
+    let instructions: Vec<_> = (1..=6)
+        .map(|n| instruction(&gbp, "15.80", &format!("payroll/{n}")))
+        .collect();
+    noah.inject(
+        Failure::delay("transactions_sell_prepare_post", Duration::from_millis(200)).times(6),
+    );
+
+    let started = Instant::now();
+    let report = BulkPayout::new(&noah.configuration(), "USDC_TEST")
+        .concurrency(3)
+        .resume(false)
+        .run(&instructions)
+        .await
+        .unwrap();
+    let elapsed = started.elapsed();
+
+    assert!(report.is_complete());
+    // Two rounds of three delayed prepares.
+    assert!(elapsed >= Duration::from_millis(390), "{elapsed:?}");
+    assert!(elapsed < Duration::from_secs(1), "{elapsed:?}");
+    assert_eq!(calls(&noah, "transactions_get"), 0);
+}
+
+#[tokio::test]
+async fn test_reports_failed_transactions_on_resume() {
+    let noah = MockNoah::start().await.unwrap();
+    with_customer(&noah);
+    let usd = channel_id(&noah, "USD");
+    let instructions = [
+        instruction(&usd, "25", "payroll/1"),
+        instruction(&usd, "25", "payroll/2"),
+    ];
+    let failed = models::Transaction {
+        external_id: Some("payroll/1".to_owned()),
+        status: TransactionStatus::Failed,
+        ..fixtures::transaction()
+    };
+    noah.state().transactions.push(failed.clone());
+
+    let report = BulkPayout::new(&noah.configuration(), "USDC_TEST")
+        .run(&instructions)
+        .await
+        .unwrap();
+
+    assert!(!report.is_complete());
+    let first = &report.results[0];
+    assert!(first.resumed);
+    assert_eq!(first.transaction_id, Some(failed.id));
+    assert_eq!(first.status, Some(TransactionStatus::Failed));
+    assert!(matches!(first.error, Some(PayoutError::TransactionFailed)));
+    assert!(report.results[1].is_ok() && !report.results[1].resumed);
+    assert_eq!(calls(&noah, "transactions_sell_post"), 1);
+}
@@ -0,0 +1,135 @@
+//! Tests for the response cache
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use noah_sdk::apis::configuration::{Configuration, RequestOptions};
+use noah_sdk::apis::{utilities_api, Error};
+use noah_sdk::cache::ResponseCache;
+use noah_sdk::testing::{Failure, MockNoah};
+
+fn cached(noah: &MockNoah, cache: &Arc<ResponseCache>) -> Configuration {
+    Configuration {
+        cache: Some(cache.clone()),
+        ..noah.configuration()
+    }
+}
+
+fn calls(noah: &MockNoah, operation: &str) -> usize {
+    noah.state()
+        .requests
+        .iter()
+        .filter(|request| request.operation == Some(operation))
+        .count()
+}
+
+async fn price(config: &Configuration, amount: &str) -> String {
+    let response =
+        utilities_api::prices_get(config, "USDC_TEST", "EUR", Some(amount), None, None, None)
+            .await
+            .unwrap();
+    response.items[0].destination_amount.clone().unwrap()
+}
+
+#[tokio::test]
+async fn test_caches_per_parameters_until_the_ttl_passes() {
+    let noah = MockNoah::start().await.unwrap();
+    let cache = Arc::new(ResponseCache::new().ttl("prices_get", Duration::from_millis(200)));
+    let config = cached(&noah, &cache);
+
+    assert_eq!(price(&config, "100").await, "92.00");
+    assert_eq!(price(&config, "100").await, "92.00");
+    assert_eq!(calls(&noah, "prices_get"), 1);
+    assert_eq!(price(&config, "50").await, "46.00");
+    assert_eq!(calls(&noah, "prices_get"), 2);
+    assert_eq!(cache.len(), 2);
+
+    // Countries keep their default hour.
+    utilities_api::channels_sell_countries_get(&config, None, None)
+        .await
+        .unwrap();
+    tokio::time::sleep(Duration::from_millis(250)).await;
+    price(&config, "100").await;
+    utilities_api::channels_sell_countries_get(&config, None, None)
+        .await
+        .unwrap();
+    assert_eq!(calls(&noah, "prices_get"), 3);
+    assert_eq!(calls(&noah, "channels_sell_countries_get"), 1);
+}
+
+#[tokio::test]
+async fn test_concurrent_identical_calls_share_one_request() {
+    let noah = MockNoah::start().await.unwrap();
+    let cache = Arc::new(ResponseCache::new());
+    let config = cached(&noah, &cache);
+
+    let list = || {
+        utilities_api::channels_sell_get(
+            &config,
+            "USDC_TEST",
+            Some("DE"),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+    };
+    let (a, b, c) = tokio::join!(list(), list(), list());
+    assert_eq!(a.unwrap(), b.unwrap());
+    assert_eq!(c.unwrap().items[0].country, "DE");
+    assert_eq!(calls(&noah, "channels_sell_get"), 1);
+
+    // A caller waiting on a slow identical call still gives up at its deadline.
+    noah.inject(Failure::delay(
+        "channels_sell_countries_get",
+        Duration::from_millis(500),
+    ));
+    let impatient =
+        config.with_request_options(RequestOptions::new().deadline(Duration::from_millis(100)));
+    let waiter = async {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        let started = Instant::now();
+        let result = utilities_api::channels_sell_countries_get(&impatient, None, None).await;
+        (result, started.elapsed())
+    };
+    let (slow, (waited, elapsed)) = tokio::join!(
+        utilities_api::channels_sell_countries_get(&config, None, None),
+        waiter
+    );
+    assert!(slow.is_ok());
+    assert!(matches!(waited, Err(Error::DeadlineExceeded)), "{waited:?}");
+    assert!(elapsed < Duration::from_millis(300), "{elapsed:?}");
+}
+
+#[tokio::test]
+async fn test_invalidation_and_errors_go_to_the_api() {
+    let noah = MockNoah::start().await.unwrap();
+    let cache = Arc::new(ResponseCache::new());
+    let config = cached(&noah, &cache);
+
+    price(&config, "100").await;
+    cache.invalidate("prices_get");
+    assert!(cache.is_empty());
+    price(&config, "100").await;
+    assert_eq!(calls(&noah, "prices_get"), 2);
+
+    for _ in 0..2 {
+        let unknown =
+            utilities_api::prices_get(&config, "USDC_TEST", "JPY", None, None, None, None).await;
+        assert!(unknown.is_err());
+    }
+    assert_eq!(calls(&noah, "prices_get"), 4);
+
+    // Balances are not cached.
+    for _ in 0..2 {
+        utilities_api::balances_get(&config, None, None, None)
+            .await
+            .unwrap();
+    }
+    assert_eq!(calls(&noah, "balances_get"), 2);
+    cache.clear();
+    assert!(cache.is_empty());
+}
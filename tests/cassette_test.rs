@@ -0,0 +1,135 @@
+//! Tests for HTTP cassettes
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use noah_sdk::apis::{onboarding_api, utilities_api, Error};
+use noah_sdk::models;
+use noah_sdk::testing::{Cassette, CassetteMode, MockNoah, StatusCode};
+
+fn cassette_path() -> PathBuf {
+    std::env::temp_dir()
+        .join(format!("noah-cassette-{}", uuid::Uuid::new_v4()))
+        .join("cassette.json")
+}
+
+fn customer() -> models::CustomerInput {
+    models::CustomerInput::Individual(Box::new(models::IndividualCustomerInput {
+        full_name: Box::new(models::FullName {
+            first_name: "Jane".to_string(),
+            last_name: "Doe".to_string(),
+            middle_name: None,
+        }),
+        date_of_birth: "1990-01-01".to_string(),
+        ..Default::default()
+    }))
+}
+
+#[tokio::test]
+async fn test_recording_scrubs_credentials_and_pii() {
+    let noah = MockNoah::start().await.unwrap();
+    let path = cassette_path();
+    let cassette = Arc::new(Cassette::record(&path));
+    let mut config = noah.configuration();
+    config.cassette = Some(cassette.clone());
+
+    onboarding_api::customers_customer_id_put(&config, "cust-1", customer(), Some("sig-value"))
+        .await
+        .unwrap();
+    utilities_api::customers_customer_id_get(&config, "cust-1")
+        .await
+        .unwrap();
+    cassette.save().unwrap();
+
+    let saved = std::fs::read_to_string(&path).unwrap();
+    for secret in ["mock-api-key", "sig-value", "Jane", "1990-01-01"] {
+        assert!(!saved.contains(secret), "{secret} leaked into the cassette");
+    }
+    let interactions = cassette.interactions();
+    assert_eq!(interactions.len(), 2);
+    assert_eq!(interactions[0].request.path, "/customers/cust-1");
+    assert_eq!(interactions[0].request.headers["x-api-key"], "[REDACTED]");
+    assert_eq!(
+        interactions[0].request.headers["api-signature"],
+        "[REDACTED]"
+    );
+    assert_eq!(interactions[1].operation, "customers_customer_id_get");
+    assert_eq!(interactions[1].response.status, 200);
+}
+
+#[tokio::test]
+async fn test_replay_needs_no_server_and_ignores_query_order() {
+    let path = cassette_path();
+    let config = {
+        let noah = MockNoah::start().await.unwrap();
+        let cassette = Arc::new(Cassette::record(&path));
+        let mut config = noah.configuration();
+        config.cassette = Some(cassette.clone());
+        utilities_api::channels_sell_get(
+            &config,
+            "USDC_TEST",
+            Some("DE"),
+            Some("EUR"),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+        cassette.save().unwrap();
+        config
+    };
+
+    // Store the query in a different order, as another client might have sent it
+    let mut saved: serde_json::Value =
+        serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+    saved["interactions"][0]["request"]["query"]
+        .as_array_mut()
+        .unwrap()
+        .reverse();
+    std::fs::write(&path, serde_json::to_vec(&saved).unwrap()).unwrap();
+
+    let cassette = Arc::new(Cassette::open(&path).unwrap());
+    assert_eq!(cassette.mode(), CassetteMode::Replay);
+    let mut config = config;
+    config.cassette = Some(cassette.clone());
+    let channels = utilities_api::channels_sell_get(
+        &config,
+        "USDC_TEST",
+        Some("DE"),
+        Some("EUR"),
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+    )
+    .await
+    .unwrap();
+    assert_eq!(channels.items[0].fiat_currency, "EUR");
+    assert!(cassette.unplayed().is_empty());
+}
+
+#[tokio::test]
+async fn test_replay_without_matching_interaction() {
+    let path = cassette_path();
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(&path, r#"{"interactions": []}"#).unwrap();
+
+    let mut config = noah_sdk::apis::configuration::Configuration::new();
+    config.cassette = Some(Arc::new(Cassette::replay(&path).unwrap()));
+    let err = utilities_api::balances_get(&config, None, None, None)
+        .await
+        .unwrap_err();
+    match err {
+        Error::ResponseError(e) => {
+            assert_eq!(e.status, StatusCode::NOT_IMPLEMENTED);
+            assert!(e.content.contains("balances_get"));
+        }
+        other => panic!("unexpected error: {other}"),
+    }
+}
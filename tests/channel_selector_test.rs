@@ -0,0 +1,198 @@
+//! Tests for channel selection
+
+use noah_sdk::channels::{Candidate, ChannelSelector, Exclusion, WeightedCost};
+use noah_sdk::models::{self, ProcessingTier};
+use noah_sdk::sell::{SellError, SellFlow};
+use noah_sdk::testing::{fixtures, MockNoah};
+use noah_sdk::Decimal;
+
+fn channel(id: &str, rate: &str, fee: &str, tier: ProcessingTier) -> models::Channel {
+    models::Channel {
+        id: id.to_owned(),
+        rate: rate.to_owned(),
+        calculated: Some(Box::new(models::ChannelCalculated {
+            total_fee: fee.to_owned(),
+        })),
+        processing_tier: Some(tier),
+        ..fixtures::channel()
+    }
+}
+
+#[test]
+fn test_explains_why_channels_are_excluded() {
+    let sepa = channel("sepa", "0.92", "1", ProcessingTier::Standard);
+    let fedwire = models::Channel {
+        fiat_currency: "USD".to_owned(),
+        country: "US".to_owned(),
+        ..channel("fedwire", "1.00", "1", ProcessingTier::Standard)
+    };
+    let card = models::Channel {
+        payment_method_category: "Card".to_owned(),
+        ..channel("card", "0.92", "1", ProcessingTier::Standard)
+    };
+    let large = models::Channel {
+        limits: Box::new(models::ChannelLimits {
+            min_limit: "500".to_owned(),
+            max_limit: None,
+        }),
+        ..channel("large", "0.92", "1", ProcessingTier::Standard)
+    };
+    let broken = channel("broken", "n/a", "1", ProcessingTier::Standard);
+
+    let selection = ChannelSelector::new()
+        .country("de")
+        .fiat_currency("EUR")
+        .payment_method_category("Bank")
+        .fiat_amount(Decimal::new(100, 0))
+        .select(vec![sepa, fedwire, card, large, broken]);
+
+    assert_eq!(selection.best().unwrap().id, "sepa");
+    let reasons: Vec<_> = selection
+        .excluded
+        .iter()
+        .map(|excluded| (excluded.channel.id.as_str(), excluded.reason.clone()))
+        .collect();
+    assert_eq!(
+        reasons,
+        vec![
+            (
+                "fedwire",
+                Exclusion::Country {
+                    wanted: "de".to_owned()
+                }
+            ),
+            (
+                "card",
+                Exclusion::PaymentMethodCategory {
+                    wanted: "Bank".to_owned()
+                }
+            ),
+            (
+                "large",
+                Exclusion::BelowMinimum {
+                    amount: Decimal::new(100, 0),
+                    min: Decimal::new(500, 0)
+                }
+            ),
+            (
+                "broken",
+                Exclusion::InvalidValue {
+                    field: "Rate",
+                    value: "n/a".to_owned()
+                }
+            ),
+        ]
+    );
+    assert_eq!(
+        selection.excluded[2].to_string(),
+        "DE BankSepa channel large: 100 is below the minimum of 500"
+    );
+}
+
+#[test]
+fn test_ranks_by_the_cost_function() {
+    // At 100 EUR, `cheap` costs 100 / 0.95 + 3 = 108.26 and `priority` 100 / 0.92 + 1 = 109.70.
+    let channels = vec![
+        channel("priority", "0.92", "1", ProcessingTier::Priority),
+        channel("cheap", "0.95", "3", ProcessingTier::Standard),
+    ];
+    let selector = ChannelSelector::new().fiat_amount(Decimal::new(100, 0));
+
+    let selection = selector.select(channels.clone());
+    let ranked: Vec<_> = selection
+        .ranked
+        .iter()
+        .map(|ranked| ranked.candidate.channel.id.as_str())
+        .collect();
+    assert_eq!(ranked, ["cheap", "priority"]);
+    assert_eq!(selection.ranked[1].candidate.total_fee, Decimal::ONE);
+    assert_eq!(selection.ranked[1].cost.round_dp(2), Decimal::new(10970, 2));
+
+    let selection = selector
+        .clone()
+        .cost(WeightedCost {
+            preferred_tier: Some(ProcessingTier::Priority),
+            tier_penalty: Decimal::new(5, 0),
+            ..WeightedCost::default()
+        })
+        .select(channels.clone());
+    assert_eq!(selection.best().unwrap().id, "priority");
+
+    let selection = selector
+        .cost(|candidate: &Candidate| -candidate.rate)
+        .select(channels);
+    assert_eq!(selection.best().unwrap().id, "cheap");
+}
+
+#[tokio::test]
+async fn test_fetches_channels_and_drives_the_sell_flow() {
+    let noah = MockNoah::start().await.unwrap();
+    let config = noah.configuration();
+
+    let selection = ChannelSelector::new()
+        .fiat_currency("GBP")
+        .fetch(&config, "USDC_TEST", None, None)
+        .await
+        .unwrap();
+    assert_eq!(selection.ranked.len(), 1);
+    assert_eq!(selection.best().unwrap().country, "GB");
+
+    let error = SellFlow::new(&config, "USDC_TEST", "100")
+        .selector(ChannelSelector::new().payment_method_category("Card"))
+        .run()
+        .await
+        .unwrap_err();
+    match error {
+        SellError::NoChannel { excluded } => {
+            assert_eq!(excluded.len(), 3);
+            assert!(excluded.iter().all(|excluded| matches!(
+                excluded.reason,
+                Exclusion::PaymentMethodCategory { .. }
+            )));
+        }
+        other => panic!("unexpected {other}"),
+    }
+}
+
+#[tokio::test]
+async fn test_sell_flow_picks_from_every_page() {
+    let noah = MockNoah::start().await.unwrap();
+    let cheapest = uuid::Uuid::new_v4().to_string();
+    {
+        let mut state = noah.state();
+        let eur = state.channels.iter().find(|c| c.fiat_currency == "EUR");
+        let eur = eur.unwrap().clone();
+        // More expensive channels than fit in a page, then the cheapest
+        for n in 0..25 {
+            let id = if n == 24 {
+                cheapest.clone()
+            } else {
+                uuid::Uuid::new_v4().to_string()
+            };
+            let fee = if n == 24 { "0.10" } else { "2.00" };
+            state
+                .channels
+                .push(channel(&id, &eur.rate, fee, ProcessingTier::Standard));
+        }
+        state.channels.retain(|c| c.calculated.is_some());
+    }
+
+    let quote = SellFlow::new(&noah.configuration(), "USDC_TEST", "92")
+        .fiat_currency("EUR")
+        .payment_method_id("pm-1")
+        .quote()
+        .await
+        .unwrap();
+    assert_eq!(quote.channel().id, cheapest);
+    let pages: Vec<_> = noah
+        .state()
+        .requests
+        .iter()
+        .filter(|request| request.operation == Some("channels_sell_get"))
+        .map(|request| request.query.clone())
+        .collect();
+    assert_eq!(pages.len(), 2);
+    assert!(pages
+        .iter()
+        .all(|query| query.contains(&("PaymentMethodID".to_owned(), "pm-1".to_owned()))));
+}
@@ -0,0 +1,192 @@
+//! Tests for the circuit breaker
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use noah_sdk::apis::configuration::{Configuration, RequestOptions};
+use noah_sdk::apis::{utilities_api, Error};
+use noah_sdk::circuit_breaker::{CircuitBreaker, CircuitState, StateChange};
+use noah_sdk::rate_limit::{RateLimit, RateLimiter};
+use noah_sdk::testing::{Failure, MockNoah, StatusCode};
+
+fn guarded(noah: &MockNoah, breaker: CircuitBreaker) -> (Configuration, Arc<CircuitBreaker>) {
+    let breaker = Arc::new(breaker);
+    let config = Configuration {
+        circuit_breaker: Some(breaker.clone()),
+        ..noah.configuration()
+    };
+    (config, breaker)
+}
+
+fn calls(noah: &MockNoah, operation: &str) -> usize {
+    noah.state()
+        .requests
+        .iter()
+        .filter(|request| request.operation == Some(operation))
+        .count()
+}
+
+fn recorder() -> (
+    Arc<Mutex<Vec<StateChange>>>,
+    impl Fn(&StateChange) + Send + Sync,
+) {
+    let changes = Arc::new(Mutex::new(Vec::new()));
+    let sink = changes.clone();
+    (changes, move |change: &StateChange| {
+        sink.lock().unwrap().push(change.clone())
+    })
+}
+
+#[tokio::test]
+async fn test_opens_on_consecutive_server_errors() {
+    let noah = MockNoah::start().await.unwrap();
+    let (changes, listener) = recorder();
+    let (config, breaker) = guarded(
+        &noah,
+        CircuitBreaker::new()
+            .failure_threshold(2)
+            .on_state_change(listener),
+    );
+
+    // Client errors show the endpoint is up.
+    noah.inject(Failure::status("balances_get", StatusCode::BAD_REQUEST).times(3));
+    for _ in 0..3 {
+        assert!(utilities_api::balances_get(&config, None, None, None)
+            .await
+            .is_err());
+    }
+    assert_eq!(breaker.state("balances_get"), CircuitState::Closed);
+
+    noah.inject(Failure::status("balances_get", StatusCode::SERVICE_UNAVAILABLE).times(2));
+    for _ in 0..2 {
+        let error = utilities_api::balances_get(&config, None, None, None)
+            .await
+            .unwrap_err();
+        assert!(matches!(error, Error::ResponseError(_)), "{error}");
+    }
+    let error = utilities_api::balances_get(&config, None, None, None)
+        .await
+        .unwrap_err();
+    match error {
+        Error::CircuitOpen {
+            operation,
+            retry_in,
+        } => {
+            assert_eq!(operation, "balances_get");
+            assert!(retry_in > Duration::from_secs(29));
+        }
+        other => panic!("unexpected {other}"),
+    }
+    assert_eq!(calls(&noah, "balances_get"), 5);
+    assert_eq!(
+        changes.lock().unwrap()[0].to_string(),
+        "circuit for balances_get went open after 2 failures"
+    );
+
+    // Other endpoints keep their own circuit.
+    utilities_api::channels_sell_countries_get(&config, None, None)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_half_open_probe_closes_or_reopens() {
+    let noah = MockNoah::start().await.unwrap();
+    let (changes, listener) = recorder();
+    let (config, breaker) = guarded(
+        &noah,
+        CircuitBreaker::new()
+            .failure_threshold(1)
+            .open_for(Duration::from_millis(100))
+            .on_state_change(listener),
+    );
+
+    noah.inject(Failure::status("balances_get", StatusCode::BAD_GATEWAY).times(2));
+    let _ = utilities_api::balances_get(&config, None, None, None).await;
+    assert_eq!(breaker.state("balances_get"), CircuitState::Open);
+    tokio::time::sleep(Duration::from_millis(150)).await;
+    // The probe fails too.
+    let _ = utilities_api::balances_get(&config, None, None, None).await;
+    assert_eq!(breaker.state("balances_get"), CircuitState::Open);
+
+    tokio::time::sleep(Duration::from_millis(150)).await;
+    utilities_api::balances_get(&config, None, None, None)
+        .await
+        .unwrap();
+    assert_eq!(breaker.state("balances_get"), CircuitState::Closed);
+
+    let transitions: Vec<_> = changes
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|change| (change.from, change.to))
+        .collect();
+    use CircuitState::*;
+    assert_eq!(
+        transitions,
+        [
+            (Closed, Open),
+            (Open, HalfOpen),
+            (HalfOpen, Open),
+            (Open, HalfOpen),
+            (HalfOpen, Closed),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_timeouts_count_as_failures() {
+    let noah = MockNoah::start().await.unwrap();
+    let (config, breaker) = guarded(&noah, CircuitBreaker::new().failure_threshold(1));
+    let impatient =
+        config.with_request_options(RequestOptions::new().timeout(Duration::from_millis(50)));
+
+    noah.inject(Failure::delay("balances_get", Duration::from_millis(300)));
+    let error = utilities_api::balances_get(&impatient, None, None, None)
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(error, Error::Reqwest(ref e) if e.is_timeout()),
+        "{error}"
+    );
+    assert_eq!(breaker.state("balances_get"), CircuitState::Open);
+
+    breaker.reset("balances_get");
+    utilities_api::balances_get(&config, None, None, None)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_open_circuit_spends_no_rate_limit_tokens() {
+    let noah = MockNoah::start().await.unwrap();
+    let (config, breaker) = guarded(&noah, CircuitBreaker::new().failure_threshold(1));
+    let config = Configuration {
+        rate_limiter: Some(Arc::new(
+            RateLimiter::new().global(RateLimit::per_minute(2)),
+        )),
+        ..config
+    };
+
+    noah.inject(Failure::status(
+        "balances_get",
+        StatusCode::SERVICE_UNAVAILABLE,
+    ));
+    let calls = async {
+        let _ = utilities_api::balances_get(&config, None, None, None).await;
+        for _ in 0..5 {
+            let error = utilities_api::balances_get(&config, None, None, None)
+                .await
+                .unwrap_err();
+            assert!(matches!(error, Error::CircuitOpen { .. }), "{error}");
+        }
+        breaker.reset("balances_get");
+        // The second token is still there.
+        utilities_api::balances_get(&config, None, None, None)
+            .await
+            .unwrap();
+    };
+    tokio::time::timeout(Duration::from_secs(5), calls)
+        .await
+        .expect("rejected calls waited for rate-limit tokens");
+}
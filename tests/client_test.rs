@@ -10,7 +10,7 @@ async fn test_client_creation() {
         base_path: "https://api.sandbox.noah.com/v1".to_string(),
         api_key: Some(ApiKey {
             prefix: None,
-            key: "test-api-key".to_string(),
+            key: "test-api-key".into(),
         }),
         ..Default::default()
     };
@@ -25,7 +25,7 @@ async fn test_get_balances() {
         base_path: "https://api.sandbox.noah.com/v1".to_string(),
         api_key: Some(ApiKey {
             prefix: None,
-            key: "test-api-key".to_string(),
+            key: "test-api-key".into(),
         }),
         ..Default::default()
     };
@@ -0,0 +1,185 @@
+//! Tests for configuration loading and retries
+
+use std::time::Duration;
+
+use noah_sdk::apis::configuration::{ConfigError, Configuration, Environment, Profile};
+use noah_sdk::apis::{payout_api, utilities_api, Error};
+use noah_sdk::models;
+use noah_sdk::testing::{Failure, MockNoah, StatusCode};
+
+fn temp_file(extension: &str, content: &str) -> std::path::PathBuf {
+    let path =
+        std::env::temp_dir().join(format!("noah-config-{}.{extension}", uuid::Uuid::new_v4()));
+    std::fs::write(&path, content).unwrap();
+    path
+}
+
+#[test]
+fn test_profile_from_vars() {
+    let config = Profile::from_vars([
+        ("NOAH_API_KEY", "key-123"),
+        ("NOAH_ENV", "production"),
+        ("NOAH_MAX_RETRIES", "3"),
+        ("NOAH_RETRY_BACKOFF_MS", "50"),
+        ("HOME", "/root"),
+    ])
+    .unwrap()
+    .into_configuration()
+    .unwrap();
+
+    assert_eq!(config.api_key.as_ref().unwrap().key.expose(), "key-123");
+    assert_eq!(config.environment(), Environment::Production);
+    assert_eq!(config.retry.max_retries, 3);
+    assert_eq!(config.retry.initial_backoff, Duration::from_millis(50));
+}
+
+#[test]
+fn test_profile_errors_name_the_setting() {
+    let err = Profile::from_vars([("NOAH_ENV", "sandbox")])
+        .unwrap()
+        .into_configuration()
+        .unwrap_err();
+    assert!(matches!(err, ConfigError::Missing { ref setting } if setting == "NOAH_API_KEY"));
+
+    let err = Profile::from_vars([("NOAH_TIMEOUT_SECS", "soon")]).unwrap_err();
+    assert!(
+        matches!(err, ConfigError::Invalid { ref setting, .. } if setting == "NOAH_TIMEOUT_SECS")
+    );
+
+    let err = Profile::from_vars([
+        ("NOAH_API_KEY", "key-123"),
+        ("NOAH_ENV", "production"),
+        ("NOAH_BASE_URL", "https://api.sandbox.noah.com/v1"),
+    ])
+    .unwrap()
+    .into_configuration()
+    .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "conflicting settings NOAH_ENV and NOAH_BASE_URL"
+    );
+}
+
+#[test]
+fn test_from_file_profiles() {
+    let toml = temp_file(
+        "toml",
+        r#"
+        [profiles.sandbox]
+        environment = "sandbox"
+        api_key = "sandbox-key"
+
+        [profiles.local]
+        base_url = "http://localhost:4010/v1"
+        api_key = "local-key"
+        timeout_secs = 5
+        "#,
+    );
+    let sandbox = Configuration::from_file(&toml, "sandbox").unwrap();
+    assert_eq!(sandbox.environment(), Environment::Sandbox);
+    assert_eq!(
+        sandbox.api_key.as_ref().unwrap().key.expose(),
+        "sandbox-key"
+    );
+    let local = Configuration::from_file(&toml, "local").unwrap();
+    assert_eq!(local.base_path, "http://localhost:4010/v1");
+    assert!(matches!(
+        Configuration::from_file(&toml, "prod"),
+        Err(ConfigError::UnknownProfile { .. })
+    ));
+
+    let json = temp_file(
+        "json",
+        r#"{"profiles": {"prod": {"environment": "production", "api_key": "a", "api_key_env": "B"}}}"#,
+    );
+    let err = Configuration::from_file(&json, "prod").unwrap_err();
+    assert!(matches!(err, ConfigError::Conflict { .. }));
+    assert!(err.to_string().contains("`api_key` in profile `prod`"));
+
+    std::fs::remove_file(toml).unwrap();
+    std::fs::remove_file(json).unwrap();
+}
+
+#[tokio::test]
+async fn test_retries_server_errors() {
+    let mut server = mockito::Server::new_async().await;
+    let unavailable = server
+        .mock("GET", "/balances")
+        .with_status(503)
+        .expect(1)
+        .create_async()
+        .await;
+    let ok = server
+        .mock("GET", "/balances")
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"Items": []}"#)
+        .expect(1)
+        .create_async()
+        .await;
+
+    let mut config =
+        Configuration::for_environment(Environment::Custom(server.url().parse().unwrap()));
+    config.retry.max_retries = 1;
+    config.retry.initial_backoff = Duration::from_millis(1);
+
+    let balances = utilities_api::balances_get(&config, None, None, None)
+        .await
+        .unwrap();
+    assert!(balances.items.is_empty());
+    unavailable.assert_async().await;
+    ok.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_retries_posts_only_with_a_nonce() {
+    let noah = MockNoah::start().await.unwrap();
+    let mut config = noah.configuration();
+    config.retry.max_retries = 2;
+    config.retry.initial_backoff = Duration::from_millis(1);
+    let channel = noah.state().channels[0].clone();
+    let calls = |operation: &str| {
+        noah.state()
+            .requests
+            .iter()
+            .filter(|request| request.operation == Some(operation))
+            .count()
+    };
+
+    let prepare = models::PrepareSellRequest {
+        channel_id: channel.id.parse().unwrap(),
+        crypto_currency: "USDC_TEST".to_string(),
+        fiat_amount: "92".to_string(),
+        ..Default::default()
+    };
+    noah.inject(Failure::status(
+        "transactions_sell_prepare_post",
+        StatusCode::SERVICE_UNAVAILABLE,
+    ));
+    let error = payout_api::transactions_sell_prepare_post(&config, prepare.clone(), None)
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(error, Error::ResponseError(ref e) if e.status == StatusCode::SERVICE_UNAVAILABLE)
+    );
+    assert_eq!(calls("transactions_sell_prepare_post"), 1);
+
+    let prepared = payout_api::transactions_sell_prepare_post(&config, prepare, None)
+        .await
+        .unwrap();
+    noah.inject(Failure::status(
+        "transactions_sell_post",
+        StatusCode::SERVICE_UNAVAILABLE,
+    ));
+    let sell = models::SellRequest {
+        crypto_currency: "USDC_TEST".to_string(),
+        fiat_amount: "92".to_string(),
+        crypto_authorized_amount: prepared.crypto_authorized_amount,
+        form_session_id: prepared.form_session_id,
+        nonce: "nonce-1".to_string(),
+        external_id: None,
+    };
+    payout_api::transactions_sell_post(&config, sell, None)
+        .await
+        .unwrap();
+    assert_eq!(calls("transactions_sell_post"), 2);
+}
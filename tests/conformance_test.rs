@@ -0,0 +1,381 @@
+//! Conformance tests against the vendored OpenAPI spec
+//!
+//! Every schema in `spec/openapi.json` gets example payloads generated from it: one with all
+//! properties and one with only the required ones, one per variant for unions and one per value
+//! for string enums. Each payload must deserialize into the model of the same name and serialize
+//! back to the same JSON, ignoring key order.
+//!
+//! The spec must be Noah's published document, vendored with `make spec` so that `spec/SOURCE`
+//! records where it came from. Until it is, these tests fail.
+
+use std::collections::BTreeSet;
+
+use noah_sdk::models;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+
+const SPEC: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/spec/openapi.json");
+const SOURCE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/spec/SOURCE");
+
+/// Deep enough for every model; past this only required properties are generated.
+const MAX_DEPTH: usize = 8;
+
+macro_rules! conformance {
+    ($($model:ident),* $(,)?) => {
+        const MODELS: &[&str] = &[$(stringify!($model)),*];
+
+        fn round_trip(schema: &str, payload: &Value) -> Option<Result<Value, String>> {
+            match schema {
+                $(stringify!($model) => Some(check::<models::$model>(payload)),)*
+                _ => None,
+            }
+        }
+    };
+}
+
+conformance!(
+    AmountCondition,
+    AssociateInformationInput,
+    BalanceResponse,
+    BankDepositToOnchainAddressHostedRequest,
+    BankDepositToOnchainAddressRequest,
+    BankDepositToOnchainAddressResponse,
+    BusinessAssociateInformationInput,
+    BusinessCustomer,
+    BusinessCustomerInput,
+    BusinessCustomerPrefill,
+    BusinessCustomerPrefillAmlctfRegulated,
+    BusinessCustomerPrefillAmlctfRegulatedCustomerRiskSplit,
+    BusinessCustomerPrefillScreeningInput,
+    BusinessFinancialsInput,
+    Channel,
+    ChannelCalculated,
+    ChannelLimits,
+    CheckoutManageRequest,
+    CheckoutPayinCryptoPostRequest,
+    CheckoutPayinFiatPostRequest,
+    CheckoutPayoutFiatPostRequest,
+    CheckoutSession,
+    CheckoutSessionResponse,
+    ComparisonOperator,
+    Customer,
+    CustomerFormRequest,
+    CustomerIdentity,
+    CustomerInput,
+    DenyExtensionItem,
+    DenyExtensionPrincipal,
+    DepositDestinationTrigger,
+    DepositDestinationTriggerInput,
+    DepositSourceTrigger,
+    DepositSourceTriggerCondition,
+    DepositSourceTriggerConditionInput,
+    DepositSourceTriggerInput,
+    DestinationAddress,
+    DocumentSide,
+    DocumentType,
+    EntityEnum,
+    EntityRejectionData,
+    EntityVerification,
+    Error,
+    ErrorExtensions,
+    FeeBreakdownItem,
+    FiatAmount,
+    FiatDeposit,
+    FiatDepositSimulateRequest,
+    FiatDepositSimulateResponse,
+    FiatDepositStatus,
+    FiatOption,
+    FiatPayment,
+    FiatPaymentMethodBankDisplay,
+    FiatPaymentMethodCardDisplay,
+    FiatPaymentMethodIdentifierDisplay,
+    FiatPaymentRefund,
+    FiatPaymentStatus,
+    FormSchema,
+    FullName,
+    GetBalancesResponse,
+    GetChannelsResponse,
+    GetCustomersResponse,
+    GetFormResponse,
+    GetPaymentMethodsResponse,
+    GetPricesResponse,
+    GetTransactionsResponse,
+    HostedOnboardingRequest,
+    HostedSessionResponse,
+    IndividualCustomer,
+    IndividualCustomerInput,
+    IndividualCustomerPrefill,
+    IndividualFinancialsInput,
+    IntercomIdentifyHashResponse,
+    LineItem,
+    OnchainDepositSourceTriggerConditionInput,
+    OnchainDepositToPaymentMethodHostedRequest,
+    OnchainDepositToPaymentMethodRequest,
+    OnchainDepositToPaymentMethodResponse,
+    PaymentMethod,
+    PaymentMethodDisplay,
+    PaymentMethodDisplayDetails,
+    PrefillDocumentUploadUrlResponse,
+    PrefillOnboardingRequest,
+    PrepareSellRequest,
+    PrepareSellResponse,
+    PriceItem,
+    ProcessingTier,
+    RegisteredForeignBranchesInput,
+    RequestExtension,
+    RequestExtensionItem,
+    Rule,
+    RuleCreateRequest,
+    RuleCreateRequestActionsInner,
+    RuleTrigger,
+    SellActionInput,
+    SellRequest,
+    SellResponse,
+    SenderPaymentMethod,
+    SingleOnchainDepositSourceTriggerInput,
+    SortDirection,
+    StepDecimal,
+    StepDecimalOperation,
+    StreetAddress,
+    SumSubToken,
+    Transaction,
+    TransactionAdjustment,
+    TransactionBreakdownItem,
+    TransactionDirection,
+    TransactionOrchestration,
+    TransactionStatus,
+    UboInformationInput,
+    Verification,
+    Verifications,
+    Webhook,
+    WebhookData,
+);
+
+fn check<T: Serialize + DeserializeOwned>(payload: &Value) -> Result<Value, String> {
+    let model: T = serde_json::from_value(payload.clone()).map_err(|e| e.to_string())?;
+    serde_json::to_value(&model).map_err(|e| e.to_string())
+}
+
+struct Spec {
+    document: Value,
+}
+
+impl Spec {
+    /// The vendored spec; fails the test when it has not been vendored yet.
+    fn load() -> Spec {
+        let document = std::fs::read_to_string(SPEC)
+            .expect("spec/openapi.json is missing, vendor it with `make spec URL=...`");
+        Spec {
+            document: serde_json::from_str(&document).expect("spec/openapi.json is valid JSON"),
+        }
+    }
+
+    fn schemas(&self) -> &Map<String, Value> {
+        self.document["components"]["schemas"]
+            .as_object()
+            .expect("components.schemas")
+    }
+
+    fn resolve<'a>(&'a self, schema: &'a Value) -> (&'a str, &'a Value) {
+        match schema.get("$ref").and_then(Value::as_str) {
+            Some(reference) => {
+                let name = reference
+                    .strip_prefix("#/components/schemas/")
+                    .unwrap_or_else(|| panic!("unsupported reference {reference}"));
+                let schema = self
+                    .schemas()
+                    .get(name)
+                    .unwrap_or_else(|| panic!("dangling reference {reference}"));
+                (name, schema)
+            }
+            None => ("", schema),
+        }
+    }
+
+    /// Properties that are set by a union's discriminator rather than by the schema's own model.
+    fn discriminated(&self, name: &str) -> Option<&str> {
+        let reference = format!("#/components/schemas/{name}");
+        self.schemas().values().find_map(|schema| {
+            let discriminator = schema.get("discriminator")?;
+            let mapping = discriminator["mapping"].as_object()?;
+            mapping
+                .values()
+                .any(|target| *target == reference)
+                .then(|| discriminator["propertyName"].as_str())
+                .flatten()
+        })
+    }
+
+    /// The payloads to check for the named schema.
+    fn examples(&self, name: &str) -> Vec<Value> {
+        let schema = &self.schemas()[name];
+        if let Some(variants) = schema.get("oneOf").and_then(Value::as_array) {
+            return (0..variants.len())
+                .map(|variant| self.union(schema, variant, true, 0))
+                .collect();
+        }
+        if let Some(values) = schema.get("enum").and_then(Value::as_array) {
+            return values.clone();
+        }
+        if schema.get("properties").is_some() {
+            return vec![
+                self.example(schema, true, 0),
+                self.example(schema, false, 0),
+            ];
+        }
+        vec![self.example(schema, true, 0)]
+    }
+
+    fn union(&self, schema: &Value, variant: usize, full: bool, depth: usize) -> Value {
+        let (variant_name, _) = self.resolve(&schema["oneOf"][variant]);
+        let mut payload = self.example(&schema["oneOf"][variant], full, depth);
+        if let Some(discriminator) = schema.get("discriminator") {
+            let tag = discriminator["mapping"]
+                .as_object()
+                .and_then(|mapping| {
+                    let reference = format!("#/components/schemas/{variant_name}");
+                    mapping.iter().find(|(_, target)| **target == reference)
+                })
+                .map(|(tag, _)| tag.clone())
+                .unwrap_or_else(|| variant_name.to_owned());
+            let property = discriminator["propertyName"].as_str().unwrap();
+            payload[property] = Value::String(tag);
+        }
+        payload
+    }
+
+    fn example(&self, schema: &Value, full: bool, depth: usize) -> Value {
+        let (_, schema) = self.resolve(schema);
+        if schema.get("oneOf").is_some() {
+            return self.union(schema, 0, full, depth + 1);
+        }
+        if let Some(values) = schema.get("enum").and_then(Value::as_array) {
+            return values[0].clone();
+        }
+        match schema.get("type").and_then(Value::as_str) {
+            Some("object") => {
+                let full = full && depth < MAX_DEPTH;
+                let required: BTreeSet<&str> = schema
+                    .get("required")
+                    .and_then(Value::as_array)
+                    .map(|names| names.iter().filter_map(Value::as_str).collect())
+                    .unwrap_or_default();
+                let mut object = Map::new();
+                if let Some(properties) = schema.get("properties").and_then(Value::as_object) {
+                    for (key, property) in properties {
+                        if full || required.contains(key.as_str()) {
+                            object.insert(key.clone(), self.example(property, full, depth + 1));
+                        }
+                    }
+                }
+                if let Some(values) = schema.get("additionalProperties") {
+                    object.insert("key".to_owned(), self.example(values, full, depth + 1));
+                }
+                Value::Object(object)
+            }
+            Some("array") if depth < MAX_DEPTH => {
+                json!([self.example(&schema["items"], full, depth + 1)])
+            }
+            Some("array") => json!([]),
+            Some("string") => match schema.get("format").and_then(Value::as_str) {
+                Some("uuid") => json!("3fa85f64-5717-4562-b3fc-2c963f66afa6"),
+                _ => json!("string"),
+            },
+            Some("integer") => json!(1),
+            Some("number") => json!(1.5),
+            Some("boolean") => json!(true),
+            _ => json!({ "any": "value" }),
+        }
+    }
+}
+
+#[test]
+fn test_every_schema_round_trips() {
+    let spec = Spec::load();
+    let mut failures = Vec::new();
+    for name in spec.schemas().keys() {
+        let skipped = spec.discriminated(name);
+        for mut payload in spec.examples(name) {
+            let actual = match round_trip(name, &payload) {
+                Some(Ok(actual)) => actual,
+                Some(Err(e)) => {
+                    failures.push(format!("{name}: {e}\n  payload: {payload}"));
+                    continue;
+                }
+                None => {
+                    failures.push(format!("{name}: no model"));
+                    break;
+                }
+            };
+            // Outside its union a variant does not write the discriminator; the union does.
+            if let (Some(property), Some(object)) = (skipped, payload.as_object_mut()) {
+                object.remove(property);
+            }
+            if actual != payload {
+                failures.push(format!(
+                    "{name}: round trip changed the payload\n  expected: {payload}\n  actual:   {actual}"
+                ));
+            }
+        }
+    }
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
+
+#[test]
+fn test_every_model_has_a_schema() {
+    let spec = Spec::load();
+    let schemas: BTreeSet<&str> = spec.schemas().keys().map(String::as_str).collect();
+    let models: BTreeSet<&str> = MODELS.iter().copied().collect();
+    assert_eq!(
+        models.difference(&schemas).collect::<Vec<_>>(),
+        Vec::<&&str>::new(),
+        "models missing from the spec"
+    );
+    assert_eq!(
+        schemas.difference(&models).collect::<Vec<_>>(),
+        Vec::<&&str>::new(),
+        "schemas without a model"
+    );
+}
+
+#[test]
+fn test_every_reference_resolves() {
+    fn walk<'a>(value: &'a Value, references: &mut Vec<&'a str>) {
+        match value {
+            Value::Object(object) => {
+                if let Some(reference) = object.get("$ref").and_then(Value::as_str) {
+                    references.push(reference);
+                }
+                object.values().for_each(|value| walk(value, references));
+            }
+            Value::Array(values) => values.iter().for_each(|value| walk(value, references)),
+            _ => {}
+        }
+    }
+
+    let spec = Spec::load();
+    let mut references = Vec::new();
+    walk(&spec.document, &mut references);
+    assert!(!references.is_empty());
+    for reference in references {
+        spec.resolve(&json!({ "$ref": reference }));
+    }
+}
+
+#[test]
+fn test_spec_records_its_source() {
+    let spec = Spec::load();
+    let source =
+        std::fs::read_to_string(SOURCE).expect("spec/SOURCE records where the spec is from");
+    let field = |name: &str| {
+        source
+            .lines()
+            .find_map(|line| line.strip_prefix(name)?.trim_start().strip_prefix('='))
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .unwrap_or_else(|| panic!("spec/SOURCE has no {name}"))
+    };
+    assert!(field("url").starts_with("https://"));
+    assert_eq!(field("version"), spec.document["info"]["version"]);
+}
@@ -0,0 +1,66 @@
+//! Tests for environment presets and guards
+
+use noah_sdk::apis::configuration::{Configuration, Environment, EnvironmentError};
+use noah_sdk::apis::{payin_api, payout_api, utilities_api, Error};
+use noah_sdk::models::{FiatDepositSimulateRequest, SellRequest};
+
+#[test]
+fn test_environment_presets() {
+    let sandbox = Configuration::for_environment(Environment::Sandbox);
+    assert_eq!(sandbox.base_path, "https://api.sandbox.noah.com/v1");
+    assert_eq!(sandbox.environment(), Environment::Sandbox);
+    assert_eq!(Configuration::default().environment(), Environment::Sandbox);
+
+    let production = Configuration::for_environment(Environment::Production);
+    assert_eq!(production.base_path, "https://api.noah.com/v1");
+    assert!(production.environment().is_production());
+
+    let custom = Configuration::for_environment(Environment::Custom(
+        "http://localhost:8080/v1/".parse().unwrap(),
+    ));
+    assert_eq!(custom.base_path, "http://localhost:8080/v1");
+    assert!(matches!(custom.environment(), Environment::Custom(_)));
+}
+
+#[tokio::test]
+async fn test_simulate_deposit_refused_in_production() {
+    let config = Configuration::for_environment(Environment::Production);
+    let request = FiatDepositSimulateRequest::new(
+        "pm-123".to_string(),
+        "100.00".to_string(),
+        "USD".to_string(),
+    );
+
+    let result = payin_api::sandbox_fiat_deposit_simulate_post(&config, request, None).await;
+    match result {
+        Err(Error::Environment(EnvironmentError::SandboxOnlyOperation { operation })) => {
+            assert_eq!(operation, "sandbox_fiat_deposit_simulate_post")
+        }
+        other => panic!("unexpected result: {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_codes_checked_against_environment() {
+    let production = Configuration::for_environment(Environment::Production);
+    let request = SellRequest {
+        crypto_currency: "USDC_TEST".to_string(),
+        ..Default::default()
+    };
+    let result = payout_api::transactions_sell_post(&production, request, None).await;
+    assert!(matches!(
+        result,
+        Err(Error::Environment(EnvironmentError::SandboxCode { ref field, ref value }))
+            if field == "CryptoCurrency" && value == "USDC_TEST"
+    ));
+
+    let sandbox = Configuration::for_environment(Environment::Sandbox);
+    let result = utilities_api::channels_sell_get(
+        &sandbox, "BTC", None, None, None, None, None, None, None, None,
+    )
+    .await;
+    assert!(matches!(
+        result,
+        Err(Error::Environment(EnvironmentError::ProductionCode { ref value, .. })) if value == "BTC"
+    ));
+}
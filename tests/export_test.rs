@@ -0,0 +1,179 @@
+//! Tests for the transaction export
+
+use chrono::{DateTime, TimeZone, Utc};
+use noah_sdk::export::{ExportError, ExportFormat, ExportRow, TransactionExport};
+use noah_sdk::models::{self, SortDirection, Transaction, TransactionBreakdownItem};
+use noah_sdk::testing::{fixtures, MockNoah};
+
+fn september() -> (DateTime<Utc>, DateTime<Utc>) {
+    (
+        Utc.with_ymd_and_hms(2026, 9, 1, 0, 0, 0).unwrap(),
+        Utc.with_ymd_and_hms(2026, 10, 1, 0, 0, 0).unwrap(),
+    )
+}
+
+/// Stores a transaction created at each of `times`, oldest first, and returns them.
+fn history(noah: &MockNoah, times: &[&str]) -> Vec<Transaction> {
+    let transactions: Vec<_> = times
+        .iter()
+        .map(|created| Transaction {
+            created: created.to_string(),
+            ..fixtures::transaction()
+        })
+        .collect();
+    noah.state().transactions.extend(transactions.clone());
+    transactions
+}
+
+fn calls(noah: &MockNoah, operation: &str) -> usize {
+    noah.state()
+        .requests
+        .iter()
+        .filter(|request| request.operation == Some(operation))
+        .count()
+}
+
+#[tokio::test]
+async fn test_writes_the_range_as_csv() {
+    let noah = MockNoah::start().await.unwrap();
+    let mut transactions = history(
+        &noah,
+        &[
+            "2026-08-31T23:59:59Z",
+            "2026-09-01T00:00:00Z",
+            "2026-09-15T12:00:00+02:00",
+            "2026-09-30T23:59:59Z",
+            "2026-10-01T00:00:00Z",
+        ],
+    );
+    {
+        let mut state = noah.state();
+        let adjusted = &mut state.transactions[2];
+        adjusted.external_id = Some("payroll, \"September\"".to_owned());
+        adjusted.breakdown = Some(vec![
+            TransactionBreakdownItem::new("ChannelFee".into(), "0.10".into()),
+            TransactionBreakdownItem::new("ChannelFee".into(), "0.25".into()),
+            TransactionBreakdownItem::new("Remaining".into(), "91.65".into()),
+            TransactionBreakdownItem::new("Rounding".into(), "0.001".into()),
+        ]);
+        adjusted.adjustment_for = Some(Box::new(models::TransactionAdjustment::new(
+            transactions[1].id,
+            uuid::Uuid::nil(),
+            "ExchangeRateCorrection".into(),
+        )));
+        transactions[2] = adjusted.clone();
+    }
+
+    let (from, until) = september();
+    let mut csv = Vec::new();
+    let rows = TransactionExport::new(&noah.configuration(), from, until)
+        .page_size(2)
+        .write(ExportFormat::Csv, &mut csv)
+        .await
+        .unwrap();
+    assert_eq!(rows, 3);
+
+    let csv = String::from_utf8(csv).unwrap();
+    let lines: Vec<_> = csv.split_terminator("\r\n").collect();
+    assert_eq!(lines.len(), 4);
+    assert_eq!(lines[0], ExportRow::COLUMNS.join(","));
+    let ids: Vec<_> = lines[1..]
+        .iter()
+        .map(|line| line.split(',').next().unwrap())
+        .collect();
+    let expected: Vec<_> = transactions[1..4]
+        .iter()
+        .map(|t| t.id.to_string())
+        .collect();
+    assert_eq!(ids, expected);
+    assert_eq!(
+        lines[2],
+        format!(
+            "{},,2026-09-15T12:00:00+02:00,Pending,Out,OffNetwork,cust-1,\
+             \"payroll, \"\"September\"\"\",USDC_TEST,101.00,,92,1.00,0.92,EUR,,,\
+             0.35,,91.65,Rounding:0.001,{},{},ExchangeRateCorrection",
+            transactions[2].id,
+            transactions[1].id,
+            uuid::Uuid::nil()
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_json_lines_have_every_column() {
+    let noah = MockNoah::start().await.unwrap();
+    let transactions = history(&noah, &["2026-09-10T08:30:00Z"]);
+    {
+        let mut state = noah.state();
+        state.transactions[0].network_fee = Some("0.000000000000000001".to_owned());
+        // More digits than a Decimal holds
+        state.transactions[0].amount = Some("1234567890123456789012.1234567890".to_owned());
+    }
+
+    let (from, until) = september();
+    let export = TransactionExport::new(&noah.configuration(), from, until);
+    let mut jsonl = Vec::new();
+    export
+        .write(ExportFormat::JsonLines, &mut jsonl)
+        .await
+        .unwrap();
+
+    let jsonl = String::from_utf8(jsonl).unwrap();
+    assert_eq!(jsonl.lines().count(), 1);
+    let row: serde_json::Map<String, serde_json::Value> = serde_json::from_str(&jsonl).unwrap();
+    let mut keys: Vec<_> = row.keys().map(String::as_str).collect();
+    let mut columns = ExportRow::COLUMNS.to_vec();
+    keys.sort();
+    columns.sort();
+    assert_eq!(keys, columns);
+    assert_eq!(row["ID"], transactions[0].id.to_string());
+    assert_eq!(row["NetworkFee"], "0.000000000000000001");
+    assert_eq!(row["Amount"], "1234567890123456789012.1234567890");
+    assert_eq!(row["FiatAmount"], "92");
+    assert!(row["AdjustmentID"].is_null());
+    assert!(row["BreakdownOther"].is_null());
+
+    noah.state().transactions[0].amount = Some("1.01e2".to_owned());
+    let error = export
+        .write(ExportFormat::JsonLines, &mut Vec::new())
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(error, ExportError::InvalidValue { field: "Amount", ref value, .. } if value == "1.01e2"),
+        "{error}"
+    );
+}
+
+#[tokio::test]
+async fn test_stops_listing_past_the_range() {
+    let noah = MockNoah::start().await.unwrap();
+    history(
+        &noah,
+        &[
+            "2026-07-01T00:00:00Z",
+            "2026-08-01T00:00:00Z",
+            "2026-09-01T00:00:00Z",
+            "2026-09-02T00:00:00Z",
+            "2026-10-01T00:00:00Z",
+        ],
+    );
+
+    let (from, until) = september();
+    let export = TransactionExport::new(&noah.configuration(), from, until)
+        .sort_direction(SortDirection::Desc)
+        .page_size(1);
+    let mut rows = Vec::new();
+    assert_eq!(export.write(ExportFormat::Csv, &mut rows).await.unwrap(), 2);
+    // October, the two September transactions, then August ends the listing.
+    assert_eq!(calls(&noah, "transactions_get"), 4);
+
+    noah.state().transactions[3].amount = Some("12,50".to_owned());
+    let error = export
+        .write(ExportFormat::Csv, &mut Vec::new())
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(error, ExportError::InvalidValue { field: "Amount", ref value, .. } if value == "12,50"),
+        "{error}"
+    );
+}
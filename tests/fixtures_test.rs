@@ -0,0 +1,85 @@
+//! Tests for model fixtures and generators
+
+use noah_sdk::apis::utilities_api;
+use noah_sdk::models::{self, Channel, CheckoutSession, FiatDeposit, Transaction};
+use noah_sdk::testing::{fixtures, MockNoah};
+use proptest::prelude::*;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+fn round_trip<T: Serialize + DeserializeOwned>(value: &T) -> T {
+    serde_json::from_str(&serde_json::to_string(value).unwrap()).unwrap()
+}
+
+fn is_positive_amount(amount: &str) -> bool {
+    amount.parse::<f64>().is_ok_and(|amount| amount > 0.0)
+}
+
+#[tokio::test]
+async fn test_fixtures_are_served_by_the_mock() {
+    let noah = MockNoah::start().await.unwrap();
+    let config = noah.configuration();
+    let transaction = Transaction {
+        status: models::TransactionStatus::Settled,
+        ..fixtures::transaction()
+    };
+    {
+        let mut state = noah.state();
+        state.transactions.push(transaction.clone());
+        state.customers.push(models::Customer::Business(Box::new(
+            fixtures::business_customer(),
+        )));
+    }
+
+    let fetched =
+        utilities_api::transactions_transaction_id_get(&config, &transaction.id.to_string(), None)
+            .await
+            .unwrap();
+    assert_eq!(fetched, transaction);
+    let customer = utilities_api::customers_customer_id_get(&config, fixtures::CUSTOMER_ID)
+        .await
+        .unwrap();
+    assert!(
+        matches!(customer, models::Customer::Business(ref c) if c.registered_name == "Acme GmbH")
+    );
+
+    assert_ne!(fixtures::fiat_deposit().id, fixtures::fiat_deposit().id);
+    assert_eq!(
+        round_trip(&fixtures::checkout_session()).line_items.len(),
+        1
+    );
+    assert_eq!(round_trip(&fixtures::channel()).fiat_currency, "EUR");
+}
+
+proptest! {
+    #[test]
+    fn test_generated_payments_round_trip(
+        transaction in any::<Transaction>(),
+        deposit in any::<FiatDeposit>(),
+        session in any::<CheckoutSession>(),
+    ) {
+        prop_assert!(is_positive_amount(transaction.amount.as_deref().unwrap()));
+        prop_assert!(is_positive_amount(&deposit.fiat_amount));
+        prop_assert!(chrono::DateTime::parse_from_rfc3339(&session.created).is_ok());
+        prop_assert_eq!(round_trip(&transaction), transaction);
+        prop_assert_eq!(round_trip(&deposit), deposit);
+        prop_assert_eq!(round_trip(&session), session);
+    }
+
+    #[test]
+    fn test_generated_channels_and_customers_round_trip(
+        channel in any::<Channel>(),
+        customer in any::<models::BusinessCustomer>(),
+    ) {
+        let min: f64 = channel.limits.min_limit.parse().unwrap();
+        let max: f64 = channel.limits.max_limit.as_deref().unwrap().parse().unwrap();
+        prop_assert!(min < max);
+        prop_assert!(is_positive_amount(&channel.rate));
+        prop_assert_eq!(
+            customer.registration_country.as_deref(),
+            customer.registered_address.as_ref().map(|a| a.country.as_str())
+        );
+        prop_assert_eq!(round_trip(&channel), channel);
+        prop_assert_eq!(round_trip(&customer), customer);
+    }
+}
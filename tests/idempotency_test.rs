@@ -0,0 +1,77 @@
+//! Tests for idempotency key management
+
+use std::cell::Cell;
+use std::error::Error as _;
+use std::io;
+
+use noah_sdk::idempotency::{FileStore, IdempotencyManager, InMemoryStore};
+use noah_sdk::models::SellRequest;
+
+#[test]
+fn test_nonce_is_stable_per_operation() {
+    let idempotency = IdempotencyManager::new(InMemoryStore::new());
+
+    let first = idempotency.nonce("payout-1").unwrap();
+    assert_eq!(idempotency.nonce("payout-1").unwrap(), first);
+    assert_ne!(idempotency.nonce("payout-2").unwrap(), first);
+
+    let request = idempotency
+        .apply("payout-1", SellRequest::default())
+        .unwrap();
+    assert_eq!(request.nonce, first);
+}
+
+#[tokio::test]
+async fn test_run_returns_stored_result_without_calling_again() {
+    let idempotency = IdempotencyManager::new(InMemoryStore::new());
+    let calls = Cell::new(0);
+
+    let failed: Result<String, _> = idempotency
+        .run("payout-1", |_| async {
+            calls.set(calls.get() + 1);
+            Err("timeout")
+        })
+        .await;
+    assert!(failed.is_err());
+
+    for _ in 0..2 {
+        let nonce: String = idempotency
+            .run("payout-1", |nonce| async {
+                calls.set(calls.get() + 1);
+                Ok::<_, &str>(nonce)
+            })
+            .await
+            .unwrap();
+        assert_eq!(nonce, idempotency.nonce("payout-1").unwrap());
+    }
+    assert_eq!(calls.get(), 2);
+
+    let error = idempotency
+        .run("payout-2", |_| async {
+            Err::<String, _>(io::Error::other("connection reset"))
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(error.source().unwrap().to_string(), "connection reset");
+}
+
+#[test]
+fn test_file_store_survives_restart() {
+    let path = std::env::temp_dir().join(format!("noah-idempotency-{}.json", uuid::Uuid::new_v4()));
+
+    let nonce = {
+        let idempotency = IdempotencyManager::new(FileStore::new(&path));
+        let nonce = idempotency.nonce("payout-1").unwrap();
+        idempotency.complete("payout-1", &"tx-1").unwrap();
+        nonce
+    };
+
+    let idempotency = IdempotencyManager::new(FileStore::new(&path));
+    assert_eq!(idempotency.nonce("payout-1").unwrap(), nonce);
+    assert_eq!(
+        idempotency.result::<String>("payout-1").unwrap().as_deref(),
+        Some("tx-1")
+    );
+
+    std::fs::remove_file(&path).unwrap();
+}
@@ -1,6 +1,6 @@
 //! Unit tests for models
 
-use noah_sdk::models::BalanceResponse;
+use noah_sdk::models::{BalanceResponse, PaymentMethodDisplayDetails};
 
 #[test]
 fn test_balance_response_deserialization() {
@@ -19,3 +19,27 @@ fn test_balance_response_deserialization() {
     assert_eq!(balance.crypto_currency, "USDC");
     assert_eq!(balance.account_type, "Current");
 }
+
+#[test]
+fn test_tagged_display_details_round_trip() {
+    let json = r#"
+    {
+        "Type": "FiatPaymentMethodBankDisplay",
+        "AccountNumber": "12345678",
+        "BankCode": "BANKGB2L"
+    }
+    "#;
+
+    let details: PaymentMethodDisplayDetails = serde_json::from_str(json).unwrap();
+    let PaymentMethodDisplayDetails::FiatPaymentMethodBankDisplay(ref bank) = details else {
+        panic!("expected bank display details");
+    };
+    assert_eq!(bank.account_number.as_deref(), Some("12345678"));
+
+    let serialized = serde_json::to_string(&details).unwrap();
+    assert_eq!(serialized.matches("\"Type\"").count(), 1);
+    assert_eq!(
+        serde_json::from_str::<PaymentMethodDisplayDetails>(&serialized).unwrap(),
+        details
+    );
+}
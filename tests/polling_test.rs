@@ -0,0 +1,199 @@
+//! Tests for transaction status polling
+
+use std::time::Duration;
+
+use futures_util::StreamExt;
+use noah_sdk::apis::Error;
+use noah_sdk::models::{Transaction, TransactionStatus};
+use noah_sdk::polling::{wait_for_transaction, PollError, PollPolicy};
+use noah_sdk::testing::{fixtures, Failure, MockNoah, StatusCode};
+
+fn policy(timeout: Duration) -> PollPolicy {
+    PollPolicy::new(timeout)
+        .initial_interval(Duration::from_millis(10))
+        .max_interval(Duration::from_millis(40))
+}
+
+async fn pending(noah: &MockNoah) -> Transaction {
+    let transaction = fixtures::transaction();
+    noah.state().transactions.push(transaction.clone());
+    transaction
+}
+
+fn set_status(noah: &MockNoah, id: uuid::Uuid, status: TransactionStatus) {
+    let mut state = noah.state();
+    let transaction = state.transactions.iter_mut().find(|t| t.id == id).unwrap();
+    transaction.status = status;
+}
+
+#[test]
+fn test_interval_backs_off() {
+    let policy = PollPolicy::new(Duration::from_secs(60))
+        .initial_interval(Duration::from_secs(1))
+        .max_interval(Duration::from_secs(5));
+    let intervals: Vec<_> = (1..=5)
+        .map(|poll| policy.interval(poll).as_secs())
+        .collect();
+    assert_eq!(intervals, [1, 2, 4, 5, 5]);
+}
+
+#[tokio::test]
+async fn test_yields_status_changes_until_final() {
+    let noah = MockNoah::start().await.unwrap();
+    let transaction = pending(&noah).await;
+
+    let mut updates = wait_for_transaction(
+        &noah.configuration(),
+        &transaction.id.to_string(),
+        policy(Duration::from_secs(5)),
+    );
+    let first = updates.next().await.unwrap().unwrap();
+    assert_eq!(first.status, TransactionStatus::Pending);
+
+    let settle = async {
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        set_status(&noah, transaction.id, TransactionStatus::Settled);
+    };
+    let (last, ()) = tokio::join!(updates.next(), settle);
+    assert_eq!(last.unwrap().unwrap().status, TransactionStatus::Settled);
+    assert!(updates.next().await.is_none());
+
+    // Pending was only yielded once, however many polls saw it
+    let polls = noah
+        .state()
+        .requests
+        .iter()
+        .filter(|r| r.operation == Some("transactions_transaction_id_get"))
+        .count();
+    assert!(polls > 2, "{polls} polls");
+}
+
+#[tokio::test]
+async fn test_stops_on_timeout_or_error() {
+    let noah = MockNoah::start().await.unwrap();
+    let transaction = pending(&noah).await;
+
+    let updates: Vec<_> = wait_for_transaction(
+        &noah.configuration(),
+        &transaction.id.to_string(),
+        policy(Duration::from_millis(100)),
+    )
+    .collect()
+    .await;
+    assert_eq!(updates.len(), 2);
+    assert!(updates[0].is_ok());
+    assert!(matches!(
+        updates[1],
+        Err(PollError::TimedOut {
+            status: Some(TransactionStatus::Pending)
+        })
+    ));
+
+    let mut updates = wait_for_transaction(
+        &noah.configuration(),
+        &uuid::Uuid::new_v4().to_string(),
+        policy(Duration::from_secs(5)),
+    );
+    match updates.next().await.unwrap() {
+        Err(PollError::Api(Error::ResponseError(response))) => {
+            assert_eq!(response.status, StatusCode::NOT_FOUND);
+        }
+        other => panic!("unexpected {other:?}"),
+    }
+    assert!(updates.next().await.is_none());
+}
+
+#[tokio::test]
+async fn test_polls_through_transient_errors() {
+    let noah = MockNoah::start().await.unwrap();
+    let transaction = pending(&noah).await;
+    noah.inject(
+        Failure::status(
+            "transactions_transaction_id_get",
+            StatusCode::SERVICE_UNAVAILABLE,
+        )
+        .times(2),
+    );
+    noah.inject(Failure::status(
+        "transactions_transaction_id_get",
+        StatusCode::TOO_MANY_REQUESTS,
+    ));
+
+    let mut updates = wait_for_transaction(
+        &noah.configuration(),
+        &transaction.id.to_string(),
+        policy(Duration::from_secs(5)),
+    );
+    let first = updates.next().await.unwrap().unwrap();
+    assert_eq!(first.status, TransactionStatus::Pending);
+    let polls = noah
+        .state()
+        .requests
+        .iter()
+        .filter(|r| r.operation == Some("transactions_transaction_id_get"))
+        .count();
+    assert_eq!(polls, 4);
+
+    // Errors until the timeout end in a timeout, with the last status seen
+    noah.inject(
+        Failure::status("transactions_transaction_id_get", StatusCode::BAD_GATEWAY).times(1000),
+    );
+    let mut updates = wait_for_transaction(
+        &noah.configuration(),
+        &transaction.id.to_string(),
+        policy(Duration::from_millis(100)),
+    );
+    assert!(matches!(
+        updates.next().await.unwrap(),
+        Err(PollError::TimedOut { status: None })
+    ));
+    assert!(updates.next().await.is_none());
+}
+
+#[tokio::test]
+async fn test_polls_once_more_at_the_timeout() {
+    let noah = MockNoah::start().await.unwrap();
+    let transaction = pending(&noah).await;
+    let policy = PollPolicy::new(Duration::from_millis(200))
+        .initial_interval(Duration::from_secs(30))
+        .max_interval(Duration::from_secs(30));
+
+    // The wait is cut short at the timeout, where a last poll sees the status
+    let started = std::time::Instant::now();
+    let mut updates = wait_for_transaction(
+        &noah.configuration(),
+        &transaction.id.to_string(),
+        policy.clone(),
+    );
+    assert!(updates.next().await.unwrap().is_ok());
+    set_status(&noah, transaction.id, TransactionStatus::Settled);
+    let last = updates.next().await.unwrap().unwrap();
+    assert_eq!(last.status, TransactionStatus::Settled);
+    assert!(started.elapsed() >= Duration::from_millis(200));
+    assert!(started.elapsed() < Duration::from_secs(5));
+
+    // Without a change, it times out after that poll rather than a full interval early
+    let transaction = pending(&noah).await;
+    let started = std::time::Instant::now();
+    let updates: Vec<_> =
+        wait_for_transaction(&noah.configuration(), &transaction.id.to_string(), policy)
+            .collect()
+            .await;
+    assert!(matches!(
+        updates[..],
+        [
+            Ok(_),
+            Err(PollError::TimedOut {
+                status: Some(TransactionStatus::Pending)
+            })
+        ]
+    ));
+    assert!(started.elapsed() >= Duration::from_millis(200));
+    let polls = noah
+        .state()
+        .requests
+        .iter()
+        .filter(|r| r.operation == Some("transactions_transaction_id_get"))
+        .count();
+    assert_eq!(polls, 4);
+}
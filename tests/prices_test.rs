@@ -0,0 +1,191 @@
+//! Tests for price comparison
+
+use noah_sdk::models::{FeeBreakdownItem, PriceItem};
+use noah_sdk::prices::{CryptoSide, FeeMismatch, Price, PriceComparison, PriceError};
+use noah_sdk::testing::MockNoah;
+use noah_sdk::Decimal;
+
+fn item(category: &str, rate: &str, total_fee: &str, fees: &[(&str, &str)]) -> PriceItem {
+    PriceItem {
+        payment_method_category: category.to_owned(),
+        rate: rate.to_owned(),
+        updated_at: "2026-10-19T12:00:00Z".to_owned(),
+        total_fee: Some(total_fee.to_owned()),
+        source_amount: Some("100".to_owned()),
+        crypto_fee_breakdown: Some(
+            fees.iter()
+                .map(|(kind, amount)| FeeBreakdownItem::new(kind.to_string(), amount.to_string()))
+                .collect(),
+        ),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_price_nets_out_and_reconciles_fees() {
+    let price = Price::new(
+        PriceItem {
+            business_fee: Some("0.50".to_owned()),
+            ..item(
+                "Bank",
+                "0.92",
+                "1.50",
+                &[("ChannelFee", "1.00"), ("BusinessFee", "0.50")],
+            )
+        },
+        CryptoSide::Source,
+    )
+    .unwrap();
+    assert_eq!(price.net_destination_amount(), Some(Decimal::new(9062, 2)));
+    assert_eq!(price.effective_rate(), Some(Decimal::new(9062, 4)));
+    assert_eq!(price.reconcile(), Ok(()));
+
+    let short = Price::new(
+        item("Bank", "0.92", "1.50", &[("ChannelFee", "1.00")]),
+        CryptoSide::Source,
+    )
+    .unwrap();
+    assert_eq!(
+        short.reconcile(),
+        Err(FeeMismatch::TotalFee {
+            total_fee: Decimal::new(150, 2),
+            breakdown: Decimal::new(100, 2),
+        })
+    );
+
+    let business = Price::new(
+        PriceItem {
+            business_fee: Some("0.75".to_owned()),
+            ..item(
+                "Bank",
+                "0.92",
+                "1.50",
+                &[("ChannelFee", "1.00"), ("BusinessFee", "0.50")],
+            )
+        },
+        CryptoSide::Source,
+    )
+    .unwrap();
+    assert_eq!(
+        business.reconcile().unwrap_err().to_string(),
+        "business fee breakdown sums to 0.50, not BusinessFee 0.75"
+    );
+}
+
+#[test]
+fn test_comparison_ranks_by_net_amount() {
+    // Card has the better rate but the higher fee: 97 * 0.93 = 90.21 against 99 * 0.92 = 91.08.
+    let comparison = PriceComparison::new(
+        vec![
+            item("Card", "0.93", "3", &[]),
+            item("Bank", "0.92", "1", &[]),
+            PriceItem {
+                source_amount: None,
+                ..item("Identifier", "0.95", "0", &[])
+            },
+        ],
+        CryptoSide::Source,
+    )
+    .unwrap();
+    let categories: Vec<_> = comparison
+        .prices()
+        .iter()
+        .map(|price| price.payment_method_category())
+        .collect();
+    assert_eq!(categories, ["Bank", "Card", "Identifier"]);
+    let card = comparison.get("Card").unwrap();
+    assert_eq!(comparison.shortfall(card), Some(Decimal::new(87, 2)));
+    assert_eq!(
+        comparison.shortfall(comparison.get("Identifier").unwrap()),
+        None
+    );
+
+    let error = PriceComparison::new(vec![item("Bank", "0.92", "free", &[])], CryptoSide::Source)
+        .unwrap_err();
+    assert!(
+        matches!(
+            error,
+            PriceError::InvalidValue {
+                field: "TotalFee",
+                ..
+            }
+        ),
+        "{error}"
+    );
+}
+
+#[test]
+fn test_pay_in_fees_are_taken_after_converting() {
+    // 100 EUR buys 108.70 USDC, of which the 1.50 USDC fee is taken.
+    let items = vec![
+        item("Card", "1.087", "2.50", &[]),
+        item("Bank", "1.087", "1.50", &[]),
+    ];
+    let comparison = PriceComparison::new(items.clone(), CryptoSide::Destination).unwrap();
+    let bank = comparison.best().unwrap();
+    assert_eq!(bank.payment_method_category(), "Bank");
+    assert_eq!(bank.net_destination_amount(), Some(Decimal::new(10720, 2)));
+    assert_eq!(bank.effective_rate(), Some(Decimal::new(1072, 3)));
+    let card = comparison.get("Card").unwrap();
+    assert_eq!(comparison.shortfall(card), Some(Decimal::ONE));
+
+    // Taking the fee in EUR instead would make it count 1.087 times as much.
+    let sell = Price::new(items[1].clone(), CryptoSide::Source).unwrap();
+    assert_eq!(
+        sell.net_destination_amount(),
+        Some(Decimal::new(107_0695, 4))
+    );
+}
+
+#[tokio::test]
+async fn test_fetch_prices_each_category() {
+    let noah = MockNoah::start().await.unwrap();
+    let config = noah.configuration();
+
+    let comparison = PriceComparison::fetch(
+        &config,
+        "USDC_TEST",
+        "EUR",
+        CryptoSide::Source,
+        "100",
+        &["Card", "Bank"],
+        None,
+    )
+    .await
+    .unwrap();
+    assert_eq!(comparison.prices().len(), 2);
+    let best = comparison.best().unwrap();
+    assert_eq!(best.net_destination_amount(), Some(Decimal::new(92, 0)));
+    assert_eq!(
+        comparison.shortfall(comparison.get("Card").unwrap()),
+        Some(Decimal::ZERO)
+    );
+
+    let categories: Vec<_> = noah
+        .state()
+        .requests
+        .iter()
+        .filter(|request| request.operation == Some("prices_get"))
+        .filter_map(|request| {
+            request
+                .query
+                .iter()
+                .find(|(name, _)| name == "PaymentMethodCategory")
+                .map(|(_, value)| value.clone())
+        })
+        .collect();
+    assert_eq!(categories, ["Card", "Bank"]);
+
+    let error = PriceComparison::fetch(
+        &config,
+        "USDC_TEST",
+        "JPY",
+        CryptoSide::Source,
+        "100",
+        &[],
+        None,
+    )
+    .await
+    .unwrap_err();
+    assert!(matches!(error, PriceError::Api(_)), "{error}");
+}
@@ -0,0 +1,77 @@
+//! Tests for sell quotes
+
+use std::time::Duration;
+
+use noah_sdk::models::{self, PrepareSellResponse};
+use noah_sdk::sell::{Quote, SellError, SellFlow};
+use noah_sdk::testing::{fixtures, MockNoah};
+use noah_sdk::Decimal;
+
+fn prepared(estimate: &str) -> PrepareSellResponse {
+    PrepareSellResponse {
+        total_fee: "1.00".to_string(),
+        crypto_amount_estimate: estimate.to_string(),
+        crypto_authorized_amount: "102.00".to_string(),
+        form_session_id: uuid::Uuid::new_v4(),
+    }
+}
+
+#[test]
+fn test_quote_measures_slippage_against_the_channel_rate() {
+    let channel = models::Channel {
+        rate: "0.92".to_string(),
+        ..fixtures::channel()
+    };
+
+    let quote = Quote::new(channel.clone(), "USDC_TEST", "92", prepared("100")).unwrap();
+    assert_eq!(quote.rate(), Decimal::new(92, 2));
+    assert_eq!(quote.slippage_bps(quote.quoted_rate()), Decimal::ZERO);
+    assert_eq!(quote.total_fee(), Decimal::new(1, 0));
+
+    // One more USDC for the same euros is a worse rate
+    let slipped = Quote::new(channel.clone(), "USDC_TEST", "92", prepared("101")).unwrap();
+    let rate = slipped.quoted_rate();
+    assert_eq!(slipped.slippage_bps(rate).to_string(), "99.01");
+    assert!(slipped.within_slippage(100, rate));
+    assert!(!slipped.within_slippage(50, rate));
+    // The market fell since quoting, so the quote is now better than the channel
+    assert!(slipped.within_slippage(0, Decimal::new(90, 2)));
+    let improved = Quote::new(channel.clone(), "USDC_TEST", "92", prepared("99")).unwrap();
+    assert!(improved.slippage_bps(rate).is_sign_negative());
+    assert!(improved.within_slippage(0, rate));
+
+    let request = slipped.sell_request("nonce-1");
+    assert_eq!(request.fiat_amount, "92");
+    assert_eq!(request.crypto_authorized_amount, "102.00");
+    assert_eq!(request.form_session_id, slipped.prepared().form_session_id);
+    assert_eq!(request.nonce, "nonce-1");
+
+    let error = Quote::new(channel, "USDC_TEST", "92", prepared("0")).unwrap_err();
+    assert!(matches!(
+        error,
+        SellError::InvalidValue {
+            field: "CryptoAmountEstimate",
+            ..
+        }
+    ));
+}
+
+#[tokio::test]
+async fn test_stale_quotes_are_not_executed() {
+    let noah = MockNoah::start().await.unwrap();
+    let flow = SellFlow::new(&noah.configuration(), "USDC_TEST", "92")
+        .fiat_currency("EUR")
+        .max_quote_age(Duration::from_millis(50));
+
+    let quote = flow.quote().await.unwrap();
+    assert!(!quote.is_stale(Duration::from_secs(60)));
+    tokio::time::sleep(Duration::from_millis(60)).await;
+    assert!(quote.is_stale(Duration::from_millis(50)));
+    let error = flow.execute(quote).await.unwrap_err();
+    assert!(matches!(error, SellError::StaleQuote { .. }), "{error}");
+    assert!(noah.state().transactions.is_empty());
+
+    let receipt = flow.execute(flow.quote().await.unwrap()).await.unwrap();
+    assert_eq!(receipt.quote.fiat_amount(), "92");
+    assert_eq!(noah.state().transactions.len(), 1);
+}
@@ -0,0 +1,141 @@
+//! Tests for the client-side rate limiter
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use noah_sdk::apis::configuration::{Configuration, RequestOptions, RetryPolicy};
+use noah_sdk::apis::{utilities_api, Error};
+use noah_sdk::rate_limit::{RateLimit, RateLimiter};
+use noah_sdk::testing::{Failure, MockNoah, StatusCode};
+
+fn limited(noah: &MockNoah, limiter: RateLimiter) -> Configuration {
+    Configuration {
+        rate_limiter: Some(Arc::new(limiter)),
+        ..noah.configuration()
+    }
+}
+
+#[tokio::test]
+async fn test_global_limit_spaces_requests() {
+    let noah = MockNoah::start().await.unwrap();
+    let config = limited(
+        &noah,
+        RateLimiter::new().global(RateLimit::per_second(10).burst(2)),
+    );
+
+    let started = Instant::now();
+    for _ in 0..5 {
+        utilities_api::balances_get(&config, None, None, None)
+            .await
+            .unwrap();
+    }
+    // Two requests go out at once, the other three wait 100ms each.
+    let elapsed = started.elapsed();
+    assert!(elapsed >= Duration::from_millis(280), "{elapsed:?}");
+    assert!(elapsed < Duration::from_secs(2), "{elapsed:?}");
+}
+
+#[tokio::test]
+async fn test_groups_queue_fairly_across_clones() {
+    let noah = MockNoah::start().await.unwrap();
+    let config = limited(
+        &noah,
+        RateLimiter::new().group(
+            "listings",
+            &["transactions_get", "customers_get"],
+            RateLimit::new(1, Duration::from_millis(300)),
+        ),
+    );
+    let clone = config.clone();
+    let order = Mutex::new(Vec::new());
+    let list = |config: &Configuration, n: usize| {
+        let config = config.clone();
+        let order = &order;
+        async move {
+            utilities_api::transactions_get(&config, None, None, None, None)
+                .await
+                .unwrap();
+            order.lock().unwrap().push(n);
+        }
+    };
+
+    let started = Instant::now();
+    tokio::join!(list(&config, 0), list(&clone, 1), list(&config, 2));
+    assert!(started.elapsed() >= Duration::from_millis(580));
+    assert_eq!(*order.lock().unwrap(), [0, 1, 2]);
+
+    // Endpoints outside the group are not held back.
+    let started = Instant::now();
+    for _ in 0..3 {
+        utilities_api::balances_get(&clone, None, None, None)
+            .await
+            .unwrap();
+    }
+    assert!(started.elapsed() < Duration::from_millis(250));
+}
+
+#[tokio::test]
+async fn test_pauses_for_retry_after() {
+    let noah = MockNoah::start().await.unwrap();
+    let mut config = limited(&noah, RateLimiter::new().global(RateLimit::per_second(100)));
+    config.retry = RetryPolicy {
+        initial_backoff: Duration::from_millis(10),
+        ..RetryPolicy::new(1)
+    };
+
+    noah.inject(
+        Failure::status("balances_get", StatusCode::TOO_MANY_REQUESTS).header("Retry-After", "1"),
+    );
+    let started = Instant::now();
+    utilities_api::balances_get(&config, None, None, None)
+        .await
+        .unwrap();
+    assert!(started.elapsed() >= Duration::from_millis(950));
+
+    noah.inject(
+        Failure::status("balances_get", StatusCode::TOO_MANY_REQUESTS).header("Retry-After", "5"),
+    );
+    let hurried =
+        config.with_request_options(RequestOptions::new().deadline(Duration::from_millis(300)));
+    let started = Instant::now();
+    let error = utilities_api::balances_get(&hurried, None, None, None)
+        .await
+        .unwrap_err();
+    assert!(matches!(error, Error::DeadlineExceeded), "{error}");
+    assert!(started.elapsed() < Duration::from_secs(1));
+}
+
+#[tokio::test]
+async fn test_degenerate_limits_do_not_stall() {
+    assert_eq!(
+        RateLimit::per_second(5).burst(0),
+        RateLimit::per_second(5).burst(1)
+    );
+    assert_eq!(
+        RateLimit::new(0, Duration::from_secs(1)),
+        RateLimit::new(1, Duration::from_secs(1))
+    );
+
+    let noah = MockNoah::start().await.unwrap();
+    let config = limited(
+        &noah,
+        RateLimiter::new()
+            .global(RateLimit::new(0, Duration::ZERO).burst(0))
+            .group(
+                "balances",
+                &["balances_get"],
+                RateLimit::new(3, Duration::ZERO),
+            ),
+    );
+    // A zero period refills the bucket at once.
+    let requests = async {
+        for _ in 0..20 {
+            utilities_api::balances_get(&config, None, None, None)
+                .await
+                .unwrap();
+        }
+    };
+    tokio::time::timeout(Duration::from_secs(5), requests)
+        .await
+        .expect("requests went through");
+}
@@ -0,0 +1,87 @@
+//! Tests for secret and PII redaction
+
+use std::sync::Mutex;
+
+use noah_sdk::apis::configuration::{ApiKey, Configuration};
+use noah_sdk::models::{self, CustomerIdentity, SumSubToken};
+use noah_sdk::redact::{self, Secret};
+use noah_sdk::testing::fixtures;
+
+/// Held while formatting, since `set_unredacted` applies to the whole process.
+static REDACTION: Mutex<()> = Mutex::new(());
+
+#[test]
+fn test_debug_output_is_redacted() {
+    let _redaction = REDACTION.lock().unwrap();
+    let config = Configuration {
+        api_key: Some(ApiKey {
+            prefix: None,
+            key: "super-secret-key".into(),
+        }),
+        bearer_access_token: Some("jwt-token".into()),
+        ..Default::default()
+    };
+    let identity = CustomerIdentity::new(
+        "US".to_string(),
+        "123-45-6789".to_string(),
+        "TaxID".to_string(),
+    );
+    let token = SumSubToken::new(Default::default(), "sumsub-token".to_string());
+
+    let debug = format!("{config:?} {identity:?} {token:?}");
+    for secret in [
+        "super-secret-key",
+        "jwt-token",
+        "123-45-6789",
+        "sumsub-token",
+    ] {
+        assert!(!debug.contains(secret), "{secret} leaked: {debug}");
+    }
+    assert!(debug.contains("[REDACTED]"));
+    assert!(debug.contains("issuing_country: \"US\""));
+
+    redact::set_unredacted(true);
+    let debug = format!("{identity:?}");
+    redact::set_unredacted(false);
+    assert!(debug.contains("123-45-6789"));
+}
+
+#[test]
+fn test_customer_names_and_addresses_are_redacted() {
+    let _redaction = REDACTION.lock().unwrap();
+    let customer = models::IndividualCustomer {
+        full_name: Box::new(models::FullName {
+            first_name: "Jane".to_owned(),
+            middle_name: Some("Quinn".to_owned()),
+            last_name: "Doe".to_owned(),
+        }),
+        primary_residence: Box::new(models::StreetAddress {
+            street: "Friedrichstraße 68".to_owned(),
+            street2: Some("Hinterhaus".to_owned()),
+            post_code: "10117".to_owned(),
+            ..*fixtures::individual_customer().primary_residence
+        }),
+        ..fixtures::individual_customer()
+    };
+
+    let debug = format!("{customer:?}");
+    for pii in [
+        "Jane",
+        "Quinn",
+        "Doe",
+        "Friedrichstraße",
+        "Hinterhaus",
+        "10117",
+        "1990-01-01",
+    ] {
+        assert!(!debug.contains(pii), "{pii} leaked: {debug}");
+    }
+    assert!(debug.contains("city: \"Berlin\""));
+}
+
+#[test]
+fn test_secret_serializes_transparently() {
+    let secret: Secret<String> = serde_json::from_str("\"value\"").unwrap();
+    assert_eq!(secret.expose(), "value");
+    assert_eq!(serde_json::to_string(&secret).unwrap(), "\"value\"");
+}
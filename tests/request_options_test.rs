@@ -0,0 +1,87 @@
+//! Tests for per-request options
+
+use std::time::{Duration, Instant};
+
+use noah_sdk::apis::configuration::{Configuration, Environment, RequestOptions};
+use noah_sdk::apis::{utilities_api, Error};
+use reqwest::header::{HeaderName, HeaderValue};
+
+fn local_config(server: &mockito::Server) -> Configuration {
+    Configuration::for_environment(Environment::Custom(server.url().parse().unwrap()))
+}
+
+#[tokio::test]
+async fn test_extra_headers_are_sent() {
+    let mut server = mockito::Server::new_async().await;
+    let mock = server
+        .mock("GET", "/balances")
+        .match_header("x-request-id", "checkout-42")
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"Items": []}"#)
+        .create_async()
+        .await;
+
+    let options = RequestOptions::new().header(
+        HeaderName::from_static("x-request-id"),
+        HeaderValue::from_static("checkout-42"),
+    );
+    let config = local_config(&server).with_request_options(options);
+    utilities_api::balances_get(&config, None, None, None)
+        .await
+        .unwrap();
+    mock.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_deadline_covers_retries_and_slow_bodies() {
+    let mut server = mockito::Server::new_async().await;
+    server
+        .mock("GET", "/balances")
+        .with_header("content-type", "application/json")
+        .with_chunked_body(|w| {
+            std::thread::sleep(Duration::from_millis(500));
+            w.write_all(br#"{"Items": []}"#)
+        })
+        .create_async()
+        .await;
+
+    let mut config = local_config(&server);
+    config.retry.max_retries = 5;
+    let config =
+        config.with_request_options(RequestOptions::new().deadline(Duration::from_millis(100)));
+
+    let started = Instant::now();
+    let result = utilities_api::balances_get(&config, None, None, None).await;
+    assert!(matches!(result, Err(Error::DeadlineExceeded)), "{result:?}");
+    assert!(started.elapsed() < Duration::from_millis(450));
+}
+
+#[tokio::test]
+async fn test_expired_deadline_fails_without_sending() {
+    let config = Configuration::default()
+        .with_request_options(RequestOptions::new().deadline(Duration::ZERO));
+    let result = utilities_api::balances_get(&config, None, None, None).await;
+    assert!(matches!(result, Err(Error::DeadlineExceeded)));
+}
+
+#[tokio::test]
+async fn test_deadline_starts_with_each_call() {
+    let mut server = mockito::Server::new_async().await;
+    server
+        .mock("GET", "/balances")
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"Items": []}"#)
+        .expect(2)
+        .create_async()
+        .await;
+
+    let config = local_config(&server)
+        .with_request_options(RequestOptions::new().deadline(Duration::from_millis(500)));
+    utilities_api::balances_get(&config, None, None, None)
+        .await
+        .unwrap();
+    tokio::time::sleep(Duration::from_millis(600)).await;
+    utilities_api::balances_get(&config, None, None, None)
+        .await
+        .unwrap();
+}
@@ -0,0 +1,176 @@
+//! Tests for the sell orchestration
+
+use std::time::Duration;
+
+use noah_sdk::models::{self, TransactionStatus};
+use noah_sdk::polling::PollPolicy;
+use noah_sdk::sell::{SellError, SellFlow};
+use noah_sdk::testing::{MockNoah, Simulator};
+use noah_sdk::Decimal;
+use serde_json::json;
+
+fn requests(noah: &MockNoah, operation: &str) -> Vec<Option<serde_json::Value>> {
+    noah.state()
+        .requests
+        .iter()
+        .filter(|request| request.operation == Some(operation))
+        .map(|request| request.body.clone())
+        .collect()
+}
+
+fn eur_channel(noah: &MockNoah) -> models::Channel {
+    let state = noah.state();
+    let channel = state.channels.iter().find(|c| c.fiat_currency == "EUR");
+    channel.unwrap().clone()
+}
+
+#[tokio::test]
+async fn test_sells_through_the_cheapest_channel_and_waits() {
+    let noah = MockNoah::start().await.unwrap();
+    {
+        let mut state = noah.state();
+        let eur = state
+            .channels
+            .iter_mut()
+            .find(|c| c.fiat_currency == "EUR")
+            .unwrap();
+        eur.form_schema = Some(Box::new(models::FormSchema {
+            r#type: "object".to_string(),
+            properties: [
+                ("IBAN".to_string(), json!({ "type": "string" })),
+                (
+                    "AccountType".to_string(),
+                    json!({ "type": "string", "default": "Checking" }),
+                ),
+            ]
+            .into(),
+            required: Some(vec!["IBAN".to_string(), "AccountType".to_string()]),
+            ..Default::default()
+        }));
+        // A second EUR channel with a fee loses to the free one
+        let mut expensive = eur.clone();
+        expensive.id = uuid::Uuid::new_v4().to_string();
+        expensive.calculated = Some(Box::new(models::ChannelCalculated::new("2.5".into())));
+        state.channels.push(expensive);
+    }
+    let eur = eur_channel(&noah);
+    let simulator = Simulator::mock(&noah);
+
+    let flow = SellFlow::new(&noah.configuration(), "USDC_TEST", "92")
+        .fiat_currency("EUR")
+        .form_field("IBAN", "DE89370400440532013000")
+        .max_fee(Decimal::new(1, 0))
+        .max_slippage_bps(10)
+        .wait_for_settlement(
+            PollPolicy::new(Duration::from_secs(5)).initial_interval(Duration::from_millis(10)),
+        );
+    let settle = async {
+        loop {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            let id = noah.state().transactions.first().map(|t| t.id.to_string());
+            if let Some(id) = id {
+                return simulator.settle_transaction(&id).await.unwrap();
+            }
+        }
+    };
+    let (receipt, settled) = tokio::join!(flow.run(), settle);
+    let receipt = receipt.unwrap();
+
+    assert_eq!(receipt.quote.channel().id, eur.id);
+    assert_eq!(receipt.quote.prepared().total_fee, "1.00");
+    assert_eq!(receipt.transaction.status, TransactionStatus::Settled);
+    assert_eq!(receipt.transaction.id, settled.id);
+    let prepared = requests(&noah, "transactions_sell_prepare_post");
+    assert_eq!(
+        prepared[0].as_ref().unwrap()["Form"],
+        json!({ "IBAN": "DE89370400440532013000", "AccountType": "Checking" })
+    );
+    let sold = requests(&noah, "transactions_sell_post");
+    assert_eq!(sold[0].as_ref().unwrap()["Nonce"], json!(receipt.nonce));
+    assert_eq!(
+        sold[0].as_ref().unwrap()["CryptoAuthorizedAmount"],
+        json!(receipt.quote.prepared().crypto_authorized_amount)
+    );
+}
+
+#[tokio::test]
+async fn test_refuses_to_sell_outside_the_bounds() {
+    let noah = MockNoah::start().await.unwrap();
+    let config = noah.configuration();
+
+    let error = SellFlow::new(&config, "USDC_TEST", "92")
+        .fiat_currency("EUR")
+        .max_fee(Decimal::new(5, 1))
+        .run()
+        .await
+        .unwrap_err();
+    assert!(matches!(error, SellError::FeeTooHigh { .. }), "{error}");
+
+    // The channel rate rose between quoting and executing
+    let flow = SellFlow::new(&config, "USDC_TEST", "92")
+        .channel(eur_channel(&noah))
+        .max_slippage_bps(50);
+    let quote = flow.quote().await.unwrap();
+    {
+        let mut state = noah.state();
+        let eur = state.channels.iter_mut().find(|c| c.fiat_currency == "EUR");
+        eur.unwrap().rate = "0.95".to_string();
+    }
+    let error = flow.execute(quote).await.unwrap_err();
+    match error {
+        SellError::Slippage {
+            current,
+            bps,
+            max_bps,
+            ..
+        } => {
+            assert_eq!(current, Decimal::new(95, 2));
+            assert_eq!(bps.to_string(), "315.79");
+            assert_eq!(max_bps, 50);
+        }
+        other => panic!("unexpected {other}"),
+    }
+
+    let error = SellFlow::new(&config, "USDC_TEST", "1000000")
+        .fiat_currency("EUR")
+        .run()
+        .await
+        .unwrap_err();
+    assert!(matches!(error, SellError::NoChannel { .. }), "{error}");
+    assert_eq!(requests(&noah, "transactions_sell_prepare_post").len(), 2);
+    assert!(requests(&noah, "transactions_sell_post").is_empty());
+}
+
+#[tokio::test]
+async fn test_nonce_makes_the_sell_idempotent() {
+    let noah = MockNoah::start().await.unwrap();
+    let config = noah.configuration();
+    noah.state()
+        .channels
+        .iter_mut()
+        .find(|c| c.fiat_currency == "EUR")
+        .unwrap()
+        .form_schema = Some(Box::new(models::FormSchema {
+        required: Some(vec!["IBAN".to_string()]),
+        ..Default::default()
+    }));
+
+    let error = SellFlow::new(&config, "USDC_TEST", "92")
+        .fiat_currency("EUR")
+        .run()
+        .await
+        .unwrap_err();
+    match error {
+        SellError::MissingFormFields(fields) => assert_eq!(fields, ["IBAN"]),
+        other => panic!("unexpected {other}"),
+    }
+
+    let flow = SellFlow::new(&config, "USDC_TEST", "92")
+        .fiat_currency("EUR")
+        .form_field("IBAN", "DE89370400440532013000")
+        .nonce("payout-42");
+    let first = flow.clone().run().await.unwrap();
+    let second = flow.run().await.unwrap();
+    assert_eq!(first.transaction.id, second.transaction.id);
+    assert_eq!(noah.state().transactions.len(), 1);
+}
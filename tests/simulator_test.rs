@@ -0,0 +1,171 @@
+//! Tests for the sandbox simulator
+
+use noah_sdk::apis::{onboarding_api, payin_api, payout_api, utilities_api};
+use noah_sdk::models::{self, FiatDepositSimulateRequest, FiatDepositStatus, TransactionStatus};
+use noah_sdk::testing::{MockNoah, Simulator, SimulatorError};
+
+async fn bank_deposit_method(noah: &MockNoah) -> String {
+    let config = noah.configuration();
+    let customer = models::CustomerInput::Individual(Box::default());
+    onboarding_api::customers_customer_id_put(&config, "cust-1", customer, None)
+        .await
+        .unwrap();
+    let workflow = payin_api::workflows_bank_deposit_to_onchain_address_post(
+        &config,
+        models::BankDepositToOnchainAddressRequest {
+            customer_id: "cust-1".to_string(),
+            fiat_currency: "EUR".to_string(),
+            crypto_currency: "USDC_TEST".to_string(),
+            network: "EthereumTestSepolia".to_string(),
+            ..Default::default()
+        },
+        None,
+    )
+    .await
+    .unwrap();
+    workflow.payment_method_id
+}
+
+#[tokio::test]
+async fn test_settled_deposit_fires_webhooks_and_credits_balance() {
+    let noah = MockNoah::start().await.unwrap();
+    let payment_method_id = bank_deposit_method(&noah).await;
+
+    let mut receiver = mockito::Server::new_async().await;
+    let hook = receiver
+        .mock("POST", "/webhooks")
+        .match_header("Webhook-Signature", "signed")
+        .with_status(200)
+        .expect(3)
+        .create_async()
+        .await;
+    let simulator = Simulator::mock(&noah)
+        .webhook_url(format!("{}/webhooks", receiver.url()))
+        .webhook_signer(|_| "signed".to_string());
+
+    let deposit = simulator
+        .deposit(FiatDepositSimulateRequest::new(
+            payment_method_id,
+            "92".to_string(),
+            "EUR".to_string(),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(deposit.status, FiatDepositStatus::Pending);
+    assert_eq!(deposit.customer_id.as_deref(), Some("cust-1"));
+
+    let settled = simulator.settle_deposit(&deposit.id).await.unwrap();
+    assert_eq!(settled.status, FiatDepositStatus::Settled);
+    hook.assert_async().await;
+
+    let webhooks = simulator.webhooks();
+    let event_types: Vec<_> = webhooks.iter().map(|w| w.event_type.as_str()).collect();
+    assert_eq!(event_types, ["FiatDeposit", "FiatDeposit", "Transaction"]);
+    let models::WebhookData::Transaction(ref transaction) = *webhooks[2].data else {
+        panic!("expected transaction data");
+    };
+    assert_eq!(transaction.direction, models::TransactionDirection::In);
+    assert_eq!(transaction.amount.as_deref(), Some("100"));
+
+    let config = noah.configuration();
+    let balances = utilities_api::balances_get(&config, None, None, None)
+        .await
+        .unwrap();
+    assert_eq!(balances.items[0].available, "1100.00");
+}
+
+#[tokio::test]
+async fn test_only_settled_deposits_refund() {
+    let noah = MockNoah::start().await.unwrap();
+    let payment_method_id = bank_deposit_method(&noah).await;
+    let simulator = Simulator::mock(&noah);
+    let deposit = || {
+        simulator.deposit(FiatDepositSimulateRequest::new(
+            payment_method_id.clone(),
+            "50".to_string(),
+            "EUR".to_string(),
+        ))
+    };
+
+    let failed = deposit().await.unwrap();
+    assert!(matches!(
+        simulator.refund_deposit(&failed.id, "10").await,
+        Err(SimulatorError::InvalidRefund { .. })
+    ));
+    simulator.fail_deposit(&failed.id).await.unwrap();
+    assert!(matches!(
+        simulator.settle_deposit(&failed.id).await,
+        Err(SimulatorError::InvalidTransition { .. })
+    ));
+    let error = simulator
+        .refund_deposit(&failed.id, "10")
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        format!("cannot refund {}: the deposit is Failed", failed.id)
+    );
+
+    let deposit = deposit().await.unwrap();
+    simulator.settle_deposit(&deposit.id).await.unwrap();
+    let refund = simulator.refund_deposit(&deposit.id, "30").await.unwrap();
+    assert_eq!(refund.refunded_amount.amount, "30");
+    assert!(simulator
+        .refund_deposit(&deposit.id, "20.01")
+        .await
+        .is_err());
+    simulator.refund_deposit(&deposit.id, "20").await.unwrap();
+
+    let state = noah.state();
+    assert!(state.fiat_deposits[0].refunds.is_empty());
+    assert_eq!(state.fiat_deposits[1].refunds.len(), 2);
+}
+
+#[tokio::test]
+async fn test_sell_lifecycle() {
+    let noah = MockNoah::start().await.unwrap();
+    let config = noah.configuration();
+    let simulator = Simulator::mock(&noah);
+    let channel_id = noah.state().channels[0].id.parse().unwrap();
+
+    let mut sells = Vec::new();
+    for nonce in ["sell-1", "sell-2"] {
+        let prepared = payout_api::transactions_sell_prepare_post(
+            &config,
+            models::PrepareSellRequest {
+                channel_id,
+                crypto_currency: "USDC_TEST".to_string(),
+                fiat_amount: "92".to_string(),
+                ..Default::default()
+            },
+            None,
+        )
+        .await
+        .unwrap();
+        let sell = models::SellRequest {
+            crypto_currency: "USDC_TEST".to_string(),
+            fiat_amount: "92".to_string(),
+            crypto_authorized_amount: prepared.crypto_authorized_amount,
+            form_session_id: prepared.form_session_id,
+            nonce: nonce.to_string(),
+            external_id: None,
+        };
+        let response = payout_api::transactions_sell_post(&config, sell, None)
+            .await
+            .unwrap();
+        sells.push(response.transaction.id.to_string());
+    }
+
+    let settled = simulator.settle_transaction(&sells[0]).await.unwrap();
+    assert_eq!(settled.status, TransactionStatus::Settled);
+    simulator.fail_transaction(&sells[1]).await.unwrap();
+
+    let balance = noah.state().balances[0].clone();
+    assert_eq!(balance.available, "899.00");
+    assert_eq!(balance.total, "899.00");
+
+    let transaction = utilities_api::transactions_transaction_id_get(&config, &sells[1], None)
+        .await
+        .unwrap();
+    assert_eq!(transaction.status, TransactionStatus::Failed);
+}
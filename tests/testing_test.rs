@@ -0,0 +1,174 @@
+//! Tests for the mock Noah server
+
+use std::time::Duration;
+
+use noah_sdk::apis::{onboarding_api, payout_api, utilities_api, Error};
+use noah_sdk::models::{self, SortDirection};
+use noah_sdk::testing::{Failure, MockNoah, StatusCode};
+
+fn individual(first_name: &str) -> models::CustomerInput {
+    models::CustomerInput::Individual(Box::new(models::IndividualCustomerInput {
+        full_name: Box::new(models::FullName {
+            first_name: first_name.to_string(),
+            last_name: "Doe".to_string(),
+            middle_name: None,
+        }),
+        date_of_birth: "1990-01-01".to_string(),
+        primary_residence: Box::new(models::StreetAddress {
+            street: "1 Main St".to_string(),
+            city: "Berlin".to_string(),
+            post_code: "10115".to_string(),
+            state: "BE".to_string(),
+            country: "DE".to_string(),
+            ..Default::default()
+        }),
+        ..Default::default()
+    }))
+}
+
+#[tokio::test]
+async fn test_customers_are_stored_and_paginated() {
+    let noah = MockNoah::start().await.unwrap();
+    let config = noah.configuration();
+
+    for (id, name) in [("cust-1", "Jane"), ("cust-2", "John"), ("cust-3", "Jim")] {
+        onboarding_api::customers_customer_id_put(&config, id, individual(name), None)
+            .await
+            .unwrap();
+    }
+
+    let customer = utilities_api::customers_customer_id_get(&config, "cust-2")
+        .await
+        .unwrap();
+    match customer {
+        models::Customer::Individual(customer) => {
+            assert_eq!(customer.full_name.first_name, "John");
+            assert_eq!(
+                customer.verification.status,
+                models::verification::Status::Approved
+            );
+        }
+        other => panic!("expected an individual customer, got {other:?}"),
+    }
+
+    let first =
+        utilities_api::customers_get(&config, Some(2), None, Some(SortDirection::Asc), None)
+            .await
+            .unwrap();
+    assert_eq!(first.items.len(), 2);
+    let rest = utilities_api::customers_get(
+        &config,
+        Some(2),
+        first.page_token.as_deref(),
+        Some(SortDirection::Asc),
+        None,
+    )
+    .await
+    .unwrap();
+    assert_eq!(rest.items.len(), 1);
+    assert_eq!(rest.page_token, None);
+
+    let missing = utilities_api::customers_customer_id_get(&config, "nobody").await;
+    assert!(matches!(
+        missing,
+        Err(Error::ResponseError(ref e)) if e.status == StatusCode::NOT_FOUND
+    ));
+}
+
+#[tokio::test]
+async fn test_sell_debits_balance_once_per_nonce() {
+    let noah = MockNoah::start().await.unwrap();
+    let config = noah.configuration();
+
+    let channels = utilities_api::channels_sell_get(
+        &config,
+        "USDC_TEST",
+        Some("DE"),
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+    )
+    .await
+    .unwrap();
+    let channel = &channels.items[0];
+    assert_eq!(channel.fiat_currency, "EUR");
+
+    let prepared = payout_api::transactions_sell_prepare_post(
+        &config,
+        models::PrepareSellRequest {
+            channel_id: channel.id.parse().unwrap(),
+            crypto_currency: "USDC_TEST".to_string(),
+            fiat_amount: "92".to_string(),
+            ..Default::default()
+        },
+        None,
+    )
+    .await
+    .unwrap();
+    assert_eq!(prepared.crypto_amount_estimate, "100");
+    assert_eq!(prepared.crypto_authorized_amount, "101.00");
+
+    let sell = models::SellRequest {
+        crypto_currency: "USDC_TEST".to_string(),
+        fiat_amount: "92".to_string(),
+        crypto_authorized_amount: prepared.crypto_authorized_amount.clone(),
+        form_session_id: prepared.form_session_id,
+        nonce: "nonce-1".to_string(),
+        external_id: Some("payout-1".to_string()),
+    };
+    let first = payout_api::transactions_sell_post(&config, sell.clone(), None)
+        .await
+        .unwrap();
+    let retried = payout_api::transactions_sell_post(&config, sell, None)
+        .await
+        .unwrap();
+    assert_eq!(first.transaction.id, retried.transaction.id);
+    assert_eq!(first.transaction.status, models::TransactionStatus::Pending);
+
+    let balances = utilities_api::balances_get(&config, None, None, None)
+        .await
+        .unwrap();
+    assert_eq!(balances.items[0].available, "899.00");
+    assert_eq!(balances.items[0].total, "1000.00");
+
+    let id = first.transaction.id.to_string();
+    let transaction = utilities_api::transactions_transaction_id_get(&config, &id, None)
+        .await
+        .unwrap();
+    assert_eq!(transaction.external_id.as_deref(), Some("payout-1"));
+    assert_eq!(noah.state().transactions.len(), 1);
+}
+
+#[tokio::test]
+async fn test_injected_failures_and_auth() {
+    let noah = MockNoah::start().await.unwrap();
+    let mut config = noah.configuration();
+    config.retry.max_retries = 2;
+    config.retry.initial_backoff = Duration::from_millis(1);
+
+    noah.inject(Failure::status("balances_get", StatusCode::SERVICE_UNAVAILABLE).times(2));
+    let balances = utilities_api::balances_get(&config, None, None, None)
+        .await
+        .unwrap();
+    assert_eq!(balances.items.len(), 1);
+    assert_eq!(noah.state().requests.len(), 3);
+
+    noah.inject(Failure::status("*", StatusCode::BAD_REQUEST).detail("bad things"));
+    let err = utilities_api::balances_get(&config, None, None, None)
+        .await
+        .unwrap_err();
+    match err {
+        Error::ResponseError(e) => assert!(e.content.contains("bad things")),
+        other => panic!("unexpected error: {other}"),
+    }
+
+    config.api_key = None;
+    let err = utilities_api::balances_get(&config, None, None, None)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::ResponseError(ref e) if e.status == StatusCode::UNAUTHORIZED));
+}
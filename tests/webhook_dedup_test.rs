@@ -0,0 +1,149 @@
+//! Tests for webhook deduplication
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use noah_sdk::models::{Transaction, TransactionStatus};
+use noah_sdk::testing::fixtures;
+use noah_sdk::webhooks::{
+    DedupStore, Event, EventData, EventKey, FileStore, MemoryStore, Outcome, WebhookRouter,
+};
+
+const TRANSACTION_ID: uuid::Uuid = uuid::Uuid::from_u128(0x3fa85f64_5717_4562_b3fc_2c963f66afa6);
+
+fn event(status: TransactionStatus, occurred: &str) -> Event {
+    Event {
+        event_version: 1,
+        occurred: occurred.to_string(),
+        user_id: "user-1".to_string(),
+        data: EventData::Transaction(Box::new(Transaction {
+            status,
+            id: TRANSACTION_ID,
+            external_id: None,
+            ..fixtures::transaction()
+        })),
+    }
+}
+
+#[test]
+fn test_event_key_identifies_deliveries() {
+    let settled = event(TransactionStatus::Settled, "2024-05-01T12:00:00Z");
+    let key = EventKey::of(&settled);
+    assert!(key
+        .as_str()
+        .starts_with("Transaction:3fa85f64-5717-4562-b3fc-2c963f66afa6:2024-05-01T12:00:00Z:"));
+    assert_eq!(key, EventKey::of(&settled.clone()));
+    // Same entity and timestamp, different payload
+    assert_ne!(
+        key,
+        EventKey::of(&event(TransactionStatus::Failed, "2024-05-01T12:00:00Z"))
+    );
+    assert_ne!(
+        key,
+        EventKey::of(&event(TransactionStatus::Settled, "2024-05-01T12:00:01Z"))
+    );
+
+    let store = MemoryStore::new(2);
+    let keys: Vec<_> = ["a", "b", "c"]
+        .iter()
+        .map(|occurred| EventKey::of(&event(TransactionStatus::Settled, occurred)))
+        .collect();
+    assert!(store.insert(&keys[0]).unwrap());
+    assert!(store.insert(&keys[1]).unwrap());
+    assert!(!store.insert(&keys[0]).unwrap());
+    // The least recently seen key makes room
+    assert!(store.insert(&keys[2]).unwrap());
+    assert_eq!(store.len(), 2);
+    assert!(!store.insert(&keys[0]).unwrap());
+    assert!(store.insert(&keys[1]).unwrap());
+}
+
+#[tokio::test]
+async fn test_router_runs_handlers_once() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let counter = calls.clone();
+    let router = WebhookRouter::new()
+        .on(move |transaction: Transaction| {
+            let calls = counter.clone();
+            async move {
+                calls.fetch_add(1, Ordering::SeqCst);
+                match transaction.status {
+                    TransactionStatus::Failed => Err("ledger unavailable"),
+                    _ => Ok(()),
+                }
+            }
+        })
+        .deduplicate(MemoryStore::new(100));
+
+    let settled = event(TransactionStatus::Settled, "2024-05-01T12:00:00Z");
+    assert!(matches!(
+        router.dispatch(settled.clone()).await,
+        Outcome::Handled
+    ));
+    let outcome = router.dispatch(settled).await;
+    assert!(matches!(outcome, Outcome::Duplicate));
+    assert!(outcome.is_ack());
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    // A failed event is handled again when Noah redelivers it
+    let failed = event(TransactionStatus::Failed, "2024-05-01T12:00:00Z");
+    assert!(!router.dispatch(failed.clone()).await.is_ack());
+    assert!(!router.dispatch(failed).await.is_ack());
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+}
+
+#[test]
+fn test_file_store_survives_restarts() {
+    let path = std::env::temp_dir().join(format!("noah-dedup-{}.log", uuid::Uuid::new_v4()));
+    let settled = EventKey::of(&event(TransactionStatus::Settled, "2024-05-01T12:00:00Z"));
+    let failed = EventKey::of(&event(TransactionStatus::Failed, "2024-05-01T12:00:00Z"));
+
+    let store = FileStore::open(&path).unwrap();
+    assert!(store.insert(&settled).unwrap());
+    assert!(store.insert(&failed).unwrap());
+    store.remove(&failed).unwrap();
+    assert!(!store.insert(&settled).unwrap());
+    drop(store);
+
+    let store = FileStore::open(&path).unwrap();
+    assert!(!store.insert(&settled).unwrap());
+    assert!(store.insert(&failed).unwrap());
+    drop(store);
+    assert_eq!(std::fs::read_to_string(&path).unwrap().lines().count(), 2);
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_file_store_forgets_keys_after_the_ttl() {
+    let path = std::env::temp_dir().join(format!("noah-dedup-{}.log", uuid::Uuid::new_v4()));
+    let pending = EventKey::of(&event(TransactionStatus::Pending, "2024-05-01T11:00:00Z"));
+    let settled = EventKey::of(&event(TransactionStatus::Settled, "2024-05-01T12:00:00Z"));
+    // A key written before keys had times
+    std::fs::write(&path, format!("{pending}\n")).unwrap();
+    // Compacting must not write over another file with the same stem
+    let sibling = path.with_extension("tmp");
+    std::fs::write(&sibling, "other").unwrap();
+
+    let store = FileStore::open(&path)
+        .unwrap()
+        .ttl(Duration::from_millis(100));
+    assert!(!store.insert(&pending).unwrap());
+    std::thread::sleep(Duration::from_millis(150));
+    assert!(store.insert(&settled).unwrap());
+    let contents = std::fs::read_to_string(&path).unwrap();
+    assert_eq!(contents.lines().count(), 1);
+    assert!(contents.ends_with(&format!(" {settled}\n")));
+
+    std::thread::sleep(Duration::from_millis(150));
+    // Inserting compacted the file again, so only the new key is left
+    assert!(store.insert(&pending).unwrap());
+    assert_eq!(store.compact().unwrap(), 0);
+    drop(store);
+    let store = FileStore::open(&path).unwrap();
+    assert!(!store.insert(&pending).unwrap());
+    assert!(store.insert(&settled).unwrap());
+    assert_eq!(std::fs::read_to_string(&sibling).unwrap(), "other");
+    std::fs::remove_file(path).unwrap();
+    std::fs::remove_file(sibling).unwrap();
+}
@@ -0,0 +1,199 @@
+//! Tests for the webhook event router
+
+use std::sync::{Arc, Mutex};
+
+use axum::extract::{FromRef, State};
+use axum::routing::post;
+use axum::Router;
+use noah_sdk::models::{self, FiatDeposit, FiatDepositStatus, Transaction, TransactionStatus};
+use noah_sdk::testing::{fixtures, MockNoah, Simulator};
+use noah_sdk::webhooks::{
+    Event, EventData, NoahWebhook, Outcome, VerifySignature, WebhookError, WebhookRouter,
+    WebhookVerifier,
+};
+
+type Log = Arc<Mutex<Vec<String>>>;
+
+fn event(data: EventData) -> Event {
+    Event {
+        event_version: 1,
+        occurred: "2024-05-01T12:00:00Z".to_string(),
+        user_id: "user-1".to_string(),
+        data,
+    }
+}
+
+fn transaction(status: TransactionStatus) -> Event {
+    event(EventData::Transaction(Box::new(Transaction {
+        status,
+        ..fixtures::transaction()
+    })))
+}
+
+fn router(log: &Log) -> WebhookRouter {
+    let (settled, any, fallback) = (log.clone(), log.clone(), log.clone());
+    WebhookRouter::new()
+        .on_transaction_status(TransactionStatus::Settled, move |t: Transaction| {
+            let log = settled.clone();
+            async move {
+                log.lock().unwrap().push(format!("settled {}", t.status));
+                Ok::<_, WebhookError>(())
+            }
+        })
+        .on(move |t: Transaction| {
+            let log = any.clone();
+            async move {
+                log.lock()
+                    .unwrap()
+                    .push(format!("transaction {}", t.status));
+                Ok::<_, WebhookError>(())
+            }
+        })
+        .on_fiat_deposit_status(FiatDepositStatus::Failed, |_: FiatDeposit| async {
+            Err("ledger unavailable")
+        })
+        .fallback(move |event: Event| {
+            let log = fallback.clone();
+            async move {
+                log.lock()
+                    .unwrap()
+                    .push(format!("fallback {}", event.event_type()));
+                Ok::<_, WebhookError>(())
+            }
+        })
+}
+
+#[tokio::test]
+async fn test_routes_by_type_status_and_order() {
+    let log = Log::default();
+    let router = router(&log);
+
+    let outcome = router
+        .dispatch(transaction(TransactionStatus::Settled))
+        .await;
+    assert!(matches!(outcome, Outcome::Handled));
+    router
+        .dispatch(transaction(TransactionStatus::Pending))
+        .await;
+    router
+        .dispatch(event(EventData::CheckoutSession(Box::new(
+            fixtures::checkout_session(),
+        ))))
+        .await;
+    assert_eq!(
+        *log.lock().unwrap(),
+        [
+            "settled Settled",
+            "transaction Pending",
+            "fallback CheckoutSession"
+        ]
+    );
+
+    let bare = WebhookRouter::new().on(|_: models::Customer| async { Ok::<_, WebhookError>(()) });
+    let outcome = bare.dispatch(transaction(TransactionStatus::Settled)).await;
+    assert!(matches!(outcome, Outcome::Ignored));
+    assert!(outcome.is_ack());
+}
+
+#[tokio::test]
+async fn test_failed_handler_asks_for_redelivery() {
+    let log = Log::default();
+    let router = router(&log);
+    let failed = FiatDeposit {
+        status: FiatDepositStatus::Failed,
+        ..fixtures::fiat_deposit()
+    };
+
+    let outcome = router
+        .dispatch(event(EventData::FiatDeposit(Box::new(failed))))
+        .await;
+    assert!(!outcome.is_ack());
+    assert_eq!(outcome.status().as_u16(), 500);
+    assert_eq!(outcome.to_string(), "failed: ledger unavailable");
+
+    // Deposits in other statuses fall through to the fallback
+    let outcome = router
+        .dispatch(event(EventData::FiatDeposit(Box::new(
+            fixtures::fiat_deposit(),
+        ))))
+        .await;
+    assert_eq!(outcome.status().as_u16(), 200);
+    assert_eq!(*log.lock().unwrap(), ["fallback FiatDeposit"]);
+}
+
+#[derive(Clone)]
+struct AppState {
+    verifier: WebhookVerifier,
+    router: Arc<WebhookRouter>,
+}
+
+impl FromRef<AppState> for WebhookVerifier {
+    fn from_ref(state: &AppState) -> WebhookVerifier {
+        state.verifier.clone()
+    }
+}
+
+async fn receive(State(state): State<AppState>, NoahWebhook(event): NoahWebhook) -> Outcome {
+    state.router.dispatch(event).await
+}
+
+struct Trusted;
+
+impl VerifySignature for Trusted {
+    fn verify(&self, _: &[u8], _: &str) -> Result<(), WebhookError> {
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_router_behind_axum() {
+    let noah = MockNoah::start().await.unwrap();
+    let log = Log::default();
+    let app = Router::new()
+        .route("/webhooks", post(receive))
+        .with_state(AppState {
+            verifier: WebhookVerifier::new(Trusted),
+            router: Arc::new(router(&log)),
+        });
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/webhooks", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    let config = noah.configuration();
+    let channel_id = noah.state().channels[0].id.parse().unwrap();
+    let prepared = noah_sdk::apis::payout_api::transactions_sell_prepare_post(
+        &config,
+        models::PrepareSellRequest {
+            channel_id,
+            crypto_currency: "USDC_TEST".to_string(),
+            fiat_amount: "92".to_string(),
+            ..Default::default()
+        },
+        None,
+    )
+    .await
+    .unwrap();
+    let sell = noah_sdk::apis::payout_api::transactions_sell_post(
+        &config,
+        models::SellRequest {
+            crypto_currency: "USDC_TEST".to_string(),
+            fiat_amount: "92".to_string(),
+            crypto_authorized_amount: prepared.crypto_authorized_amount,
+            form_session_id: prepared.form_session_id,
+            nonce: "sell-1".to_string(),
+            external_id: None,
+        },
+        None,
+    )
+    .await
+    .unwrap();
+
+    let simulator = Simulator::mock(&noah)
+        .webhook_url(url)
+        .webhook_signer(|_| "trusted".to_string());
+    simulator
+        .settle_transaction(&sell.transaction.id.to_string())
+        .await
+        .unwrap();
+    assert_eq!(*log.lock().unwrap(), ["settled Settled"]);
+}
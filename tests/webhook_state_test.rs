@@ -0,0 +1,131 @@
+//! Tests for out-of-order webhook reconciliation
+
+use std::sync::{Arc, Mutex};
+
+use noah_sdk::models::{
+    CheckoutSession, FiatDeposit, FiatDepositStatus, Transaction, TransactionStatus,
+};
+use noah_sdk::testing::fixtures;
+use noah_sdk::webhooks::{
+    Event, EventData, Outcome, StateTracker, Status, Transition, WebhookRouter,
+};
+
+fn event(data: EventData, occurred: &str) -> Event {
+    Event {
+        event_version: 1,
+        occurred: occurred.to_string(),
+        user_id: "user-1".to_string(),
+        data,
+    }
+}
+
+fn transaction(base: &Transaction, status: TransactionStatus, occurred: &str) -> Event {
+    let transaction = Transaction {
+        status,
+        ..base.clone()
+    };
+    event(EventData::Transaction(Box::new(transaction)), occurred)
+}
+
+#[test]
+fn test_final_states_win_over_late_updates() {
+    let tracker = StateTracker::new();
+    let base = fixtures::transaction();
+    let id = base.id.to_string();
+
+    let settled = transaction(&base, TransactionStatus::Settled, "2024-05-01T12:05:00Z");
+    assert_eq!(
+        tracker.observe_event(&settled),
+        Some(Transition::Applied { previous: None })
+    );
+    // The Pending update was sent first but arrives last
+    let pending = transaction(&base, TransactionStatus::Pending, "2024-05-01T12:00:00Z");
+    let current = Status::Transaction(TransactionStatus::Settled);
+    assert_eq!(
+        tracker.observe_event(&pending),
+        Some(Transition::Stale {
+            current: current.clone()
+        })
+    );
+    let failed = transaction(&base, TransactionStatus::Failed, "2024-05-01T12:06:00Z");
+    assert_eq!(
+        tracker.observe_event(&failed),
+        Some(Transition::Conflict { current })
+    );
+    assert_eq!(tracker.observe_event(&settled), Some(Transition::Unchanged));
+    assert_eq!(tracker.transaction(&id), Some(TransactionStatus::Settled));
+
+    // Deposits are tracked separately, even under the same ID
+    let deposit = FiatDeposit {
+        id: id.clone(),
+        ..fixtures::fiat_deposit()
+    };
+    let deposit = event(
+        EventData::FiatDeposit(Box::new(deposit)),
+        "2024-05-01T12:00:00Z",
+    );
+    assert!(tracker.observe_event(&deposit).unwrap().is_current());
+    assert_eq!(tracker.fiat_deposit(&id), Some(FiatDepositStatus::Pending));
+    let customer = event(EventData::Customer(Box::default()), "2024-05-01T12:00:00Z");
+    assert_eq!(tracker.observe_event(&customer), None);
+}
+
+#[test]
+fn test_unknown_checkout_statuses_are_ordered_by_occurred() {
+    let tracker = StateTracker::new();
+    let session = fixtures::checkout_session();
+    let id = session.checkout_session_id.clone();
+    let observe = |status: &str, occurred: &str| {
+        tracker.observe(&id, Status::CheckoutSession(status.to_string()), occurred)
+    };
+
+    assert!(observe("Pending", "2024-05-01T12:00:00Z").is_current());
+    assert!(observe("Authorized", "2024-05-01T12:01:00.5+00:00").is_current());
+    assert_eq!(
+        observe("Reviewing", "2024-05-01T12:01:00Z"),
+        Transition::Stale {
+            current: Status::CheckoutSession("Authorized".to_string())
+        }
+    );
+    assert_eq!(
+        observe("Settled", "2024-05-01T13:30:00+02:00"),
+        Transition::Stale {
+            current: Status::CheckoutSession("Authorized".to_string())
+        }
+    );
+    assert!(observe("Settled", "2024-05-01T12:02:00Z").is_current());
+    assert!(Status::CheckoutSession("Settled".to_string()).is_final());
+    assert_eq!(tracker.checkout_session(&id).as_deref(), Some("Settled"));
+    assert_eq!(tracker.transaction(&id), None);
+}
+
+#[tokio::test]
+async fn test_router_drops_stale_events() {
+    let tracker = Arc::new(StateTracker::new());
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let log = seen.clone();
+    let router = WebhookRouter::new()
+        .on(move |transaction: Transaction| {
+            let log = log.clone();
+            async move {
+                log.lock().unwrap().push(transaction.status);
+                Ok::<_, std::io::Error>(())
+            }
+        })
+        .on(|_: CheckoutSession| async { Ok::<_, std::io::Error>(()) })
+        .reconcile(tracker.clone());
+    let base = fixtures::transaction();
+
+    let settled = transaction(&base, TransactionStatus::Settled, "2024-05-01T12:05:00Z");
+    assert!(matches!(router.dispatch(settled).await, Outcome::Handled));
+    let pending = transaction(&base, TransactionStatus::Pending, "2024-05-01T12:00:00Z");
+    let outcome = router.dispatch(pending).await;
+    assert!(matches!(outcome, Outcome::Stale));
+    assert!(outcome.is_ack());
+
+    assert_eq!(*seen.lock().unwrap(), [TransactionStatus::Settled]);
+    assert_eq!(
+        tracker.transaction(&base.id.to_string()),
+        Some(TransactionStatus::Settled)
+    );
+}